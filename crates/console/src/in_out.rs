@@ -4,11 +4,19 @@ use std::{
 };
 
 use calc_core::config::Config;
-use interpreter::{errors::CalcError, interpreter::Interpreter};
+use interpreter::{
+    config::{AngleUnit, NumberMode},
+    errors::CalcError,
+    integer::Base,
+    interpreter::Interpreter,
+};
 use lazy_static::lazy_static;
 use regex::{Captures, Regex};
 
-use crate::printer::{print_error, print_help, print_start, Printer, Table};
+use crate::printer::{
+    print_angle_unit, print_error, print_help, print_number_mode, print_output_base, print_start,
+    Printer, Table,
+};
 
 pub enum MessageIO<T> {
     Break,
@@ -24,6 +32,21 @@ lazy_static! {
         Config::get().lock().unwrap().commands.history.clone()
     ))
     .unwrap();
+    static ref RE_ANGLE: Regex = Regex::new(&format!(
+        r"^{}(?:\s+(\w+))?$",
+        Config::get().lock().unwrap().commands.angle.clone()
+    ))
+    .unwrap();
+    static ref RE_MODE: Regex = Regex::new(&format!(
+        r"^{}(?:\s+(\w+)(?:\s+(\d+))?)?$",
+        Config::get().lock().unwrap().commands.mode.clone()
+    ))
+    .unwrap();
+    static ref RE_BASE: Regex = Regex::new(&format!(
+        r"^{}(?:\s+(\w+))?$",
+        Config::get().lock().unwrap().commands.base.clone()
+    ))
+    .unwrap();
     static ref RE_HELP: String = Config::get().lock().unwrap().commands.help.clone();
     static ref OUTPUT_LINE_HISTORY: usize = Config::get().lock().unwrap().output_line_history;
 }
@@ -46,6 +69,12 @@ pub fn handle_command(interpreter: &mut Interpreter, string: &str) -> MessageIO<
         _ => {
             if let Some(capt) = RE_GET_HISTORY.captures(string) {
                 handler_arg_history(interpreter, &capt)
+            } else if let Some(capt) = RE_ANGLE.captures(string) {
+                handler_arg_angle(interpreter, &capt)
+            } else if let Some(capt) = RE_MODE.captures(string) {
+                handler_arg_mode(interpreter, &capt)
+            } else if let Some(capt) = RE_BASE.captures(string) {
+                handler_arg_base(interpreter, &capt)
             } else {
                 MessageIO::Ok(string.to_string())
             }
@@ -62,6 +91,69 @@ pub fn handler_arg_history(interpreter: &mut Interpreter, capt: &Captures) -> Me
     MessageIO::Continue
 }
 
+/// Без аргумента печатает текущую единицу измерения углов, с аргументом - меняет её до конца сессии.
+pub fn handler_arg_angle(interpreter: &mut Interpreter, capt: &Captures) -> MessageIO<String> {
+    if let Some(arg) = capt.get(1) {
+        match AngleUnit::from_name(arg.as_str()) {
+            Ok(angle_unit) => interpreter.config.angle_unit = angle_unit,
+            Err(err) => {
+                print_error(err);
+                return MessageIO::Continue;
+            }
+        }
+    }
+    print_angle_unit(interpreter.config.angle_unit);
+    MessageIO::Continue
+}
+
+/// Без аргумента печатает текущий режим чисел, с аргументом - меняет его до конца сессии.
+/// Второй аргумент задаёт количество значащих цифр режима `big`.
+pub fn handler_arg_mode(interpreter: &mut Interpreter, capt: &Captures) -> MessageIO<String> {
+    if let Some(arg) = capt.get(1) {
+        match NumberMode::from_name(arg.as_str()) {
+            Ok(number_mode) => interpreter.config.number_mode = number_mode,
+            Err(err) => {
+                print_error(err);
+                return MessageIO::Continue;
+            }
+        }
+    }
+    if let Some(digits) = capt.get(2) {
+        match digits.as_str().parse::<u32>() {
+            Ok(digits) => {
+                let config = interpreter.config.with_digits(digits);
+                if let Err(err) = config.check_digits() {
+                    print_error(err);
+                    return MessageIO::Continue;
+                }
+                interpreter.config = config;
+            }
+            Err(_) => {
+                print_error(CalcError::InputTooBig);
+                return MessageIO::Continue;
+            }
+        }
+    }
+    print_number_mode(interpreter.config.number_mode, interpreter.config.digits);
+    MessageIO::Continue
+}
+
+/// Без аргумента печатает систему счисления целых результатов, с аргументом - меняет её
+/// до конца сессии.
+pub fn handler_arg_base(interpreter: &mut Interpreter, capt: &Captures) -> MessageIO<String> {
+    if let Some(arg) = capt.get(1) {
+        match Base::from_name(arg.as_str()) {
+            Ok(output_base) => interpreter.config.output_base = output_base,
+            Err(err) => {
+                print_error(err);
+                return MessageIO::Continue;
+            }
+        }
+    }
+    print_output_base(interpreter.config.output_base);
+    MessageIO::Continue
+}
+
 fn save_interpreter(interpreter: &mut Interpreter) {
     let serialized = serde_json::to_string(interpreter).unwrap();
 
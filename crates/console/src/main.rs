@@ -4,7 +4,7 @@ use calc_core::{get_ast, get_interpreter, get_result};
 use in_out::get_input;
 
 use crate::panic_hook::debug_panic_hook;
-use crate::{in_out::MessageIO, printer::print_error};
+use crate::{in_out::MessageIO, printer::print_diagnostics};
 
 mod in_out;
 mod panic_hook;
@@ -22,16 +22,16 @@ fn main() {
             MessageIO::Ok(input) => input,
         };
 
-        let ast = match get_ast(&input, print_error) {
+        let ast = match get_ast(&input, print_diagnostics) {
             Some(ast) => ast,
             None => continue,
         };
 
-        let result = match get_result(&mut interpreter, ast, &input, print_error) {
+        let result = match get_result(&mut interpreter, ast, &input, print_diagnostics) {
             Some(result) => result,
             None => continue,
         };
 
-        println!("{result}");
+        println!("{}", result.to_string_in(interpreter.config.output_base));
     }
 }
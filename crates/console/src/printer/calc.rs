@@ -2,7 +2,11 @@ use crossterm::{
     execute,
     style::{Print, ResetColor, SetForegroundColor},
 };
-use interpreter::errors::CalcError;
+use interpreter::{
+    config::{AngleUnit, NumberMode},
+    errors::{CalcError, Diagnostic},
+    integer::Base,
+};
 
 use crate::printer::color;
 
@@ -20,6 +24,121 @@ pub fn print_error(err: CalcError) {
     .unwrap();
 }
 
+/// Печатает все ошибки ввода `input` по очереди.
+pub fn print_diagnostics(input: &str, diagnostics: Vec<Diagnostic>) {
+    for diagnostic in diagnostics {
+        print_diagnostic(input, diagnostic);
+    }
+}
+
+/// Печатает ошибку, а под ней - ввод с отметкой `^~~~` под участком с ошибкой,
+/// ожидаемые на этом месте токены и подсказку, если они есть.
+fn print_diagnostic(input: &str, diagnostic: Diagnostic) {
+    let input = input.trim_end();
+    let marker = diagnostic.span.map(|span| {
+        let start = span.start.min(input.len());
+        let end = span.end.clamp(start, input.len());
+        let width = input[start..end].chars().count().max(1);
+        format!(
+            "{}^{}",
+            " ".repeat(input[..start].chars().count()),
+            "~".repeat(width - 1)
+        )
+    });
+    let hint = hint(&diagnostic);
+
+    print_error(diagnostic.error);
+
+    let mut stdout = std::io::stdout();
+    if let Some(marker) = marker {
+        execute!(
+            stdout,
+            Print(format!("    {input}\n")),
+            SetForegroundColor(color::RED_ERROR),
+            Print(format!("    {marker}\n")),
+            ResetColor,
+        )
+        .unwrap();
+    }
+    if !diagnostic.expected.is_empty() {
+        execute!(
+            stdout,
+            SetForegroundColor(color::CYAN),
+            Print(format!("Expected: {}\n", diagnostic.expected.join(", "))),
+            ResetColor,
+        )
+        .unwrap();
+    }
+    if let Some(hint) = hint {
+        execute!(
+            stdout,
+            SetForegroundColor(color::CYAN),
+            Print(format!("Hint: {hint}\n")),
+            ResetColor,
+        )
+        .unwrap();
+    }
+}
+
+/// Подсказывает, как исправить ввод с ошибкой.
+fn hint(diagnostic: &Diagnostic) -> Option<String> {
+    let expects = |token: &str| diagnostic.expected.iter().any(|expected| expected == token);
+
+    match &diagnostic.error {
+        CalcError::SyntaxError if expects(")") => Some("close the bracket with `)`".to_string()),
+        CalcError::SyntaxError if expects("number") => {
+            Some("a number, a variable or a bracket is missing here".to_string())
+        }
+        CalcError::SyntaxError if diagnostic.expected.is_empty() => {
+            Some("remove this symbol, the calculator does not know it".to_string())
+        }
+        CalcError::CallingNonexistentVariable(name) => Some(format!(
+            "create the variable first, for example: {name} = 1"
+        )),
+        CalcError::CallingNonexistentFunction(name) => Some(format!(
+            "define the function first, for example: {name}(x) = x"
+        )),
+        CalcError::IncorrectNumberOfArguments(..) => {
+            Some("arguments of a function are separated by `;`".to_string())
+        }
+        CalcError::HistoryEntryNotFound(_) => {
+            Some("the history has fewer results, see `/history`".to_string())
+        }
+        CalcError::ComplexNotSupported(_) => {
+            Some("take a real part first with re(), im(), abs() or arg()".to_string())
+        }
+        CalcError::NotAnInteger(_) => {
+            Some("drop the fractional part first, for example: x div 1".to_string())
+        }
+        CalcError::InvalidMatrix => {
+            Some("rows of a matrix are vectors of one length: [[1; 2]; [3; 4]]".to_string())
+        }
+        CalcError::MatrixNotSupported(_) => {
+            Some("pick an element first, for example: v[1]".to_string())
+        }
+        CalcError::NonlinearSystem => Some(
+            "add a starting guess to find a root numerically: solve(x^2 = 4; x; 1)".to_string(),
+        ),
+        CalcError::UnknownNotInEquations(_) => Some(
+            "the unknowns are the names used in the equations: solve(x^2 = 2; x; 1)".to_string(),
+        ),
+        CalcError::AccuracyNotReached(_) => Some(
+            "split the interval at a singular point: integrate(f; x; a; c) + integrate(f; x; c; b)"
+                .to_string(),
+        ),
+        CalcError::TooManyDigits(..) => {
+            Some("raise `max_digits` in the user config or choose fewer digits".to_string())
+        }
+        CalcError::TooManyTerms(..) | CalcError::TooManyNestedTerms(..) => {
+            Some("raise `max_terms` in the user config or split the range".to_string())
+        }
+        CalcError::ExpressionNotSupported(_) => {
+            Some("evaluate the derivative at a point: diff(x^2; x; 3)".to_string())
+        }
+        _ => None,
+    }
+}
+
 /// Печатает `>>>`, зелёным цветом.
 pub fn print_start() {
     execute!(
@@ -30,3 +149,40 @@ pub fn print_start() {
     )
     .unwrap();
 }
+
+/// Печатает текущую единицу измерения углов голубым цветом.
+pub fn print_angle_unit(angle_unit: AngleUnit) {
+    execute!(
+        std::io::stdout(),
+        SetForegroundColor(color::CYAN),
+        Print(format!("Angle unit: {angle_unit}\n")),
+        ResetColor,
+    )
+    .unwrap();
+}
+
+/// Печатает текущий режим чисел голубым цветом, а для режима `big` - и его точность.
+pub fn print_number_mode(number_mode: NumberMode, digits: u32) {
+    let digits = match number_mode {
+        NumberMode::Big => format!(", {digits} digits"),
+        _ => String::new(),
+    };
+    execute!(
+        std::io::stdout(),
+        SetForegroundColor(color::CYAN),
+        Print(format!("Number mode: {number_mode}{digits}\n")),
+        ResetColor,
+    )
+    .unwrap();
+}
+
+/// Печатает систему счисления целых результатов голубым цветом.
+pub fn print_output_base(output_base: Base) {
+    execute!(
+        std::io::stdout(),
+        SetForegroundColor(color::CYAN),
+        Print(format!("Output base: {output_base}\n")),
+        ResetColor,
+    )
+    .unwrap();
+}
@@ -8,7 +8,10 @@ mod table;
 
 use interpreter::interpreter::Interpreter;
 
-pub use calc::{print_error, print_start};
+pub use calc::{
+    print_angle_unit, print_diagnostics, print_error, print_number_mode, print_output_base,
+    print_start,
+};
 pub use help::print_help;
 pub use table::Table;
 
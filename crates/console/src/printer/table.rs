@@ -3,14 +3,17 @@ use crossterm::{
     execute,
     style::{Print, ResetColor, SetForegroundColor},
 };
-use interpreter::{errors::CalcError, history::History, interpreter::Interpreter};
-use rust_decimal::Decimal;
+use interpreter::{
+    errors::CalcError, history::History, integer::Base, interpreter::Interpreter, value::Value,
+};
 
-/// Получение длины самого большого элемента в `History` - Vec<(String, Result<Decimal, `CalcError`>)>.
-/// Cчетается даже длина для второй части - Result, преобразованный в тип String.
+/// Получение длины самого большого элемента в `History` - Vec<(String, Result<Value, `CalcError`>)>.
+/// Cчетается даже длина для второй части - Result, преобразованный в тип String
+/// в системе счисления `base`: запись матрицы бывает длиннее запроса.
 /// Не считается длина элемента, если он является ошибкой, то есть Err(_).
 ///
 /// * `history` - сама история, по которой будет идти поиск.
+/// * `base` - система счисления целых результатов.
 /// * `min_len` - минимальная значение для длины, которое должно вернуться.
 ///
 /// # Example
@@ -22,16 +25,16 @@ use rust_decimal::Decimal;
 ///     ("2*2".to_string(), Ok(4.0)),
 /// ];
 ///
-/// assert_eq!(get_len_of_longest_valid_element_in_history(&history, 5), 5);
-/// assert_eq!(get_len_of_longest_valid_element_in_history(&history, 1), 3);
+/// assert_eq!(get_len_of_longest_valid_element_in_history(&history, Base::Decimal, 5), 5);
+/// assert_eq!(get_len_of_longest_valid_element_in_history(&history, Base::Decimal, 1), 3);
 /// ```
 fn get_len_of_longest_valid_element_in_history(
-    history: &[(String, Result<Decimal, CalcError>)], min_len: usize,
+    history: &[(String, Result<Value, CalcError>)], base: Base, min_len: usize,
 ) -> usize {
     let max_len = history
         .iter()
         .filter_map(|(req_str, res)| match res {
-            Ok(_) => Some(req_str.len()),
+            Ok(res) => Some(req_str.len().max(res.to_string_in(base).chars().count())),
             Err(_) => None,
         })
         .max()
@@ -45,12 +48,14 @@ fn get_len_of_longest_valid_element_in_history(
 /// * `left_name` - имя левой колонки таблицы.
 /// * `right_name` - имя правой колонки таблицы.
 /// * `content` - содержимое таблицы.
+/// * `base` - система счисления целых результатов.
 #[derive(Clone)]
 pub struct Table<'a> {
     width: usize,
     left_name: String,
     right_name: String,
-    content: &'a Vec<(String, Result<Decimal, CalcError>)>,
+    content: &'a Vec<(String, Result<Value, CalcError>)>,
+    base: Base,
 }
 
 impl<'a> Table<'a> {
@@ -60,10 +65,11 @@ impl<'a> Table<'a> {
     /// * `right_name` - имя правой колонки таблицы.
     /// * `history` - содержимое таблицы, которое печатается в два столбика.
     pub fn new(
-        left_name: &str, right_name: &str, history: &'a Vec<(String, Result<Decimal, CalcError>)>,
+        left_name: &str, right_name: &str, history: &'a Vec<(String, Result<Value, CalcError>)>,
     ) -> Self {
         let width = get_len_of_longest_valid_element_in_history(
             history,
+            Base::default(),
             left_name.len() + right_name.len(),
         );
 
@@ -72,9 +78,21 @@ impl<'a> Table<'a> {
             content: history,
             left_name: left_name.to_string(),
             right_name: right_name.to_string(),
+            base: Base::default(),
         }
     }
 
+    /// Целые результаты показываются в системе счисления `base`.
+    pub fn with_base(mut self, base: Base) -> Self {
+        self.base = base;
+        self.width = get_len_of_longest_valid_element_in_history(
+            self.content,
+            base,
+            self.left_name.len() + self.right_name.len(),
+        );
+        self
+    }
+
     fn print_table_title(self) -> Self {
         let width = self.width;
         execute!(
@@ -119,14 +137,14 @@ impl<'a> Table<'a> {
         self
     }
 
-    pub fn print_table_line(self, res: Decimal, req_str: &str) -> Self {
+    pub fn print_table_line(self, res: &Value, req_str: &str) -> Self {
         let width = self.width;
         execute!(
             std::io::stdout(),
             SetForegroundColor(color::BLUE),
             Print("| "),
             SetForegroundColor(color::CYAN),
-            Print(format!("{res:^width$}",)),
+            Print(format!("{:^width$}", res.to_string_in(self.base))),
             SetForegroundColor(color::BLUE),
             Print(" | "),
             SetForegroundColor(color::CYAN),
@@ -142,7 +160,7 @@ impl<'a> Table<'a> {
     pub fn print_table_lines(self, to: usize) {
         for (_, (req_str, res)) in self.content.iter().enumerate().take(to) {
             match res {
-                Ok(res) => self.clone().print_table_line(*res, req_str),
+                Ok(res) => self.clone().print_table_line(res, req_str),
                 Err(_) => continue,
             };
         }
@@ -159,6 +177,7 @@ impl Printer for Table<'_> {
         history.reverse();
 
         Table::new(left_name, right_name, &history)
+            .with_base(interpreter.config.output_base)
             .print_table_header()
             .print_table_lines(to);
     }
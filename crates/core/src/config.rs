@@ -1,4 +1,9 @@
+use interpreter::{
+    config::{AngleUnit, NumberMode},
+    integer::Base,
+};
 use lazy_static::lazy_static;
+use rust_decimal::Decimal;
 use std::sync::{Arc, Mutex};
 use std::{fs::File, io::BufReader};
 
@@ -15,11 +20,35 @@ pub struct Config {
     pub output_line_history: usize,
     pub max_size_history: usize,
     pub max_number_variable: usize,
+    #[serde(default)]
+    pub angle_unit: AngleUnit,
+    #[serde(default)]
+    pub number_mode: NumberMode,
+    #[serde(default = "Config::default_digits")]
+    pub digits: u32,
+    #[serde(default = "Config::default_max_digits")]
+    pub max_digits: u32,
+    #[serde(default)]
+    pub output_base: Base,
+    #[serde(default = "Config::default_max_iterations")]
+    pub max_iterations: usize,
+    #[serde(default = "Config::default_tolerance")]
+    pub tolerance: Decimal,
+    #[serde(default = "Config::default_max_terms")]
+    pub max_terms: usize,
 }
 
 impl From<Config> for interpreter::config::Config {
     fn from(value: Config) -> Self {
         interpreter::config::Config::new(value.max_size_history, value.max_number_variable)
+            .with_angle_unit(value.angle_unit)
+            .with_number_mode(value.number_mode)
+            .with_digits(value.digits)
+            .with_max_digits(value.max_digits)
+            .with_output_base(value.output_base)
+            .with_max_iterations(value.max_iterations)
+            .with_tolerance(value.tolerance)
+            .with_max_terms(value.max_terms)
     }
 }
 
@@ -29,6 +58,26 @@ pub struct Commands {
     pub help: String,
     pub history: String,
     pub empty_input: String,
+    #[serde(default = "Commands::default_angle")]
+    pub angle: String,
+    #[serde(default = "Commands::default_mode")]
+    pub mode: String,
+    #[serde(default = "Commands::default_base")]
+    pub base: String,
+}
+
+impl Commands {
+    fn default_angle() -> String {
+        "/angle".to_string()
+    }
+
+    fn default_mode() -> String {
+        "/mode".to_string()
+    }
+
+    fn default_base() -> String {
+        "/base".to_string()
+    }
 }
 
 impl Config {
@@ -50,5 +99,33 @@ impl Config {
     #[must_use]
     pub fn get_config_for_interpreter(&self) -> interpreter::config::Config {
         interpreter::config::Config::new(self.max_size_history, self.max_number_variable)
+            .with_angle_unit(self.angle_unit)
+            .with_number_mode(self.number_mode)
+            .with_digits(self.digits)
+            .with_max_digits(self.max_digits)
+            .with_output_base(self.output_base)
+            .with_max_iterations(self.max_iterations)
+            .with_tolerance(self.tolerance)
+            .with_max_terms(self.max_terms)
+    }
+
+    fn default_digits() -> u32 {
+        interpreter::config::Config::DEFAULT_DIGITS
+    }
+
+    fn default_max_digits() -> u32 {
+        interpreter::config::Config::DEFAULT_MAX_DIGITS
+    }
+
+    fn default_max_iterations() -> usize {
+        interpreter::config::Config::DEFAULT_MAX_ITERATIONS
+    }
+
+    fn default_tolerance() -> Decimal {
+        interpreter::config::Config::DEFAULT_TOLERANCE
+    }
+
+    fn default_max_terms() -> usize {
+        interpreter::config::Config::DEFAULT_MAX_TERMS
     }
 }
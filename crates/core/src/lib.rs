@@ -3,28 +3,119 @@
 pub mod config;
 
 use config::Config;
-use interpreter::{ast::calc::Calc, errors::CalcError, interpreter::Interpreter};
-use lalrpop_util::lalrpop_mod;
-use rust_decimal::Decimal;
+use interpreter::{
+    ast::{calc::Calc, expr::Expr, simplify},
+    errors::{CalcError, Diagnostic, Span},
+    interpreter::Interpreter,
+    value::Value,
+};
+use lalrpop_util::{lalrpop_mod, lexer::Token, ErrorRecovery, ParseError};
 use std::{fs, io};
 
 lalrpop_mod!(pub parser, "/lexer/parser.rs");
 
-pub fn get_ast(input: &str, funct_caused_error: fn(CalcError) -> ()) -> Option<Calc> {
+/// Разбирает ввод пользователя.
+/// Парсер восстанавливается после синтаксических ошибок и продолжает разбор,
+/// поэтому возвращаются все найденные во вводе ошибки, каждая со своим местом и ожидаемыми токенами.
+pub fn parse(input: &str) -> Result<Calc, Vec<Diagnostic>> {
     let mut errors = Vec::new();
+    let result = parser::CalcParser::new().parse(&mut errors, input);
+    into_result(input, result, errors)
+}
 
-    match parser::CalcParser::new().parse(&mut errors, input) {
-        Ok(ast) => Some(ast),
+/// Разбирает выражение и упрощает его без интерпретатора: `x + x` превращается в `2 * x`.
+/// Имена остаются как есть, тела функций и значения переменных не подставляются.
+pub fn simplify(input: &str) -> Result<Expr, Vec<Diagnostic>> {
+    let mut errors = Vec::new();
+    let result = parser::ExprParser::new().parse(&mut errors, input);
+    into_result(input, result, errors).map(|expr| simplify::simplify(&expr))
+}
+
+/// Результат разбора или все найденные во вводе ошибки.
+fn into_result<'input, T>(
+    input: &'input str, result: Result<T, ParseError<usize, Token<'input>, CalcError>>,
+    errors: Vec<ErrorRecovery<usize, Token<'input>, CalcError>>,
+) -> Result<T, Vec<Diagnostic>> {
+    let mut diagnostics: Vec<Diagnostic> = errors
+        .into_iter()
+        .map(|recovery| to_diagnostic(input, recovery.error))
+        .collect();
+
+    match result {
+        Ok(ast) if diagnostics.is_empty() => Ok(ast),
+        Ok(_) => Err(diagnostics),
         Err(err) => {
-            err.map_error(|e| {
-                funct_caused_error(e);
-            });
+            diagnostics.push(to_diagnostic(input, err));
+            Err(diagnostics)
+        }
+    }
+}
 
+pub fn get_ast(input: &str, funct_caused_error: fn(&str, Vec<Diagnostic>) -> ()) -> Option<Calc> {
+    match parse(input) {
+        Ok(ast) => Some(ast),
+        Err(diagnostics) => {
+            funct_caused_error(input, diagnostics);
             None
         }
     }
 }
 
+fn to_diagnostic(input: &str, error: ParseError<usize, Token<'_>, CalcError>) -> Diagnostic {
+    let syntax_error = |start: usize, end: usize| {
+        Diagnostic::new(CalcError::SyntaxError, Some(Span::new(start, end)))
+    };
+
+    match error {
+        ParseError::InvalidToken { location } => {
+            let len = input[location..].chars().next().map_or(1, char::len_utf8);
+            syntax_error(location, location + len)
+        }
+        ParseError::UnrecognizedEof { location, expected } => {
+            syntax_error(location, location).with_expected(describe_expected(&expected))
+        }
+        ParseError::UnrecognizedToken {
+            token: (start, _, end),
+            expected,
+        } => syntax_error(start, end).with_expected(describe_expected(&expected)),
+        ParseError::ExtraToken {
+            token: (start, _, end),
+        } => syntax_error(start, end),
+        ParseError::User { error } => Diagnostic::from(error),
+    }
+}
+
+/// Переводит имена токенов LALRPOP в понятные пользователю: `r#"[A-Za-z]+"#` - `variable`,
+/// `"solve("` - `solve(...)`. Оператор со скобкой, например `"mod("`, - это сам оператор.
+fn describe_expected(expected: &[String]) -> Vec<String> {
+    let mut described: Vec<String> = Vec::new();
+
+    for token in expected {
+        let name = match token.as_str() {
+            t if t.starts_with("r#\"0") || (t.starts_with("r#") && t.contains(r"\d")) => {
+                "number".to_string()
+            }
+            t if t.starts_with("r#") && t.contains(r"\(") => "function call".to_string(),
+            t if t.starts_with("r#") || t == "\"_\"" => "variable".to_string(),
+            t => match t.trim_matches('"') {
+                "(" => "(".to_string(),
+                t => match t.strip_suffix('(') {
+                    Some(word) if OPERATOR_WORDS.contains(&word) => word.to_string(),
+                    Some(form) => format!("{form}(...)"),
+                    None => t.to_string(),
+                },
+            },
+        };
+        if !described.contains(&name) {
+            described.push(name);
+        }
+    }
+    described
+}
+
+/// Операторы-слова, которые грамматика читает и вместе с открывающей скобкой.
+const OPERATOR_WORDS: [&str; 9] = ["to", "or", "xor", "and", "shl", "shr", "mod", "div", "not"];
+
 pub fn load_interpreter() -> io::Result<Interpreter> {
     let contents = fs::read_to_string("interpreter.json")?;
     let interpreter: Interpreter = serde_json::from_str(&contents)?;
@@ -47,15 +138,13 @@ pub fn get_interpreter() -> Interpreter {
 }
 
 pub fn get_result(
-    interpreter: &mut Interpreter, ast: Calc, input: &str, funct_caused_error: fn(CalcError) -> (),
-) -> Option<Decimal> {
+    interpreter: &mut Interpreter, ast: Calc, input: &str,
+    funct_caused_error: fn(&str, Vec<Diagnostic>) -> (),
+) -> Option<Value> {
     match interpreter.eval(ast, input) {
-        Ok(n) => {
-            n?;
-            n
-        }
+        Ok(n) => n,
         Err(err) => {
-            funct_caused_error(err);
+            funct_caused_error(input, vec![Diagnostic::new(err, interpreter.error_span())]);
             None
         }
     }
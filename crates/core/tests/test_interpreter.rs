@@ -1,12 +1,51 @@
 use calc_core::parser;
-use interpreter::{config::Config, interpreter::Interpreter};
+use interpreter::{
+    config::{AngleUnit, Config, NumberMode},
+    errors::Span,
+    integer::Base,
+    interpreter::Interpreter,
+    registry::{NativeFunction, Registry},
+    value::Value,
+};
+use rust_decimal::Decimal;
 
 /// * expected - полученный ввод данных от пользвателя.
 /// * received - ожидаемое значение.
 macro_rules! testy {
     ($expected: expr, $received: expr) => {
+        testy!(Config::new(50, 50), $expected, $received);
+    };
+    ($config: expr, $expected: expr, $received: expr) => {
+        let mut interpreter = Interpreter::new($config).unwrap();
+
+        let mut errors = Vec::new();
+
+        match parser::CalcParser::new().parse(&mut errors, $expected) {
+            Ok(ast) => match interpreter.eval(ast, $expected) {
+                Ok(result) => match result {
+                    Some(result) => assert_eq!(format!("{:?}", result), $received),
+                    None => assert_eq!("\n", $received),
+                },
+                Err(err) => assert_eq!(format!("Error: {err:?}"), $received),
+            },
+            Err(err) => assert_eq!(format!("Error: {err:?}"), $received),
+        }
+    };
+}
+
+/// * inputs - ввод, последовательно выполняемый одним интерпретатором.
+/// * expected - последний ввод, результат которого проверяется.
+/// * received - ожидаемое значение.
+macro_rules! testy_session {
+    ([$($input: expr),* $(,)?], $expected: expr, $received: expr) => {
         let mut interpreter = Interpreter::new(Config::new(50, 50)).unwrap();
 
+        $(
+            let mut errors = Vec::new();
+            let ast = parser::CalcParser::new().parse(&mut errors, $input).unwrap();
+            interpreter.eval(ast, $input).unwrap();
+        )*
+
         let mut errors = Vec::new();
 
         match parser::CalcParser::new().parse(&mut errors, $expected) {
@@ -22,6 +61,35 @@ macro_rules! testy {
     };
 }
 
+/// * config - настройки интерпретатора, по умолчанию `Config::new(50, 50)`.
+/// * inputs - ввод, последовательно выполняемый одним интерпретатором.
+/// * saved - фрагменты, которые должны быть в сохранённом интерпретаторе.
+/// * received - ожидаемое значение последнего ввода у загруженного интерпретатора.
+///
+/// Возвращает загруженный интерпретатор для дополнительных проверок.
+macro_rules! testy_saved {
+    ([$($input: expr),+ $(,)?], [$($saved: expr),* $(,)?], $received: expr) => {
+        testy_saved!(Config::new(50, 50), [$($input),+], [$($saved),*], $received)
+    };
+    ($config: expr, [$($input: expr),+ $(,)?], [$($saved: expr),* $(,)?], $received: expr) => {{
+        let mut interpreter = Interpreter::new($config).unwrap();
+
+        $(
+            let mut errors = Vec::new();
+            let ast = parser::CalcParser::new().parse(&mut errors, $input).unwrap();
+            interpreter.eval(ast, $input).unwrap();
+        )+
+
+        let saved = serde_json::to_string(&interpreter).unwrap();
+        $(assert!(saved.contains($saved), "{saved}");)*
+
+        let loaded: Interpreter = serde_json::from_str(&saved).unwrap();
+        let (_, result) = &loaded.get_request_history(1)[0];
+        assert_eq!(format!("{:?}", result.clone().unwrap()), $received);
+        loaded
+    }};
+}
+
 #[test]
 fn div() {
     testy!("12 * 3", "36");
@@ -50,6 +118,37 @@ fn int_div() {
     testy!("3.8 div 4.7", "0");
 }
 
+#[test]
+fn overflow() {
+    testy!("79228162514264337593543950335 + 1", "Error: Overflow");
+    testy!("-79228162514264337593543950335 - 1", "Error: Overflow");
+    testy!("79228162514264337593543950335 * 2", "Error: Overflow");
+    testy!("79228162514264337593543950335 / 0.1", "Error: Overflow");
+    testy!("79228162514264337593543950335 div 0.1", "Error: Overflow");
+    testy!("30!", "Error: Overflow");
+    testy!("2 ^ 200", "Error: Overflow");
+    testy!("expe(100)", "Error: Overflow");
+}
+
+#[test]
+fn underflow() {
+    testy!(
+        "0.0000000000000001 * 0.0000000000000001",
+        "Error: Underflow"
+    );
+    testy!("0.0000000000000001 / 10000000000000000", "Error: Underflow");
+    testy!("2 ^ -200", "Error: Underflow");
+    testy!("expe(-100)", "Error: Underflow");
+}
+
+#[test]
+fn division_by_zero() {
+    testy!("5 / 0", "Error: DivisionByZero");
+    testy!("5 div 0", "Error: DivisionByZero");
+    testy!("5 mod 0", "Error: ModuloByZero");
+    testy!("0 ^ -1", "Error: DivisionByZero");
+}
+
 #[test]
 fn add() {
     testy!("3 + 4", "7");
@@ -63,3 +162,1286 @@ fn sub() {
     testy!("2 + 2 - 2", "2");
     testy!("3 - 4.5", "-1.5");
 }
+
+#[test]
+fn pow() {
+    testy!("2 ^ 10", "1024");
+    testy!("2 ^ 3 ^ 2", "512");
+    testy!("-2 ^ 2", "-4");
+    testy!("(-2) ^ 2", "4");
+    testy!("4 ^ 0.5", "2");
+}
+
+#[test]
+fn unary_minus() {
+    testy!("34 -5", "29");
+    testy!("-(2 + 3)", "-5");
+    testy!("2 * -3", "-6");
+    testy!("--4", "4");
+}
+
+#[test]
+fn factorial() {
+    testy!("5!", "120");
+    testy!("0!", "1");
+    testy!("3!!", "720");
+    testy!("2.5!", "Error: FactorialOfNotNaturalNumber");
+    testy!("(-3)!", "Error: FactorialOfNotNaturalNumber");
+}
+
+#[test]
+fn percent() {
+    testy!("50%", "0.5");
+    testy!("12.5%", "0.125");
+    testy!("1000%", "10");
+    testy!("-5%", "-0.05");
+}
+
+#[test]
+fn user_function() {
+    testy_session!(["f(x) = x * 2"], "f(21)", "42");
+    testy_session!(["f(x; y) = x - y"], "f(10; 4) + 1", "7");
+    testy_session!(["a = 3", "f(x) = x * a"], "f(2)", "6");
+    testy_session!(["f(x) = x + 1", "g(x) = f(x) * 2"], "g(4)", "10");
+    testy_session!(["f(x) = x + 1", "f(x) = x + 2"], "f(1)", "3");
+    testy_session!(["x = 100", "f(x) = x"], "f(1)", "1");
+    testy_session!(["x = 3"], "x (2)", "6");
+    testy!("PI (2)", "6.2831853071795864769252867666");
+    testy!("10 mod(3)", "1");
+    testy!("12 and(10) + 1", "8");
+}
+
+#[test]
+fn user_function_errors() {
+    testy!("f(1)", "Error: CallingNonexistentFunction(\"f\")");
+    testy!(
+        "sin(x) = x",
+        "Error: CannotCreateFunctionWithBuiltInName(\"sin\")"
+    );
+    testy!("f(x; x) = x", "Error: InvalidFunctionParameters");
+    testy_session!(
+        ["f(x) = x"],
+        "f(1; 2)",
+        "Error: IncorrectNumberOfArguments(1, 2)"
+    );
+    testy_session!(["f(x) = f(x)"], "f(1)", "Error: RecursionLimitExceeded");
+}
+
+#[test]
+fn history_results() {
+    testy_session!(["2 + 3"], "ans * 2", "10");
+    testy_session!(["2 + 3"], "_ - 1", "4");
+    testy_session!(["1", "2", "3"], "m1 + m3", "4");
+    testy_session!(["1", "2", "a = 10", "3"], "m2", "2");
+    testy_session!(["1"], "m2", "Error: HistoryEntryNotFound(\"m2\")");
+    testy!("ans", "Error: HistoryEntryNotFound(\"ans\")");
+    testy!(
+        "ans = 1",
+        "Error: CannotCreateVariableWithHistoryName(\"ans\")"
+    );
+    testy!("_ = 1", "Error: CannotCreateVariableWithHistoryName(\"_\")");
+    testy!(
+        "m3 = 1",
+        "Error: CannotCreateVariableWithHistoryName(\"m3\")"
+    );
+}
+
+#[test]
+fn angle_unit() {
+    let radians = Config::new(50, 50).with_angle_unit(AngleUnit::Radians);
+    let gradians = Config::new(50, 50).with_angle_unit(AngleUnit::Gradians);
+
+    testy!("sin(90)", "1");
+    testy!("cos(0)", "1");
+    testy!(radians, "sin(0)", "0");
+    testy!(radians, "cos(0)", "1");
+    testy!(radians, "sin(PI / 2)", "1");
+    testy!(gradians, "sin(100)", "1");
+}
+
+#[test]
+fn trigonometry_precision() {
+    testy!("sin(30)", "0.5");
+    testy!("cos(60)", "0.5");
+    testy!("sin(180)", "0");
+    testy!("sin(390)", "0.5");
+    testy!("tg(45)", "1");
+    testy!("sin(10)", "0.1736481776669303488517166268");
+    testy!("cos(1000)", "0.1736481776669303488517166268");
+    testy!("atan(1)", "45");
+    testy!("tg(90)", "Error: OutOfDomain(\"tg\")");
+    testy!("ctg(0)", "Error: OutOfDomain(\"ctg\")");
+}
+
+#[test]
+fn angle_conversion() {
+    testy!("rad(180)", "3.141592653589793238462643383");
+    testy!("deg(rad(45))", "45");
+    testy!("deg(rad(90))", "90.00000000000000000000000002");
+    testy!("deg(1)", "57.29577951308232087679815481");
+    testy!("sin(deg(PI / 6))", "0.5");
+}
+
+#[test]
+fn inverse_trigonometry() {
+    let radians = Config::new(50, 50).with_angle_unit(AngleUnit::Radians);
+
+    testy!("asin(0)", "0");
+    testy!("acos(1)", "0");
+    testy!("atan(0)", "0");
+    testy!("atan2(0; 1)", "0");
+    testy!("asin(0.5)", "30");
+    testy!("acos(0.5)", "60");
+    testy!("atan2(1; -1)", "135");
+    testy!(radians, "actg(0)", "1.570796326794896619231321692");
+    testy!("asin(2)", "Error: OutOfDomain(\"asin\")");
+    testy!("acos(-1.5)", "Error: OutOfDomain(\"acos\")");
+    testy!("atan2(0; 0)", "Error: OutOfDomain(\"atan2\")");
+}
+
+#[test]
+fn hyperbolic() {
+    testy!("sinh(0)", "0");
+    testy!("cosh(0)", "1");
+    testy!("tanh(0)", "0");
+    testy!("asinh(0)", "0");
+    testy!("acosh(1)", "0");
+    testy!("atanh(0)", "0");
+    testy!("acosh(0.5)", "Error: OutOfDomain(\"acosh\")");
+    testy!("atanh(1)", "Error: OutOfDomain(\"atanh\")");
+    testy!("sinh(1)", "1.175201193643801456882381851");
+    testy!("tanh(0.5)", "0.4621171572600097585023184836");
+    testy!("atanh(0.5)", "0.5493061443340548456976226185");
+}
+
+#[test]
+fn logarithm() {
+    testy!("ln(1)", "0");
+    testy!("ln(E)", "1");
+    testy!("log10(1000)", "3");
+    testy!("log2(1024)", "10");
+    testy!("log(8; 2)", "3");
+    testy!("ln(10)", "2.302585092994045684017991455");
+    testy!("ln(0.9999999)", "-0.0000001000000050000003333334");
+    testy!("expe(-64)", "0.0000000000000000000000000002");
+    testy!("ln(0)", "Error: OutOfDomain(\"ln\")");
+    testy!("log10(-5)", "Error: OutOfDomain(\"log10\")");
+    testy!("log(2; 1)", "Error: OutOfDomain(\"log\")");
+    testy!("log(-1; 2)", "Error: OutOfDomain(\"log\")");
+}
+
+#[test]
+fn root() {
+    testy!("sqrt(9)", "3");
+    testy!("sqrt(2.25)", "1.5");
+    testy!("rt(1024; 10)", "2");
+    testy!("rt(-27; 3)", "-3");
+    testy!("rt(0; 3)", "0");
+    testy!("rt(16; -2)", "0.25");
+    testy!("sqrt(2)", "1.414213562373095048801688724");
+    testy!("sqrt(-4)", "2i");
+    testy!(
+        "rt(-16; 4)",
+        "1.41421356237309504880169 + 1.41421356237309504880169i"
+    );
+}
+
+#[test]
+fn exponent() {
+    testy!("expe(0)", "1");
+    testy!("pow(2; 10)", "1024");
+    testy!("pow(3)", "9");
+    testy!("exp(2; 10)", "1024");
+    testy!("pow(2; -2)", "0.25");
+    testy!("pow(2; 0.5)", "1.414213562373095048801688724");
+    testy!("expe(1)", "2.718281828459045235360287471");
+}
+
+#[test]
+fn error_span() {
+    let cases = [
+        ("x + 1", Span::new(0, 1)),
+        ("2 * sin(1; 2)", Span::new(4, 13)),
+        ("1 + 4 / (2 - 2)", Span::new(4, 15)),
+        ("2 + f(1)", Span::new(4, 8)),
+    ];
+
+    for (input, span) in cases {
+        let mut interpreter = Interpreter::new(Config::new(50, 50)).unwrap();
+        let mut errors = Vec::new();
+        let ast = parser::CalcParser::new()
+            .parse(&mut errors, "f(x) = x / 0")
+            .unwrap();
+        interpreter.eval(ast, "f(x) = x / 0").unwrap();
+
+        let ast = parser::CalcParser::new().parse(&mut errors, input).unwrap();
+        assert!(interpreter.eval(ast, input).is_err());
+        assert_eq!(interpreter.error_span(), Some(span), "{input}");
+    }
+}
+
+#[test]
+fn registry() {
+    let mut interpreter = Interpreter::new(Config::new(50, 50)).unwrap();
+    interpreter
+        .registry
+        .register(
+            NativeFunction::real("lerp", 2..=3, |args, _| {
+                Ok(args[0] + (args[1] - args[0]) * args[2])
+            })
+            .with_defaults(vec![Decimal::new(5, 1).into()])
+            .with_doc("lerp(a; b; t) - linear interpolation"),
+        )
+        .unwrap();
+    interpreter.registry.unregister("sin");
+
+    let mut eval = |input: &str| {
+        let mut errors = Vec::new();
+        let ast = parser::CalcParser::new().parse(&mut errors, input).unwrap();
+        match interpreter.eval(ast, input) {
+            Ok(result) => format!("{result:?}"),
+            Err(err) => format!("Error: {err:?}"),
+        }
+    };
+
+    assert_eq!(eval("lerp(2; 4)"), "Some(3.0)");
+    assert_eq!(eval("lerp(2; 4; 0.25)"), "Some(2.50)");
+    assert_eq!(eval("lerp(1)"), "Error: IncorrectNumberOfArguments(2, 1)");
+    assert_eq!(
+        eval("lerp(1; 2; 3; 4)"),
+        "Error: IncorrectNumberOfArguments(3, 4)"
+    );
+    assert_eq!(
+        eval("lerp(x) = x"),
+        "Error: CannotCreateFunctionWithBuiltInName(\"lerp\")"
+    );
+    assert_eq!(
+        eval("sin(90)"),
+        "Error: CallingNonexistentFunction(\"sin\")"
+    );
+}
+
+#[test]
+fn registry_without_defaults() {
+    let mut registry = Registry::empty();
+    let result = registry.register(NativeFunction::real("lerp", 2..=3, |args, _| {
+        Ok(args[0] + (args[1] - args[0]) * args[2])
+    }));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "lerp: every optional argument needs a default value"
+    );
+    assert!(registry.get("lerp").is_none());
+
+    for function in Registry::default().iter() {
+        assert_eq!(
+            function.defaults.len(),
+            function.arity.end() - function.arity.start(),
+            "{}",
+            function.name
+        );
+    }
+}
+
+#[test]
+fn complex() {
+    testy!("i^2", "-1");
+    testy!("sqrt(-1)", "i");
+    testy!("3 + 4i", "3 + 4i");
+    testy!("-2i", "-2i");
+    testy!("(3 + 4i)(1 - 2i)", "11 - 2i");
+    testy!("(1 + i) / (1 - i)", "i");
+    testy!("(1 + i)(1 - i)", "2");
+    testy!("(1 + i)^10", "32i");
+    testy!("(-4)^0.5", "2i");
+    testy!("sqrt(3 + 4i)", "2 + i");
+    testy!("expe(i * PI)", "-1");
+    testy!("re(3 - 4i)", "3");
+    testy!("im(3 - 4i)", "-4");
+    testy!("abs(3 + 4i)", "5");
+    testy!("abs(-3)", "3");
+    testy!("arg(1 + i)", "45");
+    testy!("conj(3 + 4i)", "3 - 4i");
+    testy!("polar(2; 90)", "2i");
+    testy!("polar(2; 30)", "1.73205080756887729352745 + i");
+    testy!("arg(0)", "Error: OutOfDomain(\"arg\")");
+    testy!("sin(i)", "Error: ComplexNotSupported(\"sin\")");
+    testy!("(2 + i) mod 2", "Error: ComplexNotSupported(\"mod\")");
+    testy!("i!", "Error: ComplexNotSupported(\"Factorial\")");
+    testy!("i = 2", "Error: CannotCreateVariablesWithNameConstant");
+    testy_session!(["z = 3 + 4i"], "z * conj(z)", "25");
+    testy_session!(["f(x) = x^2 + 1"], "f(i)", "0");
+}
+
+#[test]
+fn complex_serialization() {
+    let loaded = testy_saved!(
+        ["z = 1 - 2i", "z * 2", "2 + 2"],
+        [r#"{"re":"2","im":"-4"}"#, r#""Ok":"4""#],
+        "4"
+    );
+    let (_, result) = &loaded.get_request_history(2)[1];
+    assert_eq!(format!("{:?}", result.clone().unwrap()), "2 - 4i");
+    assert_eq!(format!("{:?}", loaded.variables[0].value), "1 - 2i");
+}
+
+#[test]
+fn rational() {
+    let rational = Config::new(50, 50).with_number_mode(NumberMode::Rational);
+    testy!(rational, "1/3 * 3", "1");
+    testy!(rational, "1/3", "1/3");
+    testy!(rational, "1/3 + 1/6", "1/2");
+    testy!(rational, "0.1 + 0.2", "3/10");
+    testy!(rational, "7 div 2", "3");
+    testy!(rational, "-7 mod 3", "-1");
+    testy!(rational, "7/2 mod 1", "1/2");
+    testy!(rational, "(2/3)^-2", "9/4");
+    testy!(rational, "50%", "1/2");
+    testy!(rational, "5!", "120");
+    testy!(rational, "abs(-1/3)", "1/3");
+    testy!(rational, "2^0.5", "1.414213562373095048801688724");
+    testy!(rational, "sqrt(4) / 3", "0.6666666666666666666666666667");
+    testy!(rational, "1/3 + 10^27", "3000000000000000000000000001/3");
+    testy!(rational, "1/3 + 3 * 10^28", "30000000000000000000000000000");
+    testy!(rational, "1/0", "Error: DivisionByZero");
+    testy!(rational, "1 mod 0", "Error: ModuloByZero");
+    testy!("1/3 * 3", "0.9999999999999999999999999999");
+}
+
+#[test]
+fn fraction() {
+    testy!("frac(0.75)", "3/4");
+    testy!("frac(-1.5)", "-3/2");
+    testy!("frac(1/3)", "1/3");
+    testy!("frac(PI)", "355/113");
+    testy!("frac(PI; 1000000)", "1146408/364913");
+    testy!("frac(0.0001)", "0");
+    testy!("frac(0.0001; 10000)", "1/10000");
+    testy!("frac(0.5; 0)", "Error: OutOfDomain(\"frac\")");
+    testy!("frac(i)", "Error: ComplexNotSupported(\"frac\")");
+}
+
+#[test]
+fn rational_serialization() {
+    let config = Config::new(50, 50).with_number_mode(NumberMode::Rational);
+    testy_saved!(config, ["2/6"], [r#"{"numer":"1","denom":"3"}"#], "1/3");
+}
+
+#[test]
+fn float_mode() {
+    let float = Config::new(50, 50).with_number_mode(NumberMode::Float);
+    testy!(float, "0.1 + 0.2", "0.30000000000000004");
+    testy!(float, "1/3", "0.3333333333333333");
+    testy!(float, "exp(10; 40)", "1e40");
+    testy!(float, "10^300 * 10", "1e301");
+    testy!(float, "10^400", "Error: Overflow");
+    testy!(float, "2^-1075", "Error: Underflow");
+    testy!(float, "170!", "7.257415615308004e306");
+    testy!(float, "-7 mod 3", "-1");
+    testy!(float, "7 div 2", "3");
+    testy!(float, "sqrt(2)", "1.4142135623730951");
+    testy!(float, "sqrt(-4)", "2i");
+    testy!(float, "PI * 2", "6.283185307179586");
+    testy!(float, "sin(30)", "0.5");
+    testy!(float, "1/0", "Error: DivisionByZero");
+}
+
+#[test]
+fn big_mode() {
+    let big = Config::new(50, 50).with_number_mode(NumberMode::Big);
+    testy!(
+        big,
+        "exp(10; 40)",
+        "10000000000000000000000000000000000000000"
+    );
+    testy!(
+        big,
+        "exp(10; 40) + 1",
+        "10000000000000000000000000000000000000001"
+    );
+    testy!(
+        big,
+        "1/3",
+        "0.33333333333333333333333333333333333333333333333333"
+    );
+    testy!(
+        big,
+        "2/3",
+        "0.66666666666666666666666666666666666666666666666667"
+    );
+    testy!(
+        big,
+        "sqrt(2)",
+        "1.4142135623730950488016887242096980785696718753769"
+    );
+    testy!(big, "30!", "265252859812191058636308480000000");
+    testy!(
+        big,
+        "100!",
+        "9.3326215443944152681699238856266700490715968264382e+157"
+    );
+    testy!(big, "10^1000 / 10^999", "10");
+    testy!(
+        big,
+        "2^-200",
+        "6.2230152778611417071440640537801242405902521687212e-61"
+    );
+    testy!(big, "10^(10^10)", "Error: Overflow");
+    testy!(big, "-7 mod 3", "-1");
+    testy!(big, "7 div 2", "3");
+    testy!(big, "50%", "0.5");
+    testy!(big, "sqrt(-4)", "2i");
+    testy!(big, "sin(30)", "0.5");
+    testy!(big, "1/0", "Error: DivisionByZero");
+    testy!(big, "1 mod 0", "Error: ModuloByZero");
+    testy!(
+        big,
+        "123456789012345678901234567890123 + 1",
+        "123456789012345678901234567890124"
+    );
+    testy!(
+        "123456789012345678901234567890123 + 1",
+        "Error: InputTooBig"
+    );
+    testy!(
+        big,
+        "PI",
+        "3.1415926535897932384626433832795028841971693993751"
+    );
+    testy!(big, "E", "2.7182818284590452353602874713526624977572470937");
+    testy!(
+        big,
+        "ln(2)",
+        "0.69314718055994530941723212145817656807550013436026"
+    );
+    testy!(
+        big,
+        "2^0.5",
+        "1.4142135623730950488016887242096980785696718753769"
+    );
+    testy!(
+        big,
+        "10^100.5",
+        "3.1622776601683793319988935444327185337195551393252e+100"
+    );
+    testy!(big, "log10(1000)", "3");
+    testy!(big, "log(8; 2)", "3");
+    testy!(big, "ln(0)", "Error: OutOfDomain(\"ln\")");
+    testy!(
+        Config::new(50, 50)
+            .with_number_mode(NumberMode::Big)
+            .with_digits(10),
+        "1/3",
+        "0.3333333333"
+    );
+    testy!(
+        big.with_digits(20000),
+        "PI",
+        "Error: TooManyDigits(20000, 1000)"
+    );
+    testy!(
+        big.with_digits(20).with_max_digits(10),
+        "1/3",
+        "Error: TooManyDigits(20, 10)"
+    );
+    testy!(
+        Config::new(50, 50).with_digits(20000),
+        "1/3",
+        "0.3333333333333333333333333333"
+    );
+    testy!("exp(10; 40)", "Error: Overflow");
+}
+
+#[test]
+fn number_mode_serialization() {
+    let config = Config::new(50, 50).with_number_mode(NumberMode::Float);
+    testy_saved!(config, ["0.5"], [r#"{"float":0.5}"#], "0.5");
+    let config = Config::new(50, 50).with_number_mode(NumberMode::Big);
+    testy_saved!(
+        config,
+        ["exp(10; 40)"],
+        [r#"{"big":"10000000000000000000000000000000000000000","digits":50}"#],
+        "10000000000000000000000000000000000000000"
+    );
+}
+
+#[test]
+fn units() {
+    testy!("3 km + 250 m to mi", "2.0194563747713354012566610992 mi");
+    testy!("9.81 m/s^2 * 70 kg", "686.7 N");
+    testy!("60 km/h to m/s", "16.666666666666666666666666667 m/s");
+    testy!("5 ft + 3 in to cm", "160.02 cm");
+    testy!("2 h to min", "120 min");
+    testy!("100 W * 2 h to kWh", "0.2 kWh");
+    testy!("5 V * 2 A", "10 W");
+    testy!("1 J / (1 N)", "1 m");
+    testy!("1 / (2 s)", "0.5 s^-1");
+    testy!("1 / (2 s) to Hz", "0.5 Hz");
+    testy!("(3 km) / (500 m)", "6");
+    testy!("3 km * 2", "6 km");
+    testy!("(2 m)^2", "4 m^2");
+    testy!("sqrt(9 m^2)", "3 m");
+    testy!("abs(-3 m)", "3 m");
+    testy!("(7 m) mod (2 m)", "1 m");
+    testy!("(7 m) div (2 m)", "3");
+    testy!("1 gram to mg", "1000 mg");
+}
+
+#[test]
+fn temperature_units() {
+    testy!("100 degC to degF", "212 degF");
+    testy!("-40 degC to degF", "-40 degF");
+    testy!("98.6 degF to degC", "37 degC");
+    testy!("0 degC to K", "273.15 K");
+    testy!("20 degC + 5 K", "25 degC");
+    testy!("30 degC - 20 degC", "10 degC");
+    testy!("0 K + 20 degC", "293.15 K");
+}
+
+#[test]
+fn data_units() {
+    testy!("1 KiB to bit", "8192 bit");
+    testy!("1 GiB to MB", "1073.741824 MB");
+    testy!("8 bit to B", "1 B");
+    testy!("100 Mbit/s * 1 min to GB", "0.75 GB");
+}
+
+#[test]
+fn unit_errors() {
+    testy!("3 m + 2 s", "Error: UnitMismatch(\"m\", \"s\")");
+    testy!("3 m + 2", "Error: UnitMismatch(\"m\", \"1\")");
+    testy!("3 kg to m", "Error: UnitMismatch(\"kg\", \"m\")");
+    testy!("2 to 3", "Error: ExpectedUnit(\"3\")");
+    testy!("2^(3 m)", "Error: UnitMismatch(\"m\", \"1\")");
+    testy!("sqrt(2 m)", "Error: InvalidUnitPower(\"m\")");
+    testy!("sin(3 m)", "Error: UnitNotSupported(\"sin\")");
+    testy!("(3 m)!", "Error: UnitNotSupported(\"Factorial\")");
+    testy!("(1 L)^50", "Error: Overflow");
+    testy!("1/(1 m)^-128", "Error: Overflow");
+    testy!("(1 m)^-128", "1 m^-128");
+    testy!(
+        "3 parsecs",
+        "Error: CallingNonexistentVariable(\"parsecs\")"
+    );
+}
+
+#[test]
+fn constants_with_units() {
+    testy!("c", "299792458 m/s");
+    testy!("g", "9.80665 m/s^2");
+    testy!("70 kg * g", "686.4655 N");
+    testy!("5 g to kg", "0.005 kg");
+    testy!("2 * 5 g", "10 g");
+    testy!("5 * g", "49.03325 m/s^2");
+    testy!("c * 1 year to km", "9460730472580.8 km");
+    testy!(
+        "G * 5.97 * 10^24 kg / (6371 km)^2",
+        "9.816684754615099946313888315 m/s^2"
+    );
+    testy_session!(["m = 2"], "3 m", "6");
+}
+
+#[test]
+fn quantity_serialization() {
+    testy_saved!(
+        ["G * 2 kg"],
+        [r#"{"amount":"0.000000000133486","unit":"m^3/s^2"}"#],
+        "0.000000000133486 m^3/s^2"
+    );
+}
+
+#[test]
+fn integer_literals() {
+    testy!("0xFF", "0xFF");
+    testy!("0b1010", "0b1010");
+    testy!("0o17", "0o17");
+    testy!("0xFF + 1", "0x100");
+    testy!("1 + 0xFF", "0x100");
+    testy!("0x10 * 0x10", "0x100");
+    testy!("0x10 div 3", "0x5");
+    testy!("-0b101", "-0b101");
+    testy!("0x7 / 2", "3.50");
+    testy!("0xFF + 0.5", "255.5");
+    testy!("0x10 / 0", "Error: DivisionByZero");
+    testy!("sqrt(0x10)", "4");
+    testy!(
+        Config::new(50, 50).with_number_mode(NumberMode::Rational),
+        "0xFF + 1",
+        "0x100"
+    );
+}
+
+#[test]
+fn bitwise() {
+    testy!("0xF0 or 15", "0xFF");
+    testy!("0xFF and 0b1010", "0xA");
+    testy!("12 and 10", "8");
+    testy!("5 xor 3", "6");
+    testy!("not 0", "-1");
+    testy!("not 0xFF", "-0x100");
+    testy!("1 shl 4", "16");
+    testy!("0x100 shr 4", "0x10");
+    testy!("-8 shr 1", "-4");
+    testy!("1 shl 4 - 1", "8");
+    testy!("1 shl 200", "Error: Overflow");
+    testy!("1 shl -1", "Error: OutOfDomain(\"shl\")");
+}
+
+#[test]
+fn bitwise_errors() {
+    testy!("2.5 and 1", "Error: NotAnInteger(\"and\")");
+    testy!("1 shl 0.5", "Error: NotAnInteger(\"shl\")");
+    testy!("not 2.5", "Error: NotAnInteger(\"not\")");
+    testy!("hex(1.5)", "Error: NotAnInteger(\"hex\")");
+    testy!("i xor 1", "Error: ComplexNotSupported(\"xor\")");
+    testy!("3 m or 1", "Error: UnitNotSupported(\"or\")");
+}
+
+#[test]
+fn base_functions() {
+    testy!("hex(255)", "0xFF");
+    testy!("bin(10)", "0b1010");
+    testy!("oct(8)", "0o10");
+    testy!("dec(0xFF)", "255");
+    testy!("hex(-255)", "-0xFF");
+    testy!("hex(3 / 3)", "0x1");
+    testy!("abs(-0x10)", "0x10");
+}
+
+#[test]
+fn output_base() {
+    let display = |input: &str, base| {
+        let mut interpreter = Interpreter::new(Config::new(50, 50)).unwrap();
+        let mut errors = Vec::new();
+        let ast = parser::CalcParser::new().parse(&mut errors, input).unwrap();
+        let result = interpreter.eval(ast, input).unwrap().unwrap();
+        result.to_string_in(base)
+    };
+
+    assert_eq!(display("255", Base::Hex), "0xFF");
+    assert_eq!(display("10", Base::Binary), "0b1010");
+    assert_eq!(display("-8", Base::Octal), "-0o10");
+    assert_eq!(display("1.5", Base::Hex), "1.5");
+    assert_eq!(display("bin(5)", Base::Hex), "0b101");
+    assert_eq!(display("0xFF", Base::Decimal), "0xFF");
+    assert_eq!(display("2 + 3i", Base::Hex), "2 + 3i");
+    assert_eq!(display("255", Base::Decimal), "255");
+}
+
+#[test]
+fn integer_serialization() {
+    let config = Config::new(50, 50).with_output_base(Base::Binary);
+    let loaded = testy_saved!(
+        config,
+        ["0xFF + 1"],
+        [
+            r#"{"integer":"256","base":"hex"}"#,
+            r#""output_base":"binary""#
+        ],
+        "0x100"
+    );
+    assert_eq!(loaded.config.output_base, Base::Binary);
+}
+
+#[test]
+fn fixed_width_integers() {
+    testy!("int32(0x7FFFFFFF) + 1", "0x80000000 (int32)");
+    testy!("dec(int32(0x7FFFFFFF) + 1)", "-2147483648 (int32)");
+    testy!("uint8(255) + 1", "0 (uint8)");
+    testy!("uint16(3) - 5", "65534 (uint16)");
+    testy!("int8(100) + int8(100)", "-56 (int8)");
+    testy!("int64(2)^63", "-9223372036854775808 (int64)");
+    testy!("uint64(2)^64 - 1", "18446744073709551615 (uint64)");
+    testy!("int32(7) / 2", "3 (int32)");
+    testy!("int32(-7) mod 2", "-1 (int32)");
+    testy!("-int8(-128)", "-128 (int8)");
+    testy!("int16(5) * 2.5", "12.5");
+    testy!("int32(7) / 0", "Error: DivisionByZero");
+}
+
+#[test]
+fn integer_casts() {
+    testy!("int8(200)", "-56 (int8)");
+    testy!("uint8(-1)", "255 (uint8)");
+    testy!("uint8(0x1FF)", "0xFF (uint8)");
+    testy!("int8(uint8(200))", "-56 (int8)");
+    testy!("int8(-3.9)", "-3 (int8)");
+    testy!("hex(int32(-1))", "0xFFFFFFFF (int32)");
+    testy!("bin(int8(-2))", "0b11111110 (int8)");
+    testy!("int32(i)", "Error: ComplexNotSupported(\"int32\")");
+}
+
+#[test]
+fn integer_type_promotion() {
+    testy!("int32(1) + int64(1)", "2 (int64)");
+    testy!("uint32(1) + int32(-2)", "4294967295 (uint32)");
+    testy!("int8(-1) + uint8(0)", "255 (uint8)");
+    testy!("uint32(1) + 1", "2 (uint32)");
+}
+
+#[test]
+fn fixed_width_bitwise() {
+    testy!("not uint8(0)", "255 (uint8)");
+    testy!("uint8(1) shl 8", "0 (uint8)");
+    testy!("uint8(1) shl 7", "128 (uint8)");
+    testy!("int8(-128) shr 1", "-64 (int8)");
+    testy!("uint8(0xF0) xor 0xFF", "0xF (uint8)");
+    testy!("int16(-1) and 0xFF", "255 (int16)");
+}
+
+#[test]
+fn fixed_width_serialization() {
+    testy_saved!(
+        ["int32(0x7FFFFFFF) + 1"],
+        [r#"{"integer":"-2147483648","base":"hex","type":"int32"}"#],
+        "0x80000000 (int32)"
+    );
+}
+
+#[test]
+fn matrix_literals() {
+    testy!("[1; 2; 3]", "[1; 2; 3]");
+    testy!("[[1; 2]; [3; 4]]", "[[1; 2]; [3; 4]]");
+    testy!("[1 + 1; 2 * 3]", "[2; 6]");
+    testy!("[[1; 2]; [3]]", "Error: InvalidMatrix");
+    testy!("[[1; 2]; 3]", "Error: InvalidMatrix");
+    testy!("[]", "Error: InvalidMatrix");
+}
+
+#[test]
+fn matrix_arithmetic() {
+    testy!("[1; 2] + [3; 4]", "[4; 6]");
+    testy!("2 * [1; 2]", "[2; 4]");
+    testy!("[1; 2] - 1", "[0; 1]");
+    testy!("-[1; 2]", "[-1; -2]");
+    testy!("[1; 2] .* [3; 4]", "[3; 8]");
+    testy!("[2; 9] ./ [2; 4]", "[1; 2.25]");
+    testy!("[1; 2] .^ 2", "[1; 4]");
+    testy!("[[1; 2]; [3; 4]] .* [[1; 2]; [3; 4]]", "[[1; 4]; [9; 16]]");
+    testy!("[1 m; 2 m] * 2", "[2 m; 4 m]");
+    testy!(
+        "[1; 2] + [1; 2; 3]",
+        "Error: DimensionMismatch(\"2\", \"3\")"
+    );
+}
+
+#[test]
+fn matrix_product() {
+    testy!("[1; 2] * [3; 4]", "11");
+    testy!("[[1; 2]; [3; 4]] * [1; 1]", "[3; 7]");
+    testy!("[1; 1] * [[1; 2]; [3; 4]]", "[4; 6]");
+    testy!("[[1; 2]; [3; 4]] * [[0; 1]; [1; 0]]", "[[2; 1]; [4; 3]]");
+    testy!("[[1; 2]; [3; 4]]^2", "[[7; 10]; [15; 22]]");
+    testy!("[[1; 2]; [3; 4]]^-1", "[[-2; 1]; [1.5; -0.5]]");
+    testy!("[[2; 0]; [0; 2]] / [[1; 0]; [0; 1]]", "[[2; 0]; [0; 2]]");
+    testy!(
+        "[[1; 2]; [3; 4]] * [[1; 2; 3]]",
+        "Error: DimensionMismatch(\"2x2\", \"1x3\")"
+    );
+}
+
+#[test]
+fn matrix_functions() {
+    testy!("det([[1; 2]; [3; 4]])", "-2");
+    testy!("det([[2; 0; 0]; [0; 3; 0]; [0; 0; 4]])", "24");
+    testy!("inv([[1; 2]; [3; 4]])", "[[-2; 1]; [1.5; -0.5]]");
+    testy!(
+        "transpose([[1; 2; 3]; [4; 5; 6]])",
+        "[[1; 4]; [2; 5]; [3; 6]]"
+    );
+    testy!("dot([1; 2; 3]; [4; 5; 6])", "32");
+    testy!("cross([1; 0; 0]; [0; 1; 0])", "[0; 0; 1]");
+    testy!("norm([3; 4])", "5");
+    testy!("norm([3 m; 4 m])", "5 m");
+    testy!("abs([-1; 2])", "[1; 2]");
+    testy!("det([1; 2])", "Error: NotSquareMatrix(\"det\")");
+    testy!("inv([[1; 2]; [2; 4]])", "Error: SingularMatrix");
+    testy!(
+        "cross([1; 2]; [3; 4])",
+        "Error: DimensionMismatch(\"2\", \"2\")"
+    );
+    testy!("det(5)", "Error: NotAMatrix(\"det\")");
+    testy!("sin([1; 2])", "Error: MatrixNotSupported(\"sin\")");
+}
+
+#[test]
+fn matrix_rational_mode() {
+    let config = Config::new(50, 50).with_number_mode(NumberMode::Rational);
+    testy!(config, "inv([[1; 2]; [3; 4]])", "[[-2; 1]; [3/2; -1/2]]");
+}
+
+#[test]
+fn matrix_indexing() {
+    testy_session!(["v = [1; 2; 3]"], "v[2]", "2");
+    testy_session!(["m = [[1; 2]; [3; 4]]"], "m[2; 1]", "3");
+    testy_session!(["m = [[1; 2]; [3; 4]]"], "m[2]", "[3; 4]");
+    testy_session!(["m = [[1; 2]; [3; 4]]"], "2 m", "[[2; 4]; [6; 8]]");
+    testy_session!(
+        ["m = [[1; 2]; [3; 4]]"],
+        "[1 m; 2 m]",
+        "Error: InvalidMatrix"
+    );
+    testy_session!(["mat = [[1; 2]; [3; 4]]"], "[1 m; 2 m] * 2", "[2 m; 4 m]");
+    testy_session!(["f(a) = a[1] * 2"], "f([7; 8])", "14");
+    testy_session!(["v = [1; 2; 3]"], "v[4]", "Error: IndexOutOfRange(\"4\")");
+    testy_session!(
+        ["v = [1; 2; 3]"],
+        "v[1; 1]",
+        "Error: IncorrectNumberOfArguments(1, 2)"
+    );
+    testy_session!(["x = 5"], "x[1]", "Error: NotAMatrix(\"Indexing\")");
+}
+
+#[test]
+fn matrix_serialization() {
+    let loaded = testy_saved!(
+        ["m = [[1; 2]; [3; 0x4]]", "m"],
+        [r#"{"matrix":[["1","2"],["3",{"integer":"4","base":"hex"}]]}"#],
+        "[[1; 2]; [3; 0x4]]"
+    );
+    assert_eq!(
+        loaded.variables[0].value.to_string_in(Base::Hex),
+        "[[0x1; 0x2]; [0x3; 0x4]]"
+    );
+}
+
+#[test]
+fn linsolve() {
+    testy!("linsolve([[2; 1]; [1; 3]]; [3; 5])", "[0.8; 1.4]");
+    testy!(
+        "linsolve([[2; 1]; [1; 3]]; [[3; 1]; [5; 0]])",
+        "[[0.8; 0.6]; [1.4; -0.2]]"
+    );
+    testy!("linsolve([[1; 1]; [1; -1]; [2; 0]]; [2; 0; 2])", "[1; 1]");
+    testy!(
+        "linsolve([[1; 2]; [2; 4]]; [1; 3])",
+        "Error: SingularSystem"
+    );
+    testy!(
+        "linsolve([[1; 2]; [2; 4]]; [1; 2])",
+        "Error: UnderdeterminedSystem(1, 2)"
+    );
+    testy!(
+        "linsolve([[1; 0]; [0; 1]]; [1; 2; 3])",
+        "Error: DimensionMismatch(\"2x2\", \"3\")"
+    );
+}
+
+#[test]
+fn solve_linear_equations() {
+    testy!("solve(2x + y = 3; x + 3y = 5; x; y)", "[0.8; 1.4]");
+    testy!("solve(3x - 6 = 0; x)", "2");
+    testy!("solve(x / 3 = 1; x)", "3");
+    testy!("solve(sin(30) x = 1; x)", "2");
+    testy_session!(["solve(2x + y = 3; x + 3y = 5; x; y)"], "x + y", "2.2");
+    testy_session!(["f(a) = solve(2x = a; x)"], "f(10)", "5");
+    testy_session!(["g(x) = solve(2x = 4; x)"], "g(1)", "2");
+    testy_session!(["h(x; a) = solve(x^2 = a; x; 1)"], "h(5; 9)", "3");
+
+    let rational = Config::new(50, 50).with_number_mode(NumberMode::Rational);
+    testy!(
+        rational,
+        "solve(x / 3 + y / 7 = 1; x - y = 2; x; y)",
+        "[27/10; 7/10]"
+    );
+}
+
+#[test]
+fn solve_errors() {
+    testy!("solve(x^2 = 4; x)", "Error: NonlinearSystem");
+    testy!("solve(1/x = 1; x)", "Error: NonlinearSystem");
+    testy!("solve(ln(x) = 1; x)", "Error: NonlinearSystem");
+    testy!("solve(x + y = 1; x + y = 2; x; y)", "Error: SingularSystem");
+    testy!(
+        "solve(x + y = 1; x; y)",
+        "Error: UnderdeterminedSystem(1, 2)"
+    );
+    testy!("solve(x = 1; 2)", "Error: InvalidSolveArguments");
+    testy!("solve(x; x = 1)", "Error: InvalidSolveArguments");
+    testy!("solve(x = 1; x; x)", "Error: InvalidSolveArguments");
+    testy!(
+        "solve(PI = 1; PI)",
+        "Error: CannotCreateVariablesWithNameConstant"
+    );
+    testy_session!(
+        ["x = 5"],
+        "solve(x + y = 1; x + y = 2; x; y)",
+        "Error: SingularSystem"
+    );
+}
+
+#[test]
+fn solve_numeric() {
+    testy!("solve(x^3 - 2x = 5; x; 2)", "2.0945514815423265915");
+    testy!("solve(x^2 = 4; x; 1)", "2");
+    testy!("solve(x^2 = 4; x; -1)", "-2");
+    testy!("solve(x^2 = 2; x; 1)", "1.4142135623730950488");
+    testy!(
+        Config::new(50, 50).with_angle_unit(AngleUnit::Radians),
+        "solve(cos(x) = x; x; 1)",
+        "0.7390851332151606417"
+    );
+    testy!(
+        Config::new(50, 50).with_number_mode(NumberMode::Float),
+        "solve(x^3 - 2x = 5; x; 2)",
+        "2.0945514815423265"
+    );
+    testy_session!(["solve(x^3 = 1000; x; 5)"], "x", "10");
+    testy_session!(
+        ["f(t) = t^2 - 3"],
+        "solve(f(t) = 0; t; 1)",
+        "1.7320508075688772935"
+    );
+    testy!(
+        Config::new(50, 50).with_tolerance(Decimal::new(1, 6)),
+        "solve(x^2 = 2; x; 1)",
+        "1.41421"
+    );
+}
+
+#[test]
+fn solve_numeric_errors() {
+    testy!("solve(x^2 = -1; x; 1)", "Error: NoConvergence(100)");
+    testy!(
+        Config::new(50, 50).with_max_iterations(3),
+        "solve(x^3 - 2x = 5; x; 2)",
+        "Error: NoConvergence(3)"
+    );
+    testy!(
+        "solve(x = 1; y = 2; x; y; 1)",
+        "Error: InvalidSolveArguments"
+    );
+    testy!("solve(x + y = 1; x; y; 1)", "Error: InvalidSolveArguments");
+    testy!(
+        "solve(x = 1; x; 1 + 2i)",
+        "Error: ComplexNotSupported(\"solve\")"
+    );
+    testy_session!(
+        ["x = 5"],
+        "solve(x^2 = -1; x; 1)",
+        "Error: NoConvergence(100)"
+    );
+    testy!("solve(expe(x) = 0; x; 0)", "Error: NoConvergence(95)");
+    testy!("solve(x^2 = 0; x; 1)", "Error: NoConvergence(69)");
+    testy!("solve(x^2 = 0; x; 0)", "0");
+    testy!(
+        "solve(x^2 = 2; y; 1)",
+        "Error: UnknownNotInEquations(\"y\")"
+    );
+    testy!(
+        "solve(x + 1 = 2; x; y)",
+        "Error: UnknownNotInEquations(\"y\")"
+    );
+    testy_session!(["f(t) = t^2"], "solve(f(y) = 4; y; 1)", "2");
+}
+
+#[test]
+fn numeric_integrate() {
+    let radians = || Config::new(50, 50).with_angle_unit(AngleUnit::Radians);
+    testy!("integrate(x^2; x; 0; 1)", "0.3333333333333333333");
+    testy!("integrate(x; x; 1; 0)", "-0.5");
+    testy!("integrate(abs(x); x; -1; 2)", "2.5");
+    testy!("integrate(x^2; x; 2; 2)", "0");
+    testy!(radians(), "integrate(sin(x); x; 0; PI)", "2");
+    testy!("integrate(sin(x)^2; x; 0; 360)", "180");
+    testy!("integrate(1 / x; x; 1; expe(1))", "1");
+    testy!("integrate(expe(-x^2); x; -10; 10)", "1.7724538509055160273");
+    testy!("integrate(expe(-x^2); x; 0; 100)", "0.8862269254527580136");
+    testy!(
+        radians(),
+        "integrate(expe(-x^2); x; -3; 3)",
+        "1.7724146965190424678"
+    );
+    testy!("integrate(integrate(x * y; y; 0; 1); x; 0; 1)", "0.25");
+    testy!("integrate(diff(x^2; x); x; 0; 1)", "1");
+    testy_session!(["f(t) = t^2"], "integrate(f(x); x; 0; 3)", "9");
+    testy_session!(["g(a) = integrate(t^a; t; 0; 1)"], "g(3)", "0.25");
+    testy!(
+        Config::new(50, 50).with_number_mode(NumberMode::Float),
+        "integrate(x^2; x; 0; 1)",
+        "0.3333333333333333"
+    );
+}
+
+#[test]
+fn numeric_derivative() {
+    let radians = || Config::new(50, 50).with_angle_unit(AngleUnit::Radians);
+    testy!("derivative(x^3; x; 2)", "12");
+    testy!(radians(), "derivative(sin(x); x; 0)", "1");
+    testy!("derivative(sin(x); x; 30)", "0.0151149947019518154");
+    testy!("derivative(expe(x); x; 1)", "2.7182818284590452354");
+    testy!("derivative(ln(x); x; 2)", "0.5");
+    testy!("derivative(diff(x^3; x); x; 2)", "12");
+    testy_session!(["a = 2"], "derivative(a * x^2; x; a)", "8");
+}
+
+#[test]
+fn bound_variables() {
+    testy_session!(["x = 5", "integrate(x^2; x; 0; 3)"], "x", "5");
+    testy_session!(["x = 5", "derivative(x^2; x; 1)"], "x", "5");
+    testy_session!(["x = 5"], "integrate(x; y; 0; 1)", "5");
+    testy_session!(["x = 5"], "derivative(x * y; y; x)", "5");
+    testy_session!(["t = 1", "integrate(solve(t = x; t); x; 0; 1)"], "t", "1");
+    testy_session!(["sum(k; 1; 3; solve(s = k; s))"], "s", "1 s");
+    testy!("sum(k; 1; 3; solve(y = k; y) + y)", "12");
+    testy!(
+        "integrate(x; x; 0; 1) + x",
+        "Error: CallingNonexistentVariable(\"x\")"
+    );
+
+    let mut interpreter = Interpreter::new(Config::new(50, 50)).unwrap();
+    let mut errors = Vec::new();
+    let body = parser::ExprParser::new().parse(&mut errors, "x^2").unwrap();
+    let value = interpreter
+        .evaluate_with(&body, "x", Value::Real(Decimal::from(3)))
+        .unwrap();
+    assert_eq!(value.to_string(), "9");
+    assert!(interpreter.variables.is_empty());
+}
+
+#[test]
+fn numeric_calculus_errors() {
+    testy!(
+        "integrate(1 / sqrt(x); x; 0; 1)",
+        "Error: AccuracyNotReached(\"0.0000000000000643318379390074\")"
+    );
+    testy!(
+        "integrate(x; PI; 0; 1)",
+        "Error: CannotCreateVariablesWithNameConstant"
+    );
+    testy!(
+        "integrate(x; x; 0; [1; 2])",
+        "Error: MatrixNotSupported(\"integrate\")"
+    );
+    testy!(
+        "derivative(sqrt(x); x; 0)",
+        "Error: ComplexNotSupported(\"derivative\")"
+    );
+    testy!(
+        "derivative(x * y; x; 1)",
+        "Error: CallingNonexistentVariable(\"y\")"
+    );
+}
+
+#[test]
+fn sum_and_product() {
+    testy!("sum(i; 1; 100; i^2)", "338350");
+    testy!("sum(k; 1; 10; 1/k)", "2.9289682539682539682539682540");
+    testy!(
+        Config::new(50, 50).with_number_mode(NumberMode::Rational),
+        "prod(k; 2; 10; 1 - 1/k^2)",
+        "11/20"
+    );
+    testy!("prod(k; 1; 5; k)", "120");
+    testy!("sum(i; -2; 2; i^3)", "0");
+    testy!("sum(i; 1; 3; [i; i^2])", "[6; 14]");
+    testy!("sum(i; 1; 3; sum(j; 1; i; i * j))", "25");
+    testy!("sum(k; 5; 1; k)", "0");
+    testy!("prod(k; 5; 1; k)", "1");
+    testy_session!(["f(n) = sum(k; 1; n; k)"], "f(100)", "5050");
+    testy_session!(["n = 5"], "prod(k; 1; n; k)", "120");
+}
+
+#[test]
+fn sum_index_is_bound() {
+    testy_session!(["k = 7", "sum(k; 1; 3; k)"], "k", "7");
+    testy_session!(["sum(i; 1; 3; i)"], "i", "i");
+    testy!(
+        "sum(k; 1; 3; k) + k",
+        "Error: CallingNonexistentVariable(\"k\")"
+    );
+
+    let mut interpreter = Interpreter::new(Config::new(50, 50)).unwrap();
+    let mut errors = Vec::new();
+    let input = "sum(k; 1; 10; k)";
+    let ast = parser::CalcParser::new().parse(&mut errors, input).unwrap();
+    let result = interpreter.eval(ast, input).unwrap().unwrap();
+    assert_eq!(result.to_string(), "55");
+    assert!(interpreter.variables.is_empty());
+}
+
+#[test]
+fn sum_and_product_errors() {
+    testy!(
+        "sum(k; 1; 1000000; k)",
+        "Error: TooManyTerms(\"sum\", \"1000000\", 100000)"
+    );
+    testy!(
+        Config::new(50, 50).with_max_terms(10),
+        "prod(k; 1; 11; k)",
+        "Error: TooManyTerms(\"prod\", \"11\", 10)"
+    );
+    testy!(
+        Config::new(50, 50).with_max_terms(10),
+        "prod(k; 1; 10; k)",
+        "3628800"
+    );
+    testy!(
+        "sum(i; 1; 100000; sum(j; 1; 100000; 1))",
+        "Error: TooManyNestedTerms(\"200000\", 100000)"
+    );
+    testy!(
+        "sum(i; 1; 400; sum(j; 1; 400; 1))",
+        "Error: TooManyNestedTerms(\"100400\", 100000)"
+    );
+    testy!(
+        Config::new(50, 50).with_max_terms(12),
+        "sum(i; 1; 3; sum(j; 1; 3; 1))",
+        "9"
+    );
+    testy!(
+        Config::new(50, 50).with_max_terms(11),
+        "sum(i; 1; 3; sum(j; 1; 3; 1))",
+        "Error: TooManyNestedTerms(\"12\", 11)"
+    );
+    testy_session!(
+        ["f(n) = sum(j; 1; n; 1)"],
+        "sum(i; 1; 1000; f(1000))",
+        "Error: TooManyNestedTerms(\"101000\", 100000)"
+    );
+    testy!("sum(k; 1; 2.5; k)", "Error: NotAnInteger(\"sum\")");
+    testy!(
+        "sum(ans; 1; 3; 1)",
+        "Error: CannotCreateVariableWithHistoryName(\"ans\")"
+    );
+    testy!(
+        "sum(k; 1; 3; x)",
+        "Error: CallingNonexistentVariable(\"x\")"
+    );
+}
+
+#[test]
+fn symbolic_diff() {
+    let radians = || Config::new(50, 50).with_angle_unit(AngleUnit::Radians);
+    testy!(
+        radians(),
+        "diff(x^2 * sin(x); x)",
+        "x^2 * cos(x) + 2 * x * sin(x)"
+    );
+    testy!("diff(sin(x); x)", "PI * cos(x) / 180");
+    testy!(radians(), "diff(ln(x) / x; x)", "(1 - ln(x)) / x^2");
+    testy!(radians(), "diff(x^x; x)", "x^x * (ln(x) + 1)");
+    testy!(radians(), "diff(2^x; x)", "ln(2) * 2^x");
+    testy!(radians(), "diff(sqrt(x); x)", "1 / (2 * sqrt(x))");
+    testy!(radians(), "diff(expe(2x); x)", "2 * expe(2 * x)");
+    testy!(radians(), "diff(-x^2 + 3 - x; x)", "-2 * x - 1");
+    testy!(radians(), "diff(atan(x); x)", "1 / (x^2 + 1)");
+    testy!(radians(), "diff(a * x^2; x)", "2 * a * x");
+    testy!(radians(), "diff([x; x^2]; x)", "[1; 2 * x]");
+    testy!(radians(), "diff(diff(x^4; x); x)", "12 * x^2");
+}
+
+#[test]
+fn diff_at_point() {
+    testy!("diff(x^3; x; 2)", "12");
+    testy!("diff(3x + 1; x)", "3");
+    testy!("diff(x; y)", "0");
+    testy!("diff(sin(x); x; 60)", "0.0087266462599716478846184538");
+    testy_session!(["f(x) = diff(x^3; x)"], "f(2)", "12");
+    testy_session!(["g(x) = x^2 + x", "d = diff(g(x); x)"], "d", "2 * x + 1");
+    testy_session!(["d = diff(x^3; x)"], "diff(d; x; 1)", "6");
+    testy_session!(["v = [3; 4]"], "diff(v[1] * x; x)", "3");
+    testy_session!(["v = [3; 4]"], "diff(v[2] * x^2; x; 2)", "16");
+    testy!("diff([1; 2]; x)", "[0; 0]");
+}
+
+#[test]
+fn diff_errors() {
+    testy!("diff(x!; x)", "Error: NotDifferentiable(\"!\")");
+    testy!("diff(x mod 2; x)", "Error: NotDifferentiable(\"mod\")");
+    testy!("diff(re(x); x)", "Error: NotDifferentiable(\"re\")");
+    testy!(
+        "diff(x^2; PI)",
+        "Error: CannotCreateVariablesWithNameConstant"
+    );
+    testy!(
+        "diff(sin(x; 2); x)",
+        "Error: IncorrectNumberOfArguments(1, 2)"
+    );
+    testy_session!(
+        ["d = diff(x^2; x)"],
+        "d + 1",
+        "Error: ExpressionNotSupported(\"+\")"
+    );
+    testy_session!(
+        ["f(x) = f(x) + 1"],
+        "diff(f(x); x)",
+        "Error: RecursionLimitExceeded"
+    );
+}
+
+#[test]
+fn simplify() {
+    testy!("simplify(x - x)", "0");
+    testy!("simplify((2 * 3) + x)", "x + 6");
+    testy!("simplify(2x + x - x * 0 - 3)", "3 * x - 3");
+    testy!("simplify(y + x)", "x + y");
+    testy!("simplify(b * a * 2)", "2 * a * b");
+    testy!("simplify(x * x^2 / x)", "x^2");
+    testy!("simplify((x^2)^3 * x)", "x^7");
+    testy!("simplify(x / 3 + x / 6)", "0.5 * x");
+    testy!("simplify(x / 3)", "x / 3");
+    testy!("simplify(1 / x + 2 / x)", "3 / x");
+    testy!("simplify(-(x + 1))", "-x - 1");
+    testy!("simplify(3 - x^2)", "3 - x^2");
+    testy!("simplify(x - 2 - (x - 5))", "3");
+    testy!("simplify(sin(x)^2 + sin(x) * sin(x))", "2 * sin(x)^2");
+    testy!("simplify(x mod 2 + x mod 2)", "2 * (x mod 2)");
+    testy!("simplify(x / 0)", "x / 0");
+    testy!("simplify(x / x)", "1");
+    testy!("simplify(0 / x)", "0");
+    testy!("simplify(2 * (x + 1) - 2x)", "2");
+    testy!("simplify((x + 1) / 2)", "0.5 * x + 0.5");
+    testy!("simplify(3 * (x - y) + 3y)", "3 * x");
+    testy!("simplify((x + 1) * (x - 1))", "(x + 1) * (x - 1)");
+    testy!("simplify(x + sin(30))", "x + sin(30)");
+    testy!("simplify(2^10)", "1024");
+    testy_session!(["f(x) = x^2"], "simplify(f(a) + a^2)", "2 * a^2");
+    testy_session!(["d = diff(x^3; x)"], "simplify(d - 3x^2)", "0");
+    testy_session!(["x = 5"], "simplify(x + x)", "2 * x");
+    testy_session!(["f(a) = simplify(a + a)"], "f(4)", "8");
+}
+
+#[test]
+fn simplify_keeps_matrix_order() {
+    testy_session!(
+        ["A = [[1; 2]; [3; 4]]", "B = [[0; 1]; [1; 0]]"],
+        "simplify(B * A)",
+        "B * A"
+    );
+    testy_session!(
+        ["A = [[1; 2]; [3; 4]]", "B = [[0; 1]; [1; 0]]"],
+        "simplify(A * B * A)",
+        "A * B * A"
+    );
+    testy_session!(
+        ["A = [[1; 2]; [3; 4]]", "B = [[0; 1]; [1; 0]]"],
+        "simplify(A * x * A)",
+        "x * A^2"
+    );
+    testy_session!(
+        ["A = [[1; 2]; [3; 4]]", "B = [[0; 1]; [1; 0]]"],
+        "simplify(A * B - B * A)",
+        "A * B - B * A"
+    );
+    testy_session!(
+        ["A = [[1; 2]; [3; 4]]", "B = [[0; 1]; [1; 0]]"],
+        "diff(B * x * A; x)",
+        "[[3; 4]; [1; 2]]"
+    );
+}
+
+#[test]
+fn simplify_api() {
+    let simplify = |input: &str| calc_core::simplify(input).unwrap().to_string();
+    assert_eq!(simplify("(2 * 3) + x"), "x + 6");
+    assert_eq!(simplify("x * y - y * x + 0 * z"), "0");
+    assert_eq!(simplify("x^2 + 1 + 2x"), simplify("2x + x^2 + 1"));
+    assert!(calc_core::simplify("x +").is_err());
+}
+
+#[test]
+fn expression_serialization() {
+    let loaded = testy_saved!(["d = diff(x^2; x)", "d"], [], "2 * x");
+    assert_eq!(loaded.variables[0].value.to_string(), "2 * x");
+}
@@ -1,4 +1,8 @@
 use calc_core::parser;
+use interpreter::{
+    ast::expr::Expr,
+    errors::{CalcError, Span},
+};
 use rust_decimal::Decimal;
 
 /// * expected - полученное в результате парсинга значение.
@@ -28,6 +32,16 @@ macro_rules! testy_is_ok {
     };
 }
 
+/// * name - имя структуры lalrpop, с которой будут парсится данные.
+/// * expected - полученное в результате парсинга значение.
+macro_rules! testy_is_err {
+    //  проверяем, что парсер отверг ввод.
+    ($name: ident, $expected: expr) => {
+        let mut errors = Vec::new();
+        assert!(parser::$name::new().parse(&mut errors, $expected).is_err());
+    };
+}
+
 /// * name - имя структуры lalrpop, с которой будут парсится данные.
 /// * expected - полученное в результате парсинга значение.
 /// * received - ожидаемое значение.
@@ -41,31 +55,85 @@ macro_rules! testy_struct {
     };
 }
 
+/// * input - ввод с синтаксической ошибкой.
+/// * span - ожидаемый участок ввода с ошибкой.
+/// * expected - ожидаемые на месте ошибки токены.
+macro_rules! testy_diagnostic {
+    ($input: expr, $span: expr, [$($expected: expr),*]) => {
+        let diagnostic = calc_core::parse($input).unwrap_err().remove(0);
+        assert_eq!(diagnostic.span, Some(Span::new($span.start, $span.end)));
+        $(assert!(diagnostic.expected.contains(&$expected.to_string()));)*
+    };
+}
+
 #[test]
 fn number() {
-    testy_struct!(NumParser, "2", Decimal::try_from(2.0).unwrap());
+    testy_struct!(
+        NumParser,
+        "2",
+        Expr::Number(Decimal::try_from(2.0).unwrap())
+    );
 
-    testy_struct!(NumParser, "-22.7", Decimal::try_from(-22.7).unwrap());
+    testy_struct!(
+        NumParser,
+        "22.7",
+        Expr::Number(Decimal::try_from(22.7).unwrap())
+    );
 
     testy_struct!(
         NumParser,
-        "+222.222222",
-        Decimal::try_from(222.222222).unwrap()
+        "222.222222",
+        Expr::Number(Decimal::try_from(222.222222).unwrap())
     );
 
-    testy_struct!(NumParser, "222.", Decimal::try_from(222.).unwrap());
+    testy_struct!(
+        NumParser,
+        "222.",
+        Expr::Number(Decimal::try_from(222.).unwrap())
+    );
 
-    testy_struct!(NumParser, "22,7", Decimal::try_from(22.7).unwrap());
+    testy_struct!(
+        NumParser,
+        "22,7",
+        Expr::Number(Decimal::try_from(22.7).unwrap())
+    );
 
     testy_struct!(
         NumParser,
-        "+222,222222",
-        Decimal::try_from(222.222222).unwrap()
+        "222,222222",
+        Expr::Number(Decimal::try_from(222.222222).unwrap())
     );
 
-    testy_struct!(NumParser, "222,", Decimal::try_from(222.0).unwrap());
+    testy_struct!(
+        NumParser,
+        "222,",
+        Expr::Number(Decimal::try_from(222.0).unwrap())
+    );
 
-    testy_struct!(NumParser, "2,", Decimal::try_from(2.0).unwrap());
+    testy_struct!(
+        NumParser,
+        "2,",
+        Expr::Number(Decimal::try_from(2.0).unwrap())
+    );
+
+    testy_struct!(
+        NumParser,
+        "123456789012345678901234567890123",
+        Expr::Literal("123456789012345678901234567890123".to_string())
+    );
+}
+
+#[test]
+fn signed_number() {
+    testy!["-22.7", "(-22.7)"];
+
+    testy!["+222.222222", "222.222222"];
+
+    testy!["34 -5", "(34 - 5)"];
+
+    testy!["34 * -5", "(34 * (-5))"];
+
+    testy!["--5", "(-(-5))"];
 }
 
 #[test]
@@ -103,6 +171,12 @@ fn func() {
     testy!["sin(7) * 7", "(sin(7) * 7)"];
 
     testy!["sin(cos(2) * 7)", "sin((cos(2) * 7))"];
+
+    testy!["exp(2; 3)", "exp(2 3)"];
+
+    testy!["log(8; 2) + ln(x)", "(log(8 2) + ln(\"x\"))"];
+
+    testy!["log10(100) * log2(8)", "(log10(100) * log2(8))"];
 }
 
 #[test]
@@ -120,6 +194,15 @@ fn variable() {
     testy!["name * sin(foo)", "(\"name\" * sin(\"foo\"))"];
 }
 
+#[test]
+fn history_variable() {
+    testy!["ans + _", "(\"ans\" + \"_\")"];
+
+    testy!["m12 - 1", "(\"m12\" - 1)"];
+
+    testy!["2m1", "(2 * \"m1\")"];
+}
+
 #[test]
 fn init_variable() {
     testy!["name = 2", "name = 2"];
@@ -130,3 +213,299 @@ fn init_variable() {
 
     testy!["name = (1 * 2) / 3", "name = ((1 * 2) / 3)"];
 }
+
+#[test]
+fn init_function() {
+    testy!["f(x) = x * 2", "f(x) = (\"x\" * 2)"];
+
+    testy!["f(x; y) = x - y", "f(x y) = (\"x\" - \"y\")"];
+
+    testy!["area(r) = PI * exp(r)", "area(r) = (\"PI\" * exp(\"r\"))"];
+
+    testy_is_err!(CalcParser, "f(2) = 2");
+
+    testy_is_err!(CalcParser, "f(x + 1) = x");
+}
+
+#[test]
+fn call_function() {
+    testy!["f(2)", "f(2)"];
+
+    testy!["f(2; x) - 1", "(f(2 \"x\") - 1)"];
+
+    testy!["2 * f(sin(3))", "(2 * f(sin(3)))"];
+
+    testy!["f (2)", "(\"f\" * 2)"];
+
+    testy!["10 mod(3)", "(10 mod 3)"];
+
+    testy!["10 mod(3)^2", "(10 mod (3 ^ 2))"];
+
+    testy!["12 and(10) + 1", "(12 and (10 + 1))"];
+
+    testy!["not(5) + 1", "((not 5) + 1)"];
+
+    testy!["1 shl(2) * 3", "(1 shl (2 * 3))"];
+}
+
+#[test]
+fn unary_minus() {
+    testy!["-(2 + 3)", "(-(2 + 3))"];
+
+    testy!["-PI", "(-\"PI\")"];
+
+    testy!["-sin(2) * 3", "((-sin(2)) * 3)"];
+
+    testy!["2 - -x", "(2 - (-\"x\"))"];
+}
+
+#[test]
+fn power() {
+    testy!["2 ^ 3", "(2 ^ 3)"];
+
+    testy!["2 ^ 3 ^ 2", "(2 ^ (3 ^ 2))"];
+
+    testy!["-2 ^ 2", "(-(2 ^ 2))"];
+
+    testy!["2 ^ -1", "(2 ^ (-1))"];
+
+    testy!["3 * 2 ^ 2 + 1", "((3 * (2 ^ 2)) + 1)"];
+
+    testy!["2 3 ^ 2", "(2 * (3 ^ 2))"];
+}
+
+#[test]
+fn postfix() {
+    testy!["5!", "(5!)"];
+
+    testy!["3!!", "((3!)!)"];
+
+    testy!["2 ^ 3!", "(2 ^ (3!))"];
+
+    testy!["-3!", "(-(3!))"];
+
+    testy!["50%", "(50%)"];
+
+    testy!["200 * 15%", "(200 * (15%))"];
+}
+
+#[test]
+fn unit_conversion() {
+    testy!["3 km to mi", "((3 * \"km\") to \"mi\")"];
+
+    testy![
+        "3 km + 250 m to mi",
+        "(((3 * \"km\") + (250 * \"m\")) to \"mi\")"
+    ];
+
+    testy![
+        "60 km/h to m/s",
+        "(((60 * \"km\") / \"h\") to (\"m\" / \"s\"))"
+    ];
+
+    testy![
+        "9.81 m/s^2 * 70 kg",
+        "((((9.81 * \"m\") / (\"s\" ^ 2)) * 70) * \"kg\")"
+    ];
+
+    testy!["1 h to min to s", "(((1 * \"h\") to \"min\") to \"s\")"];
+}
+
+#[test]
+fn integer_literals() {
+    testy!["0xFF", "0xFF"];
+
+    testy!["0b1010 + 0o17", "(0b1010 + 0o17)"];
+
+    testy!["0xff", "0xFF"];
+
+    testy_is_err!(IntLiteralParser, "0xFFFFFFFFFFFFFFFFFFFFFFFFF");
+
+    testy_is_err!(IntLiteralParser, "0b102");
+
+    testy_is_err!(IntLiteralParser, "0o19");
+
+    testy_is_err!(IntLiteralParser, "0xFG");
+
+    assert_eq!(
+        calc_core::parse("0b102").unwrap_err().remove(0).error,
+        CalcError::InvalidDigit("2".to_string(), "binary".to_string())
+    );
+}
+
+#[test]
+fn bitwise() {
+    testy!["1 shl 4 - 1", "(1 shl (4 - 1))"];
+
+    testy!["1 or 2 xor 3 and 4", "(1 or (2 xor (3 and 4)))"];
+
+    testy!["not 5 and 3", "((not 5) and 3)"];
+
+    testy!["-not 0", "(-(not 0))"];
+
+    testy!["0xF0 or 0x0F to mi", "((0xF0 or 0xF) to \"mi\")"];
+}
+
+#[test]
+fn matrices() {
+    testy!["[1; 2; 3]", "[1; 2; 3]"];
+
+    testy!["[[1; 2]; [3; 4]]", "[[1; 2]; [3; 4]]"];
+
+    testy!["m[1; 2] + v[3]", "(\"m\"[1; 2] + \"v\"[3])"];
+
+    testy!["v [2]", "(\"v\" * [2])"];
+
+    testy!["a .* b ./ c", "((\"a\" .* \"b\") ./ \"c\")"];
+
+    testy!["a .^ 2 .^ 3", "(\"a\" .^ (2 .^ 3))"];
+
+    testy!["-[1; 2] .^ 2", "(-([1; 2] .^ 2))"];
+}
+
+#[test]
+fn solve() {
+    testy![
+        "solve(2x + y = 3; x - y = 0; x; y)",
+        "solve((((2 * \"x\") + \"y\") = 3) ((\"x\" - \"y\") = 0) \"x\" \"y\")"
+    ];
+
+    testy!["solve(x = 2 y; x)", "solve((\"x\" = (2 * \"y\")) \"x\")"];
+
+    testy![
+        "solve(x^3 = 5; x; -2)",
+        "solve(((\"x\" ^ 3) = 5) \"x\" (-2))"
+    ];
+
+    assert!(calc_core::parse("sin(x = 1)").is_err());
+
+    assert!(calc_core::parse("solve(x = 1 = 2; x)").is_err());
+}
+
+#[test]
+fn diff() {
+    testy!["diff(x^2; x)", "diff((\"x\" ^ 2) \"x\")"];
+
+    testy!["diff(sin(x); x; PI)", "diff(sin(\"x\") \"x\" \"PI\")"];
+
+    assert!(calc_core::parse("diff(x^2; 2)").is_err());
+}
+
+#[test]
+fn simplify() {
+    testy!["simplify(2x + x)", "simplify(((2 * \"x\") + \"x\"))"];
+}
+
+#[test]
+fn integrate_and_derivative() {
+    testy![
+        "integrate(x^2; x; 0; 1)",
+        "integrate((\"x\" ^ 2) \"x\" 0 1)"
+    ];
+
+    testy![
+        "derivative(sin(x); x; PI / 2)",
+        "derivative(sin(\"x\") \"x\" (\"PI\" / 2))"
+    ];
+
+    assert!(calc_core::parse("integrate(x; x; 0)").is_err());
+
+    assert!(calc_core::parse("derivative(x^2; 2; 1)").is_err());
+}
+
+#[test]
+fn sum_and_product() {
+    testy!["sum(i; 1; 100; i^2)", "sum(\"i\" 1 100 (\"i\" ^ 2))"];
+
+    testy![
+        "prod(k; 2; n; 1 - 1/k^2)",
+        "prod(\"k\" 2 \"n\" (1 - (1 / (\"k\" ^ 2))))"
+    ];
+
+    assert!(calc_core::parse("sum(i; 1; 10)").is_err());
+
+    assert!(calc_core::parse("prod(2; 1; 10; k)").is_err());
+}
+
+#[test]
+fn expression_display() {
+    for input in [
+        "2 * x + cos(x)",
+        "(a + b) * (c - d)",
+        "a - (b - c)",
+        "a / (b * c)",
+        "-x^2",
+        "(-x)^2",
+        "x^-2",
+        "2^3^2",
+        "(2^3)^2",
+        "x! + 5%",
+        "(a + b)!",
+        "3 * km to mi",
+        "(1 or 2) and 3",
+        "[1; x^2]",
+        "m[1; 2]",
+        "diff(x^3; x; 2)",
+        "simplify(x + x)",
+        "integrate(x^2; x; 0; 1)",
+        "derivative(sin(x); x; a + 1)",
+        "sum(i; 1; n; i^2)",
+        "prod(k; 2; 10; 1 - 1 / k^2)",
+    ] {
+        let mut errors = Vec::new();
+        let parsed = parser::ExprParser::new().parse(&mut errors, input).unwrap();
+        assert_eq!(parsed.to_string(), input);
+    }
+}
+
+#[test]
+fn syntax_error_span() {
+    testy_diagnostic!("2 + * 3", 4..5, [
+        "number",
+        "variable",
+        "function call",
+        "("
+    ]);
+
+    testy_diagnostic!("(2 + 3", 6..6, [")"]);
+
+    testy_diagnostic!("sin(2", 5..5, [")", ";"]);
+
+    testy_diagnostic!("2 $ 3", 2..3, []);
+
+    testy_diagnostic!("0xFF and", 8..8, ["number", "not"]);
+
+    testy_diagnostic!("2 + ä", 4..6, []);
+}
+
+#[test]
+fn expected_tokens_message() {
+    let message = |input| calc_core::parse(input).unwrap_err()[0].expected.join(", ");
+
+    assert_eq!(
+        message("2 + * 3"),
+        "(, +, -, [, variable, derivative(...), diff(...), integrate(...), not, prod(...), \
+         simplify(...), solve(...), sum(...), number, function call"
+    );
+}
+
+#[test]
+fn all_syntax_errors() {
+    let spans = |input| {
+        calc_core::parse(input)
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| diagnostic.span)
+            .collect::<Vec<Option<Span>>>()
+    };
+
+    assert_eq!(spans("(2 +) * (3 * )"), vec![
+        Some(Span::new(4, 5)),
+        Some(Span::new(13, 14))
+    ]);
+    assert_eq!(spans("2 + * 3 *"), vec![
+        Some(Span::new(4, 5)),
+        Some(Span::new(9, 9))
+    ]);
+    assert_eq!(spans("2 + * 3").len(), 1);
+}
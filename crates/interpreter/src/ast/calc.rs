@@ -1,16 +1,38 @@
 use super::expr::Expr;
+use crate::errors::CalcError;
 use std::fmt::{Debug, Error, Formatter};
 
-pub enum Calc<'input> {
-    InitVariable(&'input str, Box<Expr<'input>>),
-    Expr(Box<Expr<'input>>),
+pub enum Calc {
+    InitVariable(String, Box<Expr>),
+    InitFunction(String, Vec<String>, Box<Expr>),
+    Expr(Box<Expr>),
 }
 
-impl Debug for Calc<'_> {
+impl Calc {
+    /// Создаёт определение функции `name`, если все её аргументы - имена переменных.
+    pub fn init_function(
+        name: &str, args: Vec<Box<Expr>>, body: Box<Expr>,
+    ) -> Result<Self, CalcError> {
+        let params = args
+            .into_iter()
+            .map(|arg| match arg.unspanned() {
+                Expr::Variable(param) => Ok(param.clone()),
+                _ => Err(CalcError::InvalidFunctionParameters),
+            })
+            .collect::<Result<Vec<String>, CalcError>>()?;
+
+        Ok(Calc::InitFunction(name.to_string(), params, body))
+    }
+}
+
+impl Debug for Calc {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
-        use self::Calc::{Expr, InitVariable};
+        use self::Calc::{Expr, InitFunction, InitVariable};
         match self {
             InitVariable(name, ref expr) => write!(fmt, "{name} = {expr:?}"),
+            InitFunction(name, params, ref expr) => {
+                write!(fmt, "{name}({}) = {expr:?}", params.join(" "))
+            }
             Expr(expr) => write!(fmt, "{expr:?}"),
         }
     }
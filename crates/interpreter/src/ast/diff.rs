@@ -0,0 +1,277 @@
+//! Символьное дифференцирование: `diff(x^2 * sin(x); x)` даёт `x^2 * cos(x) + 2 * x * sin(x)`.
+//!
+//! Сначала `symbolic::expand` подставляет в выражение тела пользовательских функций и
+//! переменные, в которых хранятся выражения. Затем производная строится по правилам для
+//! каждого оператора и встроенной функции и упрощается `simplify`. Тригонометрические функции
+//! учитывают единицы углов: в градусах производная `sin(x)` равна `PI * cos(x) / 180`.
+
+use rust_decimal::Decimal;
+
+use super::{
+    expr::{Evaluatable, Expr},
+    opcode::{Opcode, UnaryOpcode},
+    simplify::simplify_in,
+};
+use crate::{
+    config::AngleUnit,
+    errors::CalcError,
+    interpreter::Interpreter,
+    symbolic::{self, Expression},
+    value::Value,
+};
+
+/// Вычисляет `diff(body; var; point)`. Без точки возвращает производную как выражение,
+/// а если в ней не осталось неизвестных имён - её значение.
+pub fn evaluate(
+    interpreter: &mut Interpreter, body: &Expr, var: &str, point: Option<&Expr>,
+) -> Result<Value, CalcError> {
+    interpreter.check_variable_name(var)?;
+    let derivative = derivative(interpreter, body, var)?;
+
+    if let Some(point) = point {
+        let point = point.evaluate(interpreter)?;
+        return derivative
+            .substitute(&[var.to_string()], &[point])
+            .evaluate(interpreter);
+    }
+    match is_symbolic(interpreter, &derivative, var) {
+        true => Ok(Value::Expression(Expression::new(derivative))),
+        false => derivative.evaluate(interpreter),
+    }
+}
+
+/// Упрощённая производная `body` по `var`.
+pub fn derivative(interpreter: &Interpreter, body: &Expr, var: &str) -> Result<Expr, CalcError> {
+    let expanded = symbolic::expand(interpreter, body, Some(var), 0)?;
+    let derivative = Derivative {
+        var,
+        angle_unit: interpreter.config.angle_unit,
+    }
+    .derive(&expanded)?;
+    Ok(simplify_in(interpreter, &derivative))
+}
+
+/// Есть ли в выражении `var` или имена, которым не присвоено значение.
+fn is_symbolic(interpreter: &mut Interpreter, expr: &Expr, var: &str) -> bool {
+    symbolic::variables(expr)
+        .iter()
+        .any(|name| name == var || Expr::get_variable(interpreter, name).is_err())
+}
+
+/// Правила дифференцирования по переменной `var`.
+struct Derivative<'a> {
+    var: &'a str,
+    angle_unit: AngleUnit,
+}
+
+impl Derivative<'_> {
+    /// Производная `expr`. Любое выражение без `var`, даже `v[1]`, - константа, кроме матрицы:
+    /// производная матрицы - матрица производных её элементов.
+    fn derive(&self, expr: &Expr) -> Result<Expr, CalcError> {
+        if !matches!(expr.unspanned(), Expr::Matrix(_)) && self.is_constant(expr) {
+            return Ok(number(0));
+        }
+        match expr {
+            Expr::Number(_) | Expr::Literal(_) | Expr::Value(_) => Ok(number(0)),
+            Expr::Variable(name) => Ok(number(i64::from(name == self.var))),
+            Expr::Op(left, op, right) => self.derive_op(left, *op, right),
+            Expr::UnaryOp(UnaryOpcode::Neg, value) => Ok(neg(self.derive(value)?)),
+            Expr::UnaryOp(UnaryOpcode::Percent, value) => Ok(div(self.derive(value)?, number(100))),
+            Expr::UnaryOp(op, _) => Err(CalcError::NotDifferentiable(
+                format!("{op:?}").trim().to_string(),
+            )),
+            Expr::Call(name, args) => self.derive_call(name, args),
+            Expr::Matrix(elements) => elements
+                .iter()
+                .map(|element| self.derive(element).map(Box::new))
+                .collect::<Result<Vec<Box<Expr>>, CalcError>>()
+                .map(Expr::Matrix),
+            Expr::Spanned(_, expr) => self.derive(expr),
+            _ => Err(CalcError::NotDifferentiable(expr.to_string())),
+        }
+    }
+
+    /// Не зависит ли выражение от `var`. Функции и переменные-выражения уже подставлены.
+    fn is_constant(&self, expr: &Expr) -> bool {
+        !symbolic::variables(expr)
+            .iter()
+            .any(|name| name == self.var)
+    }
+
+    fn derive_op(&self, left: &Expr, op: Opcode, right: &Expr) -> Result<Expr, CalcError> {
+        let (u, v) = (left.clone(), right.clone());
+        match op {
+            Opcode::Add => Ok(add(self.derive(left)?, self.derive(right)?)),
+            Opcode::Sub => Ok(sub(self.derive(left)?, self.derive(right)?)),
+            Opcode::Mul | Opcode::ElemMul => {
+                Ok(add(mul(self.derive(left)?, v), mul(u, self.derive(right)?)))
+            }
+            Opcode::Div | Opcode::ElemDiv if self.is_constant(right) => {
+                Ok(div(self.derive(left)?, v))
+            }
+            Opcode::Div | Opcode::ElemDiv => Ok(div(
+                sub(
+                    mul(self.derive(left)?, v.clone()),
+                    mul(u, self.derive(right)?),
+                ),
+                pow(v, number(2)),
+            )),
+            // (u^n)' = n * u^(n - 1) * u'
+            Opcode::Pow | Opcode::ElemPow if self.is_constant(right) => Ok(mul(
+                mul(v.clone(), pow(u, sub(v, number(1)))),
+                self.derive(left)?,
+            )),
+            // (a^v)' = a^v * ln(a) * v'
+            Opcode::Pow | Opcode::ElemPow if self.is_constant(left) => Ok(mul(
+                mul(pow(u.clone(), v), call("ln", vec![u])),
+                self.derive(right)?,
+            )),
+            // (u^v)' = u^v * (v' * ln(u) + v * u' / u)
+            Opcode::Pow | Opcode::ElemPow => Ok(mul(
+                pow(u.clone(), v.clone()),
+                add(
+                    mul(self.derive(right)?, call("ln", vec![u.clone()])),
+                    div(mul(v, self.derive(left)?), u),
+                ),
+            )),
+            _ => Err(CalcError::NotDifferentiable(format!("{op:?}"))),
+        }
+    }
+
+    fn derive_call(&self, name: &str, args: &[Box<Expr>]) -> Result<Expr, CalcError> {
+        let u = args
+            .first()
+            .map_or_else(|| number(0), |arg| (**arg).clone());
+        let second = args.get(1).map(|arg| (**arg).clone());
+        let one_minus_square = || sub(number(1), pow(u.clone(), number(2)));
+        let one_plus_square = || add(number(1), pow(u.clone(), number(2)));
+
+        // Производная функции по её аргументу, без множителя u'.
+        let outer =
+            match (name, second) {
+                ("sin", _) => self.scale_argument(call("cos", vec![u.clone()])),
+                ("cos", _) => neg(self.scale_argument(call("sin", vec![u.clone()]))),
+                ("tg", _) => self
+                    .scale_argument(div(number(1), pow(call("cos", vec![u.clone()]), number(2)))),
+                ("ctg", _) => neg(self
+                    .scale_argument(div(number(1), pow(call("sin", vec![u.clone()]), number(2))))),
+                ("asin", _) => self.scale_result(div(number(1), sqrt(one_minus_square()))),
+                ("acos", _) => neg(self.scale_result(div(number(1), sqrt(one_minus_square())))),
+                ("atan", _) => self.scale_result(div(number(1), one_plus_square())),
+                ("actg", _) => neg(self.scale_result(div(number(1), one_plus_square()))),
+                // atan2(y; x)' = (x * y' - y * x') / (x^2 + y^2)
+                ("atan2", Some(x)) => {
+                    return Ok(self.scale_result(div(
+                        sub(
+                            mul(x.clone(), self.derive(&u)?),
+                            mul(u.clone(), self.derive(&x)?),
+                        ),
+                        add(pow(x, number(2)), pow(u, number(2))),
+                    )))
+                }
+                ("sinh", _) => call("cosh", vec![u.clone()]),
+                ("cosh", _) => call("sinh", vec![u.clone()]),
+                ("tanh", _) => div(number(1), pow(call("cosh", vec![u.clone()]), number(2))),
+                ("asinh", _) => div(number(1), sqrt(add(pow(u.clone(), number(2)), number(1)))),
+                ("acosh", _) => div(number(1), sqrt(sub(pow(u.clone(), number(2)), number(1)))),
+                ("atanh", _) => div(number(1), one_minus_square()),
+                ("deg", _) => from_radians_factor(AngleUnit::Degrees),
+                ("rad", _) => to_radians_factor(AngleUnit::Degrees),
+                ("expe", _) => call("expe", vec![u.clone()]),
+                ("ln", _) => div(number(1), u.clone()),
+                ("log10", _) => div(number(1), mul(u.clone(), call("ln", vec![number(10)]))),
+                ("log2", _) => div(number(1), mul(u.clone(), call("ln", vec![number(2)]))),
+                ("log", base) => {
+                    let base = base.unwrap_or_else(|| number(10));
+                    return self.derive(&div(call("ln", vec![u]), call("ln", vec![base])));
+                }
+                ("pow" | "exp", power) => {
+                    return self.derive(&pow(u, power.unwrap_or_else(|| number(2))));
+                }
+                ("sqrt" | "rt", None) => div(number(1), mul(number(2), sqrt(u.clone()))),
+                ("sqrt" | "rt", Some(degree)) if degree.unspanned() == &number(2) => {
+                    div(number(1), mul(number(2), sqrt(u.clone())))
+                }
+                ("sqrt" | "rt", Some(degree)) => {
+                    return self.derive(&pow(u, div(number(1), degree)));
+                }
+                ("abs", _) => div(u.clone(), call("abs", vec![u.clone()])),
+                _ => return Err(CalcError::NotDifferentiable(name.to_string())),
+            };
+        Ok(mul(outer, self.derive(&u)?))
+    }
+
+    /// Производная функции угла: угол переводится в радианы множителем `PI / 180`.
+    fn scale_argument(&self, derivative: Expr) -> Expr {
+        match self.angle_unit {
+            AngleUnit::Radians => derivative,
+            unit => mul(derivative, to_radians_factor(unit)),
+        }
+    }
+
+    /// Производная функции, которая возвращает угол: `asin`, `atan`...
+    fn scale_result(&self, derivative: Expr) -> Expr {
+        match self.angle_unit {
+            AngleUnit::Radians => derivative,
+            unit => mul(from_radians_factor(unit), derivative),
+        }
+    }
+}
+
+/// Множитель перевода угла в радианы в виде выражения: `PI / 180`.
+fn to_radians_factor(unit: AngleUnit) -> Expr {
+    div(Expr::Variable("PI".to_string()), half_turn(unit))
+}
+
+/// Множитель перевода угла из радиан в виде выражения: `180 / PI`.
+fn from_radians_factor(unit: AngleUnit) -> Expr {
+    div(half_turn(unit), Expr::Variable("PI".to_string()))
+}
+
+fn half_turn(unit: AngleUnit) -> Expr {
+    match unit {
+        AngleUnit::Degrees => number(180),
+        AngleUnit::Gradians => number(200),
+        AngleUnit::Radians => Expr::Variable("PI".to_string()),
+    }
+}
+
+fn number(value: i64) -> Expr {
+    Expr::Number(Decimal::from(value))
+}
+
+fn op(left: Expr, op: Opcode, right: Expr) -> Expr {
+    Expr::Op(Box::new(left), op, Box::new(right))
+}
+
+fn add(left: Expr, right: Expr) -> Expr {
+    op(left, Opcode::Add, right)
+}
+
+fn sub(left: Expr, right: Expr) -> Expr {
+    op(left, Opcode::Sub, right)
+}
+
+fn mul(left: Expr, right: Expr) -> Expr {
+    op(left, Opcode::Mul, right)
+}
+
+fn div(left: Expr, right: Expr) -> Expr {
+    op(left, Opcode::Div, right)
+}
+
+fn pow(left: Expr, right: Expr) -> Expr {
+    op(left, Opcode::Pow, right)
+}
+
+fn neg(value: Expr) -> Expr {
+    Expr::UnaryOp(UnaryOpcode::Neg, Box::new(value))
+}
+
+fn call(name: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(name.to_string(), args.into_iter().map(Box::new).collect())
+}
+
+fn sqrt(value: Expr) -> Expr {
+    call("sqrt", vec![value])
+}
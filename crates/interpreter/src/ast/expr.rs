@@ -1,63 +1,477 @@
 use super::{
-    func::FactoryFunc,
-    func_name::FuncName,
-    opcode::{Opcode, Operation},
+    diff, numeric,
+    opcode::{Opcode, Operation, UnaryOpcode, UnaryOperation},
+    operation::FactoryOp,
+    series, simplify, solve,
+};
+use crate::{
+    constante::Constant,
+    errors::{CalcError, Span},
+    history::History,
+    interpreter::Interpreter,
+    matrix::Matrix,
+    quantity::Quantity,
+    traits::GetResult,
+    unit::Unit,
+    value::Value,
 };
-use crate::{errors::CalcError, interpreter::Interpreter, traits::GetResult};
 use rust_decimal::Decimal;
-use std::fmt::{Debug, Error, Formatter};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display, Error, Formatter};
 
-#[derive(Clone)]
-pub enum Expr<'input> {
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
     Number(Decimal),
-    Variable(&'input str),
-    Op(Box<Expr<'input>>, Opcode, Box<Expr<'input>>),
-    Func(FuncName, Vec<Box<Expr<'input>>>),
+    /// Число из ввода, которое не помещается в `Decimal` без потерь:
+    /// `123456789012345678901234567890123`. Его значение зависит от режима чисел.
+    Literal(String),
+    /// Уже вычисленное значение, например аргумент, подставленный в тело функции.
+    Value(Value),
+    Variable(String),
+    Op(Box<Expr>, Opcode, Box<Expr>),
+    UnaryOp(UnaryOpcode, Box<Expr>),
+    Call(String, Vec<Box<Expr>>),
+    /// Литерал вектора или матрицы: `[1; 2]`, `[[1; 2]; [3; 4]]`.
+    Matrix(Vec<Box<Expr>>),
+    /// Элемент или строка матрицы: `v[2]`, `m[1; 2]`. Индексы начинаются с единицы.
+    Index(Box<Expr>, Vec<Box<Expr>>),
+    /// Уравнение `a = b` в аргументах `solve`. Его значение - невязка `a - b`.
+    Equation(Box<Expr>, Box<Expr>),
+    /// `solve(уравнения; неизвестные)`: решает уравнения и записывает корни в переменные.
+    Solve(Vec<Box<Expr>>),
+    /// `diff(выражение; x)` - производная по `x`, а с третьим аргументом - её значение в точке.
+    Diff(Box<Expr>, String, Option<Box<Expr>>),
+    /// `simplify(выражение)` - упрощённая запись выражения.
+    Simplify(Box<Expr>),
+    /// `integrate(выражение; x; a; b)` - численный интеграл по `x` от `a` до `b`.
+    Integrate(Box<Expr>, String, Box<Expr>, Box<Expr>),
+    /// `derivative(выражение; x; точка)` - численная производная по `x` в точке.
+    Derivative(Box<Expr>, String, Box<Expr>),
+    /// `sum(i; a; b; выражение)` - сумма выражения по целым `i` от `a` до `b`.
+    Sum(String, Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `prod(i; a; b; выражение)` - произведение выражения по целым `i` от `a` до `b`.
+    Product(String, Box<Expr>, Box<Expr>, Box<Expr>),
     Error(CalcError),
+    /// Выражение вместе с участком ввода, из которого оно получено.
+    /// Нужен только для того, чтобы показать пользователю место ошибки.
+    Spanned(Span, Box<Expr>),
 }
 
 pub trait Evaluatable {
-    fn evaluate(&self, interpreter: &mut Interpreter) -> Result<Decimal, CalcError>;
+    fn evaluate(&self, interpreter: &mut Interpreter) -> Result<Value, CalcError>;
 }
 
-impl Debug for Expr<'_> {
+impl Debug for Expr {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
-        use self::Expr::{Error, Func, Number, Op, Variable};
+        use self::Expr::{
+            Call, Derivative, Diff, Equation, Error, Index, Integrate, Literal, Matrix, Number, Op,
+            Product, Simplify, Solve, Spanned, Sum, UnaryOp, Value, Variable,
+        };
+
+        fn join_args(args: &[Box<Expr>]) -> String {
+            args.iter()
+                .map(|val| format!("{val:?}"))
+                .collect::<Vec<String>>()
+                .join(" ")
+        }
+
+        fn join_elements(elements: &[Box<Expr>]) -> String {
+            elements
+                .iter()
+                .map(|val| format!("{val:?}"))
+                .collect::<Vec<String>>()
+                .join("; ")
+        }
+
         match self {
             Number(n) => write!(fmt, "{n:?}"),
+            Literal(literal) => write!(fmt, "{literal}"),
+            Value(v) => write!(fmt, "{v:?}"),
             Op(l, op, r) => write!(fmt, "({l:?} {op:?} {r:?})"),
-            Func(func, args) => {
-                let str = args
-                    .iter()
-                    .map(|val| format!("{val:?}"))
-                    .collect::<Vec<String>>()
-                    .join(" ");
-                write!(fmt, "{func:?}({str})")
+            UnaryOp(op, v) if op.is_prefix() => write!(fmt, "({op:?}{v:?})"),
+            UnaryOp(op, v) => write!(fmt, "({v:?}{op:?})"),
+            Call(name, args) => write!(fmt, "{name}({})", join_args(args)),
+            Matrix(elements) => write!(fmt, "[{}]", join_elements(elements)),
+            Index(target, indices) => write!(fmt, "{target:?}[{}]", join_elements(indices)),
+            Equation(left, right) => write!(fmt, "({left:?} = {right:?})"),
+            Solve(args) => write!(fmt, "solve({})", join_args(args)),
+            Diff(body, var, None) => write!(fmt, "diff({body:?} {var:?})"),
+            Diff(body, var, Some(point)) => write!(fmt, "diff({body:?} {var:?} {point:?})"),
+            Simplify(value) => write!(fmt, "simplify({value:?})"),
+            Integrate(body, var, from, to) => {
+                write!(fmt, "integrate({body:?} {var:?} {from:?} {to:?})")
+            }
+            Derivative(body, var, at) => write!(fmt, "derivative({body:?} {var:?} {at:?})"),
+            Sum(index, from, to, body) => write!(fmt, "sum({index:?} {from:?} {to:?} {body:?})"),
+            Product(index, from, to, body) => {
+                write!(fmt, "prod({index:?} {from:?} {to:?} {body:?})")
             }
             Error(msg) => write!(fmt, "Ошибка: {msg:?}"),
             Variable(name) => write!(fmt, "{name:?}"),
+            Spanned(_, expr) => write!(fmt, "{expr:?}"),
         }
     }
 }
 
-impl Expr<'_> {
-    pub fn get_variable(interpreter: &mut Interpreter, name: &&str) -> Result<Decimal, CalcError> {
+impl Expr {
+    /// Оборачивает выражение в `Expr::Spanned` с участком ввода `start..end`.
+    #[must_use]
+    pub fn spanned(start: usize, end: usize, expr: Expr) -> Box<Self> {
+        Box::new(Expr::Spanned(Span::new(start, end), Box::new(expr)))
+    }
+
+    /// Возвращает выражение без обёрток `Expr::Spanned`.
+    #[must_use]
+    pub fn unspanned(&self) -> &Expr {
+        match self {
+            Expr::Spanned(_, expr) => expr.unspanned(),
+            _ => self,
+        }
+    }
+
+    /// Неявное умножение `left right`: `2x`, `3 km`. Константа, которая называется как
+    /// единица, сразу после числа становится единицей: `5 g` - пять граммов, а не пять
+    /// ускорений свободного падения.
+    #[must_use]
+    pub fn implicit_mul(left: Box<Expr>, right: Box<Expr>) -> Expr {
+        fn ends_with_number(expr: &Expr) -> bool {
+            match expr.unspanned() {
+                Expr::Number(_) | Expr::Literal(_) => true,
+                Expr::UnaryOp(UnaryOpcode::Neg, value) => ends_with_number(value),
+                Expr::Op(_, Opcode::Mul | Opcode::Div, right) => ends_with_number(right),
+                _ => false,
+            }
+        }
+
+        let unit = match right.unspanned() {
+            Expr::Variable(name) if Constant::is_builtin(name) && ends_with_number(&left) => {
+                Unit::find(name)
+            }
+            _ => None,
+        };
+        let right = match unit {
+            Some(unit) => Box::new(Expr::Value(Value::Quantity(Quantity::new(
+                Decimal::ONE,
+                unit,
+            )))),
+            None => right,
+        };
+        Expr::Op(left, Opcode::Mul, right)
+    }
+
+    /// Имя ищется среди временных связей `integrate` и `derivative`, результатов истории,
+    /// переменных, констант и, наконец, единиц измерения.
+    pub fn get_variable(interpreter: &mut Interpreter, name: &str) -> Result<Value, CalcError> {
+        if let Some(value) = interpreter.binding(name) {
+            return Ok(value);
+        }
+        if let Some(index) = History::index_by_name(name) {
+            return interpreter
+                .get_history_result(index)
+                .ok_or(CalcError::HistoryEntryNotFound(name.to_string()))?;
+        }
+
         interpreter
             .variables
             .get_result(name)
-            .or_else(|| interpreter.constants.get_result(name))
-            .ok_or(CalcError::CallingNonexistentVariable((*name).to_string()))
+            .or_else(|| {
+                let value = interpreter.constants.get_result(name)?;
+                Some(Constant::in_mode(name, value, &interpreter.config))
+            })
+            .or_else(|| {
+                Unit::find(name).map(|unit| Value::Quantity(Quantity::new(Decimal::ONE, unit)))
+            })
+            .ok_or(CalcError::CallingNonexistentVariable(name.to_string()))
+    }
+
+    /// Заменяет переменные с именами из `params` на соответствующие им значения из `values`.
+    /// Используется для подстановки аргументов в тело пользовательской функции.
+    #[must_use]
+    pub fn substitute(&self, params: &[String], values: &[Value]) -> Expr {
+        let args = values
+            .iter()
+            .cloned()
+            .map(Expr::Value)
+            .collect::<Vec<Expr>>();
+        self.replace(params, &args)
+    }
+
+    /// Заменяет переменные с именами из `params` на выражения из `args`.
+    /// Переменная, по которой берётся производная, внутри `diff` не заменяется:
+    /// её значение становится точкой, в которой производная вычисляется.
+    #[must_use]
+    pub fn replace(&self, params: &[String], args: &[Expr]) -> Expr {
+        let replace_args = |exprs: &[Box<Expr>]| {
+            exprs
+                .iter()
+                .map(|expr| Box::new(expr.replace(params, args)))
+                .collect()
+        };
+        // Связанные переменные `diff`, `integrate`, `derivative`, `sum`, `prod` и неизвестные
+        // `solve` внутри них не заменяются.
+        let replace_except = |body: &Expr, bound: &[&str]| {
+            let (inner_params, inner_args): (Vec<String>, Vec<Expr>) = params
+                .iter()
+                .zip(args)
+                .filter(|(param, _)| !bound.contains(&param.as_str()))
+                .map(|(param, arg)| (param.clone(), arg.clone()))
+                .unzip();
+            body.replace(&inner_params, &inner_args)
+        };
+        let replace_bound = |body: &Expr, var: &str| replace_except(body, &[var]);
+
+        match self {
+            Expr::Variable(name) => params
+                .iter()
+                .position(|param| param == name)
+                .map_or_else(|| self.clone(), |i| args[i].clone()),
+            Expr::Op(left, op, right) => Expr::Op(
+                Box::new(left.replace(params, args)),
+                *op,
+                Box::new(right.replace(params, args)),
+            ),
+            Expr::UnaryOp(op, value) => Expr::UnaryOp(*op, Box::new(value.replace(params, args))),
+            Expr::Call(name, exprs) => Expr::Call(name.clone(), replace_args(exprs)),
+            Expr::Matrix(elements) => Expr::Matrix(replace_args(elements)),
+            Expr::Index(target, indices) => Expr::Index(
+                Box::new(target.replace(params, args)),
+                replace_args(indices),
+            ),
+            Expr::Equation(left, right) => Expr::Equation(
+                Box::new(left.replace(params, args)),
+                Box::new(right.replace(params, args)),
+            ),
+            Expr::Solve(exprs) => {
+                let unknowns = exprs
+                    .iter()
+                    .filter_map(|arg| match arg.unspanned() {
+                        Expr::Variable(name) => Some(name.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<&str>>();
+                Expr::Solve(
+                    exprs
+                        .iter()
+                        .map(|arg| match arg.unspanned() {
+                            Expr::Variable(_) => arg.clone(),
+                            _ => Box::new(replace_except(arg, &unknowns)),
+                        })
+                        .collect(),
+                )
+            }
+            Expr::Diff(body, var, point) => {
+                let point = match point {
+                    Some(point) => Some(Box::new(point.replace(params, args))),
+                    None => params
+                        .iter()
+                        .position(|param| param == var)
+                        .map(|i| Box::new(args[i].clone())),
+                };
+                Expr::Diff(Box::new(replace_bound(body, var)), var.clone(), point)
+            }
+            Expr::Simplify(value) => Expr::Simplify(Box::new(value.replace(params, args))),
+            Expr::Integrate(body, var, from, to) => Expr::Integrate(
+                Box::new(replace_bound(body, var)),
+                var.clone(),
+                Box::new(from.replace(params, args)),
+                Box::new(to.replace(params, args)),
+            ),
+            Expr::Derivative(body, var, at) => Expr::Derivative(
+                Box::new(replace_bound(body, var)),
+                var.clone(),
+                Box::new(at.replace(params, args)),
+            ),
+            Expr::Sum(index, from, to, body) => Expr::Sum(
+                index.clone(),
+                Box::new(from.replace(params, args)),
+                Box::new(to.replace(params, args)),
+                Box::new(replace_bound(body, index)),
+            ),
+            Expr::Product(index, from, to, body) => Expr::Product(
+                index.clone(),
+                Box::new(from.replace(params, args)),
+                Box::new(to.replace(params, args)),
+                Box::new(replace_bound(body, index)),
+            ),
+            Expr::Spanned(span, expr) => Expr::Spanned(*span, Box::new(expr.replace(params, args))),
+            Expr::Number(_) | Expr::Literal(_) | Expr::Value(_) | Expr::Error(_) => self.clone(),
+        }
+    }
+
+    /// Приоритет выражения для записи без лишних скобок: чем больше, тем сильнее связь.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Number(number) if number.is_sign_negative() => PREFIX,
+            Expr::Value(value) => {
+                let text = value.to_string();
+                match value {
+                    Value::Complex(_) => SUM,
+                    _ if text.starts_with('-') => PREFIX,
+                    Value::Quantity(_) | Value::Rational(_) if text.contains([' ', '/']) => PRODUCT,
+                    _ => ATOM,
+                }
+            }
+            Expr::Equation(..) => EQUATION,
+            Expr::Op(_, op, _) => match op {
+                Opcode::Convert => CONVERT,
+                Opcode::Or => OR,
+                Opcode::Xor => XOR,
+                Opcode::And => AND,
+                Opcode::Shl | Opcode::Shr => SHIFT,
+                Opcode::Add | Opcode::Sub => SUM,
+                Opcode::Mul
+                | Opcode::Div
+                | Opcode::Mod
+                | Opcode::IntDiv
+                | Opcode::ElemMul
+                | Opcode::ElemDiv => PRODUCT,
+                Opcode::Pow | Opcode::ElemPow => POWER,
+            },
+            Expr::UnaryOp(op, _) if op.is_prefix() => PREFIX,
+            Expr::UnaryOp(..) => POSTFIX,
+            Expr::Spanned(_, expr) => expr.precedence(),
+            _ => ATOM,
+        }
+    }
+
+    /// Записывает `self` в скобках, если его приоритет ниже `min`.
+    fn fmt_operand(&self, fmt: &mut Formatter, min: u8) -> Result<(), Error> {
+        match self.precedence() < min {
+            true => write!(fmt, "({self})"),
+            false => write!(fmt, "{self}"),
+        }
+    }
+}
+
+const EQUATION: u8 = 0;
+const CONVERT: u8 = 1;
+const OR: u8 = 2;
+const XOR: u8 = 3;
+const AND: u8 = 4;
+const SHIFT: u8 = 5;
+const SUM: u8 = 6;
+const PRODUCT: u8 = 7;
+const PREFIX: u8 = 8;
+const POWER: u8 = 9;
+const POSTFIX: u8 = 10;
+const ATOM: u8 = 11;
+
+/// Запись выражения в том виде, в каком его можно ввести снова: `2 * x + cos(x)`.
+/// Скобки ставятся только там, где без них порядок вычисления изменится.
+impl Display for Expr {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        let join = |exprs: &[Box<Expr>]| {
+            exprs
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join("; ")
+        };
+
+        match self {
+            Expr::Number(number) => write!(fmt, "{number}"),
+            Expr::Literal(literal) => write!(fmt, "{literal}"),
+            Expr::Value(value) => write!(fmt, "{value}"),
+            Expr::Variable(name) => write!(fmt, "{name}"),
+            // Степень правоассоциативна, а её основание - значение без префиксных операторов.
+            Expr::Op(left, op @ (Opcode::Pow | Opcode::ElemPow), right) => {
+                left.fmt_operand(fmt, POSTFIX)?;
+                write!(fmt, "{op:?}")?;
+                right.fmt_operand(fmt, PREFIX)
+            }
+            // Перевод в единицы принимает справа только произведение: `3 km to mi`.
+            Expr::Op(left, Opcode::Convert, right) => {
+                left.fmt_operand(fmt, CONVERT)?;
+                write!(fmt, " to ")?;
+                right.fmt_operand(fmt, PRODUCT)
+            }
+            Expr::Op(left, op, right) => {
+                let precedence = self.precedence();
+                left.fmt_operand(fmt, precedence)?;
+                write!(fmt, " {op:?} ")?;
+                right.fmt_operand(fmt, precedence + 1)
+            }
+            Expr::UnaryOp(op, value) if op.is_prefix() => {
+                write!(fmt, "{op:?}")?;
+                value.fmt_operand(fmt, PREFIX)
+            }
+            Expr::UnaryOp(op, value) => {
+                value.fmt_operand(fmt, POSTFIX)?;
+                write!(fmt, "{op:?}")
+            }
+            Expr::Call(name, args) => write!(fmt, "{name}({})", join(args)),
+            Expr::Matrix(elements) => write!(fmt, "[{}]", join(elements)),
+            Expr::Index(target, indices) => write!(fmt, "{target}[{}]", join(indices)),
+            Expr::Equation(left, right) => write!(fmt, "{left} = {right}"),
+            Expr::Solve(args) => write!(fmt, "solve({})", join(args)),
+            Expr::Diff(body, var, None) => write!(fmt, "diff({body}; {var})"),
+            Expr::Diff(body, var, Some(point)) => write!(fmt, "diff({body}; {var}; {point})"),
+            Expr::Simplify(value) => write!(fmt, "simplify({value})"),
+            Expr::Integrate(body, var, from, to) => {
+                write!(fmt, "integrate({body}; {var}; {from}; {to})")
+            }
+            Expr::Derivative(body, var, at) => write!(fmt, "derivative({body}; {var}; {at})"),
+            Expr::Sum(index, from, to, body) => write!(fmt, "sum({index}; {from}; {to}; {body})"),
+            Expr::Product(index, from, to, body) => {
+                write!(fmt, "prod({index}; {from}; {to}; {body})")
+            }
+            Expr::Error(err) => write!(fmt, "{err}"),
+            Expr::Spanned(_, expr) => write!(fmt, "{expr}"),
+        }
     }
 }
 
-impl Evaluatable for Expr<'_> {
-    fn evaluate(&self, interpreter: &mut Interpreter) -> Result<Decimal, CalcError> {
+impl Evaluatable for Expr {
+    fn evaluate(&self, interpreter: &mut Interpreter) -> Result<Value, CalcError> {
         match self {
-            Expr::Number(n) => Ok(*n),
-            Expr::Func(name, expr) => FactoryFunc::match_(name, expr, interpreter),
+            Expr::Number(n) => Ok(interpreter.config.literal(*n)),
+            Expr::Literal(literal) => interpreter.config.parse_literal(literal),
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Call(name, args) => interpreter.call_function(name, args),
+            Expr::Matrix(elements) => {
+                let values = elements
+                    .iter()
+                    .map(|element| element.evaluate(interpreter))
+                    .collect::<Result<Vec<Value>, CalcError>>()?;
+                Matrix::from_values(values).map(Value::Matrix)
+            }
+            Expr::Index(target, indices) => {
+                let Value::Matrix(matrix) = target.evaluate(interpreter)? else {
+                    return Err(CalcError::NotAMatrix("Indexing".to_string()));
+                };
+                let indices = indices
+                    .iter()
+                    .map(|index| index.evaluate(interpreter))
+                    .collect::<Result<Vec<Value>, CalcError>>()?;
+                matrix.index(&indices)
+            }
+            Expr::Equation(left, right) => {
+                let left = left.evaluate(interpreter)?;
+                let right = right.evaluate(interpreter)?;
+                FactoryOp::match_(Opcode::Sub, left, right)
+            }
+            Expr::Solve(args) => solve::solve(interpreter, args),
+            Expr::Diff(body, var, point) => {
+                diff::evaluate(interpreter, body, var, point.as_deref())
+            }
+            Expr::Simplify(value) => simplify::evaluate(interpreter, value),
+            Expr::Integrate(body, var, from, to) => {
+                numeric::integrate(interpreter, body, var, from, to)
+            }
+            Expr::Derivative(body, var, at) => numeric::derivative(interpreter, body, var, at),
+            Expr::Sum(index, from, to, body) => {
+                series::evaluate(interpreter, Opcode::Add, index, from, to, body)
+            }
+            Expr::Product(index, from, to, body) => {
+                series::evaluate(interpreter, Opcode::Mul, index, from, to, body)
+            }
             Expr::Variable(name) => Self::get_variable(interpreter, name),
             Expr::Op(left, op, right) => op.evaluate(left.clone(), right.clone(), interpreter),
+            Expr::UnaryOp(op, value) => op.evaluate(value.clone(), interpreter),
             Expr::Error(err) => Err(err.clone()),
+            Expr::Spanned(span, expr) => expr
+                .evaluate(interpreter)
+                .inspect_err(|_| interpreter.mark_error(*span)),
         }
     }
 }
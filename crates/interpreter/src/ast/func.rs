@@ -1,226 +1,876 @@
-use crate::{errors::CalcError, interpreter::Interpreter};
-
-use super::{
-    expr::{Evaluatable, Expr},
-    func_name::FuncName,
+use super::{opcode::Opcode, operation::FactoryOp};
+use crate::{
+    big::BigNumber,
+    complex::Complex,
+    config::AngleUnit,
+    errors::CalcError,
+    integer::{Base, IntType, Integer},
+    interpreter::Interpreter,
+    math::{self, DecimalMath},
+    matrix::Matrix,
+    quantity::Quantity,
+    rational::Rational,
+    registry::{NativeFunction, Registry},
+    value::Value,
 };
 
-use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
-use std::f64::consts::PI;
-
-pub struct FactoryFunc;
-
-impl FactoryFunc {
-    pub fn match_(
-        name: &FuncName, args: &[Box<Expr>], calc: &mut Interpreter,
-    ) -> Result<Decimal, CalcError> {
-        match name {
-            FuncName::Sin => Sin::ahead(args, calc),
-            FuncName::Cos => Cos::ahead(args, calc),
-            FuncName::Tg => Tg::ahead(args, calc),
-            FuncName::Ctg => Ctg::ahead(args, calc),
-            FuncName::Exponentiation => Exponentiation::ahead(args, calc),
-            FuncName::SquareRoot => SquareRoot::ahead(args, calc),
+use std::ops::RangeInclusive;
+
+/// Добавляет в реестр все встроенные функции калькулятора.
+pub(crate) fn register_builtins(registry: &mut Registry) {
+    registry
+        .insert(builtin::<Sin>(1..=1, "sin(angle) - sine"))
+        .insert(builtin::<Cos>(1..=1, "cos(angle) - cosine"))
+        .insert(builtin::<Tg>(1..=1, "tg(angle) - tangent"))
+        .insert(builtin::<Ctg>(1..=1, "ctg(angle) - cotangent"))
+        .insert(builtin::<Asin>(1..=1, "asin(number) - arcsine"))
+        .insert(builtin::<Acos>(1..=1, "acos(number) - arccosine"))
+        .insert(builtin::<Atan>(1..=1, "atan(number) - arctangent"))
+        .insert(builtin::<Atan2>(
+            2..=2,
+            "atan2(y; x) - angle between the X axis and the point (x; y)",
+        ))
+        .insert(builtin::<Actg>(1..=1, "actg(number) - arccotangent"))
+        .insert(builtin::<Sinh>(1..=1, "sinh(number) - hyperbolic sine"))
+        .insert(builtin::<Cosh>(1..=1, "cosh(number) - hyperbolic cosine"))
+        .insert(builtin::<Tanh>(1..=1, "tanh(number) - hyperbolic tangent"))
+        .insert(builtin::<Asinh>(
+            1..=1,
+            "asinh(number) - inverse hyperbolic sine",
+        ))
+        .insert(builtin::<Acosh>(
+            1..=1,
+            "acosh(number) - inverse hyperbolic cosine",
+        ))
+        .insert(builtin::<Atanh>(
+            1..=1,
+            "atanh(number) - inverse hyperbolic tangent",
+        ))
+        .insert(builtin::<Degrees>(
+            1..=1,
+            "deg(radians) - converts radians to degrees",
+        ))
+        .insert(builtin::<Radians>(
+            1..=1,
+            "rad(degrees) - converts degrees to radians",
+        ))
+        .insert(
+            value_builtin::<Exponentiation>(
+                1..=2,
+                "pow(number; power) - raises the number to a power",
+            )
+            .with_defaults(vec![Decimal::TWO.into()]),
+        )
+        .insert(
+            NativeFunction::new("exp", 1..=2, Exponentiation::ahead)
+                .with_defaults(vec![Decimal::TWO.into()])
+                .with_doc("exp(number; power) - the old name of pow"),
+        )
+        .insert(
+            value_builtin::<SquareRoot>(1..=2, "sqrt(number; degree) - root of the number")
+                .with_defaults(vec![Decimal::TWO.into()]),
+        )
+        .insert(
+            NativeFunction::new("rt", 1..=2, SquareRoot::ahead)
+                .with_defaults(vec![Decimal::TWO.into()])
+                .with_doc("rt(number; degree) - the same as sqrt"),
+        )
+        .insert(value_builtin::<NaturalExponent>(
+            1..=1,
+            "expe(number) - the Euler number raised to a power",
+        ))
+        .insert(builtin::<Ln>(1..=1, "ln(number) - natural logarithm"))
+        .insert(builtin::<Log10>(1..=1, "log10(number) - decimal logarithm"))
+        .insert(builtin::<Log2>(1..=1, "log2(number) - binary logarithm"))
+        .insert(
+            builtin::<Log>(1..=2, "log(number; base) - logarithm by any base")
+                .with_defaults(vec![Decimal::TEN.into()]),
+        )
+        .insert(value_builtin::<RealPart>(
+            1..=1,
+            "re(number) - real part of a complex number",
+        ))
+        .insert(value_builtin::<ImaginaryPart>(
+            1..=1,
+            "im(number) - imaginary part of a complex number",
+        ))
+        .insert(value_builtin::<Abs>(
+            1..=1,
+            "abs(number) - absolute value or modulus of a complex number",
+        ))
+        .insert(value_builtin::<Argument>(
+            1..=1,
+            "arg(number) - angle of a complex number",
+        ))
+        .insert(value_builtin::<Conjugate>(
+            1..=1,
+            "conj(number) - complex conjugate",
+        ))
+        .insert(value_builtin::<Polar>(
+            2..=2,
+            "polar(modulus; angle) - complex number from its polar form",
+        ))
+        .insert(
+            value_builtin::<Fraction>(
+                1..=2,
+                "frac(number; max_denominator) - the nearest simple fraction",
+            )
+            .with_defaults(vec![Decimal::from(Fraction::MAX_DENOMINATOR).into()]),
+        )
+        .insert(value_builtin::<Hex>(
+            1..=1,
+            "hex(integer) - shows the integer in hexadecimal",
+        ))
+        .insert(value_builtin::<Bin>(
+            1..=1,
+            "bin(integer) - shows the integer in binary",
+        ))
+        .insert(value_builtin::<Oct>(
+            1..=1,
+            "oct(integer) - shows the integer in octal",
+        ))
+        .insert(value_builtin::<Dec>(
+            1..=1,
+            "dec(integer) - shows the integer in decimal",
+        ))
+        .insert(value_builtin::<Determinant>(
+            1..=1,
+            "det(matrix) - determinant of a square matrix",
+        ))
+        .insert(value_builtin::<Inverse>(
+            1..=1,
+            "inv(matrix) - inverse matrix",
+        ))
+        .insert(value_builtin::<Transpose>(
+            1..=1,
+            "transpose(matrix) - transposed matrix",
+        ))
+        .insert(value_builtin::<Dot>(
+            2..=2,
+            "dot(vector; vector) - dot product of vectors",
+        ))
+        .insert(value_builtin::<Cross>(
+            2..=2,
+            "cross(vector; vector) - cross product of vectors of length 3",
+        ))
+        .insert(value_builtin::<Linsolve>(
+            2..=2,
+            "linsolve(matrix; vector) - solution of the linear system matrix * x = vector",
+        ))
+        .insert(value_builtin::<Norm>(
+            1..=1,
+            "norm(vector) - length of a vector or the Frobenius norm of a matrix",
+        ));
+
+    for int_type in IntType::ALL {
+        let name = int_type.to_string();
+        let sign = if int_type.is_signed() {
+            "signed"
+        } else {
+            "unsigned"
+        };
+        let doc = format!(
+            "{name}(number) - {sign} {}-bit integer that wraps around on overflow",
+            int_type.bits()
+        );
+        registry.insert(
+            NativeFunction::new(&name, 1..=1, move |args, _calc| cast(args, int_type))
+                .with_doc(&doc),
+        );
+    }
+}
+
+/// Функция вещественных чисел. Если у неё есть `Function::big`, то в режиме `big` она
+/// считается со всей его точностью, а не в `Decimal`.
+fn builtin<F: Function + 'static>(arity: RangeInclusive<usize>, doc: &str) -> NativeFunction {
+    NativeFunction::new(F::NAME, arity, |args, calc| {
+        if let Some(result) = big_args(args).and_then(|args| F::big(&args)) {
+            return result.map(Value::Big);
         }
+        let args = args
+            .iter()
+            .map(|arg| arg.real(F::NAME))
+            .collect::<Result<Vec<Decimal>, CalcError>>()?;
+        F::ahead(&args, calc).map(|result| calc.config.number(result))
+    })
+    .with_doc(doc)
+}
+
+/// Аргументы в виде `BigNumber`, если хотя бы один из них - число режима `big`, а остальные,
+/// например значения по умолчанию, - вещественные.
+fn big_args(args: &[Value]) -> Option<Vec<BigNumber>> {
+    if !args.iter().any(|arg| matches!(arg, Value::Big(_))) {
+        return None;
     }
+    args.iter()
+        .map(|arg| match arg {
+            Value::Big(value) => Some(value.clone()),
+            Value::Real(_) | Value::Rational(_) | Value::Integer(_) => arg
+                .real("")
+                .ok()
+                .map(|value| BigNumber::from_decimal(value, 0)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn value_builtin<F: ValueFunction + 'static>(
+    arity: RangeInclusive<usize>, doc: &str,
+) -> NativeFunction {
+    NativeFunction::new(F::NAME, arity, F::ahead).with_doc(doc)
 }
 
 trait Function {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError>;
+    /// Имя, под которым функция вызывается из выражения.
+    const NAME: &'static str;
 
-    fn check_len_args(args: &[Box<Expr>], expect: usize) -> Result<(), CalcError> {
-        let len_args = args.len();
-        if len_args != expect {
-            return Err(CalcError::IncorrectNumberOfArguments(len_args, expect));
+    /// Вычисляет функцию. Количество аргументов уже проверено реестром.
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError>;
+
+    /// Вычисляет функцию со всей точностью режима `big`. `None` - считать в `Decimal`.
+    fn big(_args: &[BigNumber]) -> Option<Result<BigNumber, CalcError>> {
+        None
+    }
+
+    /// Возвращает `CalcError::OutOfDomain`, если аргумент не входит в область определения функции.
+    fn check_domain(in_domain: bool) -> Result<(), CalcError> {
+        if !in_domain {
+            return Err(CalcError::OutOfDomain(Self::NAME.to_string()));
         }
         Ok(())
     }
+}
 
-    fn check_len_args_or_stand_default_value<'a>(
-        args: &'a [Box<Expr<'a>>], expects: usize, default_value: Vec<Decimal>,
-    ) -> Result<Vec<Box<Expr<'a>>>, CalcError> {
-        let mut new_args = args.to_vec();
-
-        match Self::check_len_args(args, expects) {
-            Ok(()) => return Ok(args.to_vec()),
-            Err(_) => {
-                new_args.extend(
-                    default_value
-                        .iter()
-                        .skip(new_args.len())
-                        .take(expects - new_args.len())
-                        .map(|&value| Box::new(Expr::Number(value))),
-                );
-            }
-        }
+/// Функция, которая принимает значения как есть: комплексные числа и точные дроби.
+trait ValueFunction {
+    /// Имя, под которым функция вызывается из выражения.
+    const NAME: &'static str;
+
+    /// Вычисляет функцию. Количество аргументов уже проверено реестром.
+    fn ahead(args: &[Value], calc: &Interpreter) -> Result<Value, CalcError>;
+}
 
-        Ok(new_args)
+trait Trigonometry {
+    /// Единицы углов, выбранные в настройках интерпретатора. Углы переводятся в радианы
+    /// и обратно внутри `DecimalMath`, с запасом точности.
+    fn unit(calc: &Interpreter) -> AngleUnit {
+        calc.config.angle_unit
     }
 }
 
-trait DecimalMath {
-    fn sin(&self) -> Result<Decimal, CalcError>;
-    fn cos(&self) -> Result<Decimal, CalcError>;
-    fn tan(&self) -> Result<Decimal, CalcError>;
-    fn powf(&self, exponent: Decimal) -> Result<Decimal, CalcError>;
+pub struct Sin;
+
+impl Trigonometry for Sin {}
+
+impl Function for Sin {
+    const NAME: &'static str = "sin";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].sin(Self::unit(calc))
+    }
 }
 
-impl DecimalMath for Decimal {
-    fn sin(&self) -> Result<Decimal, CalcError> {
-        match self.to_f64() {
-            Some(v) => {
-                let sin_value = v.sin();
-                Decimal::from_f64(sin_value).ok_or(CalcError::MathError)
-            }
-            None => Err(CalcError::MathError),
-        }
+pub struct Cos;
+
+impl Trigonometry for Cos {}
+
+impl Function for Cos {
+    const NAME: &'static str = "cos";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].cos(Self::unit(calc))
+    }
+}
+
+pub struct Tg;
+
+impl Trigonometry for Tg {}
+
+impl Function for Tg {
+    const NAME: &'static str = "tg";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        let unit = Self::unit(calc);
+        Self::check_domain(!args[0].cos(unit)?.is_zero())?;
+        args[0].tan(unit)
     }
+}
+
+pub struct Ctg;
+
+impl Trigonometry for Ctg {}
 
-    fn cos(&self) -> Result<Decimal, CalcError> {
-        match self.to_f64() {
-            Some(v) => {
-                let sin_value = v.cos();
-                Decimal::from_f64(sin_value).ok_or(CalcError::MathError)
+impl Function for Ctg {
+    const NAME: &'static str = "ctg";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        let unit = Self::unit(calc);
+        Self::check_domain(!args[0].sin(unit)?.is_zero())?;
+        args[0].cot(unit)
+    }
+}
+
+pub struct Exponentiation;
+
+impl ValueFunction for Exponentiation {
+    const NAME: &'static str = "pow";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        FactoryOp::match_(Opcode::Pow, args[0].clone(), args[1].clone())
+    }
+}
+
+pub struct SquareRoot;
+
+impl SquareRoot {
+    /// Корень нечётной степени из отрицательного числа вещественный: `rt(-8; 3)` равно `-2`.
+    fn is_odd(degree: Decimal) -> bool {
+        degree.fract().is_zero() && !(degree % Decimal::TWO).is_zero()
+    }
+}
+
+impl ValueFunction for SquareRoot {
+    const NAME: &'static str = "sqrt";
+
+    /// Корень чётной степени из отрицательного числа - главное значение комплексного корня.
+    /// Квадратный корень в режимах `float` и `big` считается со всей их точностью,
+    /// остальные корни - в `Decimal`. Корень из величины извлекается и из её единиц.
+    fn ahead(args: &[Value], calc: &Interpreter) -> Result<Value, CalcError> {
+        let square = args[1]
+            .real(Self::NAME)
+            .is_ok_and(|degree| degree == Decimal::TWO);
+        match &args[0] {
+            Value::Float(value) if square && *value >= 0.0 => {
+                return Ok(Value::Float(value.sqrt()))
+            }
+            Value::Big(value) if square && !value.is_sign_negative() => {
+                return value.sqrt().map(Value::Big)
             }
-            None => Err(CalcError::MathError),
+            Value::Quantity(value) => {
+                return value
+                    .root(args[1].real(Self::NAME)?)
+                    .map(Quantity::into_value)
+            }
+            _ => {}
         }
-    }
 
-    fn tan(&self) -> Result<Decimal, CalcError> {
-        match self.to_f64() {
-            Some(v) => {
-                let sin_value = v.tan();
-                Decimal::from_f64(sin_value).ok_or(CalcError::MathError)
+        let root = match (
+            args[0].clone().into_decimal()?,
+            args[1].clone().into_decimal()?,
+        ) {
+            (Value::Real(value), Value::Real(degree))
+                if !value.is_sign_negative() || value.is_zero() || Self::is_odd(degree) =>
+            {
+                value.nth_root(degree).map(Value::Real)
             }
-            None => Err(CalcError::MathError),
+            (_, Value::Real(degree)) if degree.is_zero() => Err(CalcError::MathError),
+            (value, Value::Real(degree)) if degree == Decimal::TWO => {
+                value.to_complex()?.sqrt().map(Value::from)
+            }
+            (value, degree) => {
+                let exponent = Complex::from(Decimal::ONE).checked_div(degree.to_complex()?)?;
+                value.to_complex()?.powc(exponent).map(Value::from)
+            }
+        };
+        match root? {
+            Value::Real(root) => Ok(calc.config.number(root)),
+            root => Ok(root),
         }
     }
+}
+
+pub struct Degrees;
+
+impl Function for Degrees {
+    const NAME: &'static str = "deg";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        math::convert_angle(args[0], AngleUnit::Radians, AngleUnit::Degrees)
+    }
+}
 
-    fn powf(&self, exponent: Decimal) -> Result<Decimal, CalcError> {
-        let base = self.to_f64().ok_or(CalcError::MathError)?;
-        let exp = exponent.to_f64().ok_or(CalcError::MathError)?;
-        Decimal::from_f64(base.powf(exp)).ok_or(CalcError::MathError)
+pub struct Radians;
+
+impl Function for Radians {
+    const NAME: &'static str = "rad";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        math::convert_angle(args[0], AngleUnit::Degrees, AngleUnit::Radians)
     }
 }
 
-trait AppendArgs {
-    fn append_args(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Vec<Decimal>, CalcError> {
-        let mut arg = Vec::new();
-        for i in args {
-            match i.evaluate(calc) {
-                Ok(res) => arg.push(res),
-                Err(err) => return Err(err),
-            }
+pub struct Asin;
+
+impl Trigonometry for Asin {}
+
+impl Function for Asin {
+    const NAME: &'static str = "asin";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0].abs() <= Decimal::ONE)?;
+        args[0].asin(Self::unit(calc))
+    }
+}
+
+pub struct Acos;
+
+impl Trigonometry for Acos {}
+
+impl Function for Acos {
+    const NAME: &'static str = "acos";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0].abs() <= Decimal::ONE)?;
+        args[0].acos(Self::unit(calc))
+    }
+}
+
+pub struct Atan;
+
+impl Trigonometry for Atan {}
+
+impl Function for Atan {
+    const NAME: &'static str = "atan";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].atan(Self::unit(calc))
+    }
+}
+
+pub struct Atan2;
+
+impl Trigonometry for Atan2 {}
+
+impl Function for Atan2 {
+    const NAME: &'static str = "atan2";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(!(args[0].is_zero() && args[1].is_zero()))?;
+        args[0].atan2(args[1], Self::unit(calc))
+    }
+}
+
+pub struct Actg;
+
+impl Trigonometry for Actg {}
+
+impl Function for Actg {
+    const NAME: &'static str = "actg";
+
+    fn ahead(args: &[Decimal], calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].acot(Self::unit(calc))
+    }
+}
+
+pub struct Sinh;
+
+impl Function for Sinh {
+    const NAME: &'static str = "sinh";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].sinh()
+    }
+}
+
+pub struct Cosh;
+
+impl Function for Cosh {
+    const NAME: &'static str = "cosh";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].cosh()
+    }
+}
+
+pub struct Tanh;
+
+impl Function for Tanh {
+    const NAME: &'static str = "tanh";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].tanh()
+    }
+}
+
+pub struct Asinh;
+
+impl Function for Asinh {
+    const NAME: &'static str = "asinh";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        args[0].asinh()
+    }
+}
+
+pub struct Acosh;
+
+impl Function for Acosh {
+    const NAME: &'static str = "acosh";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0] >= Decimal::ONE)?;
+        args[0].acosh()
+    }
+}
+
+pub struct Atanh;
+
+impl Function for Atanh {
+    const NAME: &'static str = "atanh";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0].abs() < Decimal::ONE)?;
+        args[0].atanh()
+    }
+}
+
+pub struct NaturalExponent;
+
+impl ValueFunction for NaturalExponent {
+    const NAME: &'static str = "expe";
+
+    fn ahead(args: &[Value], calc: &Interpreter) -> Result<Value, CalcError> {
+        match &args[0] {
+            Value::Complex(value) => value.exp().map(Value::from),
+            Value::Big(value) => value.exp().map(Value::Big),
+            value => value
+                .real(Self::NAME)?
+                .exp()
+                .map(|exp| calc.config.number(exp)),
         }
-        Ok(arg)
     }
 }
 
-trait Trigonometry {
-    fn radians_in_degrees(radians: Decimal) -> Result<Decimal, CalcError> {
-        let pi: Decimal = PI.try_into().map_err(|_| CalcError::MathError)?;
-        let div_to: Decimal = 180.into();
-        Ok((radians * pi) / div_to)
+pub struct Ln;
+
+impl Function for Ln {
+    const NAME: &'static str = "ln";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0] > Decimal::ZERO)?;
+        args[0].ln()
+    }
+
+    fn big(args: &[BigNumber]) -> Option<Result<BigNumber, CalcError>> {
+        let positive = !args[0].is_sign_negative() && !args[0].is_zero();
+        Some(Self::check_domain(positive).and_then(|()| args[0].ln()))
     }
 }
 
-pub struct Sin;
+pub struct Log10;
 
-impl Trigonometry for Sin {}
+impl Function for Log10 {
+    const NAME: &'static str = "log10";
 
-impl Function for Sin {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError> {
-        Self::check_len_args(args, 1)?;
-        match args[0].evaluate(calc) {
-            Ok(res) => Ok(Self::radians_in_degrees(res)?.sin()?),
-            Err(err) => Err(err),
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0] > Decimal::ZERO)?;
+        args[0].log10()
+    }
+
+    fn big(args: &[BigNumber]) -> Option<Result<BigNumber, CalcError>> {
+        let positive = !args[0].is_sign_negative() && !args[0].is_zero();
+        let base = BigNumber::from_decimal(Decimal::TEN, 0);
+        Some(Self::check_domain(positive).and_then(|()| args[0].log(&base)))
+    }
+}
+
+pub struct Log2;
+
+impl Function for Log2 {
+    const NAME: &'static str = "log2";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        Self::check_domain(args[0] > Decimal::ZERO)?;
+        args[0].log2()
+    }
+
+    fn big(args: &[BigNumber]) -> Option<Result<BigNumber, CalcError>> {
+        let positive = !args[0].is_sign_negative() && !args[0].is_zero();
+        let base = BigNumber::from_decimal(Decimal::TWO, 0);
+        Some(Self::check_domain(positive).and_then(|()| args[0].log(&base)))
+    }
+}
+
+pub struct Log;
+
+impl Function for Log {
+    const NAME: &'static str = "log";
+
+    fn ahead(args: &[Decimal], _calc: &Interpreter) -> Result<Decimal, CalcError> {
+        let (value, base) = (args[0], args[1]);
+        Self::check_domain(value > Decimal::ZERO && base > Decimal::ZERO && base != Decimal::ONE)?;
+        value.log(base)
+    }
+
+    fn big(args: &[BigNumber]) -> Option<Result<BigNumber, CalcError>> {
+        let positive = |value: &BigNumber| !value.is_sign_negative() && !value.is_zero();
+        let one = BigNumber::from_decimal(Decimal::ONE, 0);
+        let in_domain = positive(&args[0]) && positive(&args[1]) && args[1] != one;
+        Some(Self::check_domain(in_domain).and_then(|()| args[0].log(&args[1])))
+    }
+}
+
+pub struct RealPart;
+
+impl ValueFunction for RealPart {
+    const NAME: &'static str = "re";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        match &args[0] {
+            Value::Complex(value) => Ok(Value::Real(value.re)),
+            value => Ok(value.clone()),
         }
     }
 }
 
-pub struct Cos;
+pub struct ImaginaryPart;
 
-impl Trigonometry for Cos {}
+impl ValueFunction for ImaginaryPart {
+    const NAME: &'static str = "im";
 
-impl Function for Cos {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError> {
-        Self::check_len_args(args, 1)?;
-        match args[0].evaluate(calc) {
-            Ok(res) => Ok(Self::radians_in_degrees(res)?.cos()?),
-            Err(err) => Err(err),
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        Ok(Value::Real(args[0].to_complex()?.im))
+    }
+}
+
+pub struct Abs;
+
+impl ValueFunction for Abs {
+    const NAME: &'static str = "abs";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        match &args[0] {
+            Value::Real(value) => Ok(Value::Real(value.abs())),
+            Value::Rational(value) => Ok(Value::Rational(value.abs())),
+            Value::Float(value) => Ok(Value::Float(value.abs())),
+            Value::Big(value) => Ok(Value::Big(value.abs())),
+            Value::Complex(value) => value.abs().map(Value::Real),
+            Value::Quantity(value) => Ok(Value::Quantity(value.abs())),
+            Value::Integer(value) => value.abs().map(Value::Integer),
+            Value::Matrix(value) => value
+                .map(|value| Self::ahead(std::slice::from_ref(value), _calc))
+                .map(Value::Matrix),
+            Value::Expression(_) => Err(CalcError::ExpressionNotSupported(Self::NAME.to_string())),
         }
     }
 }
 
-pub struct Tg;
+pub struct Argument;
 
-impl Trigonometry for Tg {}
+impl Trigonometry for Argument {}
 
-impl Function for Tg {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError> {
-        Self::check_len_args(args, 1)?;
-        match args[0].evaluate(calc) {
-            Ok(res) => Ok(Self::radians_in_degrees(res)?.tan()?),
-            Err(err) => Err(err),
+impl ValueFunction for Argument {
+    const NAME: &'static str = "arg";
+
+    fn ahead(args: &[Value], calc: &Interpreter) -> Result<Value, CalcError> {
+        let value = args[0].to_complex()?;
+        if value.is_zero() {
+            return Err(CalcError::OutOfDomain(Self::NAME.to_string()));
         }
+        value
+            .im
+            .atan2(value.re, Self::unit(calc))
+            .map(|arg| calc.config.number(arg))
     }
 }
 
-pub struct Ctg;
+pub struct Conjugate;
 
-impl Trigonometry for Ctg {}
+impl ValueFunction for Conjugate {
+    const NAME: &'static str = "conj";
 
-impl Function for Ctg {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError> {
-        Self::check_len_args(args, 1)?;
-        match args[0].evaluate(calc) {
-            Ok(res) => {
-                Ok(Self::radians_in_degrees(res)?.cos()? / Self::radians_in_degrees(res)?.sin()?)
-            }
-            Err(err) => Err(err),
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        match &args[0] {
+            Value::Complex(value) => Ok(Value::from(value.conj())),
+            value => Ok(value.clone()),
         }
     }
 }
 
-pub struct Exponentiation;
+pub struct Polar;
+
+impl Trigonometry for Polar {}
 
-impl AppendArgs for Exponentiation {}
+impl ValueFunction for Polar {
+    const NAME: &'static str = "polar";
 
-impl Function for Exponentiation {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError> {
-        let args_add_default =
-            Self::check_len_args_or_stand_default_value(args, 2, vec![0.into(), 2.into()])?;
-        let arg = Self::append_args(&args_add_default, calc)?;
-        arg[0].powf(arg[1])
+    fn ahead(args: &[Value], calc: &Interpreter) -> Result<Value, CalcError> {
+        let modulus = args[0].real(Self::NAME)?;
+        let angle = args[1].real(Self::NAME)?;
+        Complex::from_polar(modulus, angle, Self::unit(calc)).map(Value::from)
     }
 }
 
-pub struct SquareRoot;
+pub struct Fraction;
+
+impl Fraction {
+    /// Наибольший знаменатель дроби, если он не указан вторым аргументом.
+    const MAX_DENOMINATOR: u32 = 1000;
+}
 
-impl AppendArgs for SquareRoot {}
+impl ValueFunction for Fraction {
+    const NAME: &'static str = "frac";
 
-impl SquareRoot {
-    fn nth_root(n: Decimal, a: Decimal) -> Result<Decimal, CalcError> {
-        let eps = 1e-10; // Точность вычисления.
-        let mut x: Decimal = 1.into(); // Начальное приближение.
-        while (x.powf(n)? - a).abs() > Decimal::try_from(eps).map_err(|_| CalcError::MathError)? {
-            x = ((n - Decimal::from(1)) * x + a / x.powf(n - Decimal::from(1))?) / n;
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        let value = args[0].real(Self::NAME)?;
+        let max_denom = args[1].real(Self::NAME)?;
+        if max_denom < Decimal::ONE || !max_denom.fract().is_zero() {
+            return Err(CalcError::OutOfDomain(Self::NAME.to_string()));
         }
-        Ok(x)
+        Rational::approximate(value, max_denom).map(Value::Rational)
+    }
+}
+
+/// Целое число `args[0]`, которое показывается в системе счисления `base`.
+fn in_base(args: &[Value], name: &str, base: Base) -> Result<Value, CalcError> {
+    Integer::from_value(&args[0], name).map(|integer| Value::Integer(integer.with_base(base)))
+}
+
+pub struct Hex;
+
+impl ValueFunction for Hex {
+    const NAME: &'static str = "hex";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        in_base(args, Self::NAME, Base::Hex)
+    }
+}
+
+pub struct Bin;
+
+impl ValueFunction for Bin {
+    const NAME: &'static str = "bin";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        in_base(args, Self::NAME, Base::Binary)
     }
 }
 
-impl Function for SquareRoot {
-    fn ahead(args: &[Box<Expr>], calc: &mut Interpreter) -> Result<Decimal, CalcError> {
-        let args_add_default =
-            Self::check_len_args_or_stand_default_value(args, 2, vec![0.into(), 2.into()])?;
-        let arg = Self::append_args(&args_add_default, calc)?;
-        let res = Self::nth_root(arg[0], arg[1])?;
+pub struct Oct;
+
+impl ValueFunction for Oct {
+    const NAME: &'static str = "oct";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        in_base(args, Self::NAME, Base::Octal)
+    }
+}
+
+pub struct Dec;
+
+impl ValueFunction for Dec {
+    const NAME: &'static str = "dec";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        in_base(args, Self::NAME, Base::Decimal)
+    }
+}
+
+/// Приводит `args[0]` к типу `int_type`. Дробная часть отбрасывается, как при приведении в C.
+fn cast(args: &[Value], int_type: IntType) -> Result<Value, CalcError> {
+    let integer = match &args[0] {
+        Value::Integer(integer) => *integer,
+        value => {
+            let name = int_type.to_string();
+            Integer::from_decimal(value.real(&name)?.trunc(), Base::Decimal)
+                .ok_or(CalcError::NotAnInteger(name))?
+        }
+    };
+    Ok(Value::Integer(integer.cast(int_type)))
+}
+
+/// Вектор или матрица, которые принимает функция `name`.
+fn matrix<'a>(value: &'a Value, name: &str) -> Result<&'a Matrix, CalcError> {
+    match value {
+        Value::Matrix(matrix) => Ok(matrix),
+        _ => Err(CalcError::NotAMatrix(name.to_string())),
+    }
+}
+
+pub struct Determinant;
+
+impl ValueFunction for Determinant {
+    const NAME: &'static str = "det";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        matrix(&args[0], Self::NAME)?.det()
+    }
+}
+
+pub struct Inverse;
+
+impl ValueFunction for Inverse {
+    const NAME: &'static str = "inv";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        matrix(&args[0], Self::NAME)?
+            .inv(Self::NAME)
+            .map(Value::Matrix)
+    }
+}
+
+pub struct Transpose;
+
+impl ValueFunction for Transpose {
+    const NAME: &'static str = "transpose";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        Ok(Value::Matrix(matrix(&args[0], Self::NAME)?.transpose()))
+    }
+}
+
+pub struct Dot;
+
+impl ValueFunction for Dot {
+    const NAME: &'static str = "dot";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        matrix(&args[0], Self::NAME)?.dot(matrix(&args[1], Self::NAME)?)
+    }
+}
+
+pub struct Cross;
+
+impl ValueFunction for Cross {
+    const NAME: &'static str = "cross";
+
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        matrix(&args[0], Self::NAME)?
+            .cross(matrix(&args[1], Self::NAME)?)
+            .map(Value::Matrix)
+    }
+}
+
+pub struct Norm;
+
+impl ValueFunction for Norm {
+    const NAME: &'static str = "norm";
+
+    /// Корень из суммы квадратов модулей элементов, поэтому у вектора длин
+    /// `[3 m; 4 m]` норма - `5 m`.
+    fn ahead(args: &[Value], calc: &Interpreter) -> Result<Value, CalcError> {
+        let squares = matrix(&args[0], Self::NAME)?
+            .elements()
+            .iter()
+            .map(|value| {
+                let abs = Abs::ahead(std::slice::from_ref(value), calc)?;
+                FactoryOp::match_(Opcode::Mul, abs.clone(), abs)
+            });
+        let sum = squares.reduce(|sum, square| FactoryOp::match_(Opcode::Add, sum?, square?));
+        let sum = sum.expect("a matrix is not empty")?;
+        SquareRoot::ahead(&[sum, Decimal::TWO.into()], calc)
+    }
+}
+
+pub struct Linsolve;
+
+impl ValueFunction for Linsolve {
+    const NAME: &'static str = "linsolve";
 
-        // if res.is_sign_negative() {
-        //     return Err(CalcError::ImpossibleToExtractRootCorrectly);
-        // }
-        Ok(res)
+    fn ahead(args: &[Value], _calc: &Interpreter) -> Result<Value, CalcError> {
+        matrix(&args[0], Self::NAME)?
+            .solve(matrix(&args[1], Self::NAME)?)
+            .map(Value::Matrix)
     }
 }
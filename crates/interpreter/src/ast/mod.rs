@@ -1,6 +1,10 @@
 pub mod calc;
+pub mod diff;
 pub mod expr;
 pub mod func;
-pub mod func_name;
+pub mod numeric;
 pub mod opcode;
 pub mod operation;
+pub mod series;
+pub mod simplify;
+pub mod solve;
@@ -0,0 +1,269 @@
+//! Численные интеграл и производная: `integrate(x^2; x; 0; 1)`, `derivative(sin(x); x; 30)`.
+//!
+//! Связанная переменная получает значения через `Interpreter::evaluate_with`, поэтому
+//! переменная пользователя с тем же именем не меняется. Интеграл считается адаптивной
+//! квадратурой Гаусса-Кронрода: на отрезке правило Кронрода по 15 точкам даёт значение, а его
+//! разница с правилом Гаусса по 7 из этих точек - оценку погрешности. Отрезок с наибольшей
+//! погрешностью делится пополам, пока сумма погрешностей больше `Config::tolerance`.
+//! Производная считается методом Риддерса: центральные разности с уменьшающимся шагом
+//! экстраполируются к нулевому шагу, а расхождение соседних экстраполяций оценивает погрешность.
+
+use rust_decimal::Decimal;
+
+use super::expr::{Evaluatable, Expr};
+use crate::{
+    config::NumberMode, errors::CalcError, interpreter::Interpreter, math, symbolic, value::Value,
+};
+
+/// Узлы правила Кронрода на отрезке `[-1; 1]`, кроме нуля. Нечётные из них - узлы Гаусса.
+const KRONROD_NODES: [Decimal; 7] = [
+    // 0.9914553711208126392068546975
+    Decimal::from_parts(2_617_057_695, 2_773_705_162, 537_469_033, false, 28),
+    // 0.9491079123427585245261896840
+    Decimal::from_parts(4_067_728_520, 1_041_960_140, 514_512_430, false, 28),
+    // 0.8648644233597690727897127886
+    Decimal::from_parts(241_064_910, 1_529_190_203, 468_843_943, false, 28),
+    // 0.7415311855993944398638647733
+    Decimal::from_parts(4_055_140_789, 843_134_833, 401_984_861, false, 28),
+    // 0.5860872354676911302941448383
+    Decimal::from_parts(2_307_758_271, 4_209_270_051, 317_718_526, false, 28),
+    // 0.4058451513773971669066064121
+    Decimal::from_parts(2_653_561_081, 1_752_523_653, 220_009_097, false, 28),
+    // 0.2077849550078984676006894038
+    Decimal::from_parts(579_803_606, 3_499_037_513, 112_640_449, false, 28),
+];
+
+/// Веса правила Кронрода для узлов `KRONROD_NODES` и, последний, для нуля.
+const KRONROD_WEIGHTS: [Decimal; 8] = [
+    // 0.0229353220105292249637320081
+    Decimal::from_parts(449_574_289, 4_188_603_275, 12_433_262, false, 28),
+    // 0.0630920926299785532907006632
+    Decimal::from_parts(1_450_257_064, 4_071_799_572, 34_202_291, false, 28),
+    // 0.1047900103222501838398763225
+    Decimal::from_parts(3_126_669_529, 1_817_119_238, 56_806_778, false, 28),
+    // 0.1406532597155259187451895905
+    Decimal::from_parts(3_403_915_361, 3_759_687_850, 76_248_284, false, 28),
+    // 0.1690047266392679028265834266
+    Decimal::from_parts(2_379_208_474, 3_828_132_407, 91_617_645, false, 28),
+    // 0.1903505780647854099132564024
+    Decimal::from_parts(3_798_188_600, 583_496_964, 103_189_255, false, 28),
+    // 0.2044329400752988924141619992
+    Decimal::from_parts(52_677_400, 3_773_639_874, 110_823_318, false, 28),
+    // 0.2094821410847278280129991749
+    Decimal::from_parts(1_280_660_549, 989_940_259, 113_560_496, false, 28),
+];
+
+/// Веса правила Гаусса для узлов `KRONROD_NODES[1]`, `[3]`, `[5]` и нуля.
+const GAUSS_WEIGHTS: [Decimal; 4] = [
+    // 0.1294849661688696932706114327
+    Decimal::from_parts(1_940_740_887, 3_488_588_400, 70_193_940, false, 28),
+    // 0.2797053914892766679014677714
+    Decimal::from_parts(1_226_939_602, 2_380_662_331, 151_628_596, false, 28),
+    // 0.3818300505051189449503697755
+    Decimal::from_parts(3_148_770_139, 596_785_361, 206_990_485, false, 28),
+    // 0.4179591836734693877551020408
+    Decimal::from_parts(788_871_544, 2_038_055_367, 226_576_127, false, 28),
+];
+
+/// Первый шаг метода Риддерса относительно `max(1, |x|)`.
+const INITIAL_STEP: Decimal = Decimal::from_parts(1, 0, 0, false, 1);
+
+/// Во сколько раз уменьшается шаг метода Риддерса.
+const STEP_DIVISOR: Decimal = Decimal::from_parts(14, 0, 0, false, 1);
+
+/// Наибольшее количество шагов метода Риддерса.
+const MAX_STEPS: usize = 10;
+
+/// Вычисляет `integrate(body; var; from; to)`.
+pub fn integrate(
+    interpreter: &mut Interpreter, body: &Expr, var: &str, from: &Expr, to: &Expr,
+) -> Result<Value, CalcError> {
+    interpreter.check_variable_name(var)?;
+    let body = symbolic::expand(interpreter, body, Some(var), 0)?;
+    let from = from.evaluate(interpreter)?.real("integrate")?;
+    let to = to.evaluate(interpreter)?.real("integrate")?;
+
+    in_decimal(interpreter, |interpreter| {
+        let tolerance = interpreter.config.tolerance;
+        let max_iterations = interpreter.config.max_iterations;
+        // Значение меньше наименьшего числа `Decimal` ничего не добавляет к интегралу:
+        // хвосты `expe(-x^2)` на `[-10; 10]` считаются нулём.
+        let mut f = |x: Decimal| match interpreter
+            .evaluate_with(&body, var, Value::Real(x))
+            .and_then(|value| value.real("integrate"))
+        {
+            Err(CalcError::Underflow) => Ok(Decimal::ZERO),
+            result => result,
+        };
+
+        let mut segments = vec![Segment::new(&mut f, from, to)?];
+        loop {
+            let (value, error) = segments.iter().try_fold(
+                (Decimal::ZERO, Decimal::ZERO),
+                |(value, error), segment| {
+                    Ok::<_, CalcError>((add(value, segment.value)?, add(error, segment.error)?))
+                },
+            )?;
+            if error <= precision(value, tolerance) {
+                return Ok(round_to_tolerance(value, tolerance));
+            }
+            if segments.len() > max_iterations {
+                return Err(CalcError::AccuracyNotReached(
+                    math::round(error).to_string(),
+                ));
+            }
+
+            let worst = (0..segments.len())
+                .max_by(|&i, &j| segments[i].error.cmp(&segments[j].error))
+                .unwrap_or_default();
+            let segment = segments.swap_remove(worst);
+            let middle = add(segment.from, segment.to)? / Decimal::TWO;
+            segments.push(Segment::new(&mut f, segment.from, middle)?);
+            segments.push(Segment::new(&mut f, middle, segment.to)?);
+        }
+    })
+}
+
+/// Вычисляет `derivative(body; var; at)`.
+pub fn derivative(
+    interpreter: &mut Interpreter, body: &Expr, var: &str, at: &Expr,
+) -> Result<Value, CalcError> {
+    interpreter.check_variable_name(var)?;
+    let body = symbolic::expand(interpreter, body, Some(var), 0)?;
+    let at = at.evaluate(interpreter)?.real("derivative")?;
+
+    in_decimal(interpreter, |interpreter| {
+        let tolerance = interpreter.config.tolerance;
+        let mut f = |x: Decimal| {
+            interpreter
+                .evaluate_with(&body, var, Value::Real(x))?
+                .real("derivative")
+        };
+        // Центральная разность (f(x + h) - f(x - h)) / 2h.
+        let mut central = |step: Decimal| {
+            let difference = sub(f(add(at, step)?)?, f(sub(at, step)?)?)?;
+            div(difference, mul(step, Decimal::TWO)?)
+        };
+
+        let factor = mul(STEP_DIVISOR, STEP_DIVISOR)?;
+        let mut step = mul(at.abs().max(Decimal::ONE), INITIAL_STEP)?;
+        let mut previous = vec![central(step)?];
+        let (mut best, mut error) = (previous[0], Decimal::MAX);
+
+        for _ in 1..MAX_STEPS {
+            step = div(step, STEP_DIVISOR)?;
+            // Строка таблицы Невилла: каждое следующее значение убирает ещё одну степень шага.
+            let mut row = vec![central(step)?];
+            let mut power = factor;
+            for (j, &coarser) in previous.iter().enumerate() {
+                let finer = row[j];
+                let next = div(sub(mul(finer, power)?, coarser)?, sub(power, Decimal::ONE)?)?;
+                let estimate = sub(next, finer)?.abs().max(sub(next, coarser)?.abs());
+                if estimate <= error {
+                    (best, error) = (next, estimate);
+                }
+                row.push(next);
+                power = mul(power, factor)?;
+            }
+
+            // Если диагональ таблицы расходится сильнее оценки, округления уже мешают.
+            let diverged = sub(row[row.len() - 1], previous[previous.len() - 1])?.abs()
+                >= mul(error, Decimal::TWO)?;
+            if diverged || error <= precision(best, tolerance) {
+                break;
+            }
+            previous = row;
+        }
+
+        match error <= precision(best, tolerance) {
+            true => Ok(round_to_tolerance(best, tolerance)),
+            false => Err(CalcError::AccuracyNotReached(
+                math::round(error).to_string(),
+            )),
+        }
+    })
+}
+
+/// Вычисляет `compute` в режиме `Decimal` и переводит результат в числа текущего режима.
+pub(crate) fn in_decimal(
+    interpreter: &mut Interpreter,
+    compute: impl FnOnce(&mut Interpreter) -> Result<Decimal, CalcError>,
+) -> Result<Value, CalcError> {
+    let mode = interpreter.config.number_mode;
+    interpreter.config.number_mode = NumberMode::Decimal;
+    let result = compute(interpreter);
+    interpreter.config.number_mode = mode;
+    Ok(interpreter.config.number(result?))
+}
+
+/// Допустимая погрешность результата `x`: `tolerance * max(1, |x|)`.
+pub(crate) fn precision(x: Decimal, tolerance: Decimal) -> Decimal {
+    tolerance
+        .checked_mul(x.abs().max(Decimal::ONE))
+        .unwrap_or(Decimal::MAX)
+}
+
+/// Округляет результат до разряда перед первым разрядом допустимой погрешности: в нём уже
+/// может быть ошибка метода. Так `solve(x^2 = 4; x; 1)` даёт ровно `2`, а
+/// `integrate(abs(x); x; -1; 2)` - ровно `2.5`.
+pub(crate) fn round_to_tolerance(x: Decimal, tolerance: Decimal) -> Decimal {
+    let precision = precision(x, tolerance).normalize();
+    if precision.is_zero() {
+        return x;
+    }
+    let digits = precision.mantissa().unsigned_abs().ilog10() + 1;
+    x.round_dp(precision.scale().saturating_sub(digits))
+        .normalize()
+}
+
+/// Отрезок интегрирования со значением интеграла на нём и оценкой погрешности.
+struct Segment {
+    from: Decimal,
+    to: Decimal,
+    value: Decimal,
+    error: Decimal,
+}
+
+impl Segment {
+    fn new(
+        f: &mut impl FnMut(Decimal) -> Result<Decimal, CalcError>, from: Decimal, to: Decimal,
+    ) -> Result<Self, CalcError> {
+        let center = add(from, to)? / Decimal::TWO;
+        let half = sub(to, from)? / Decimal::TWO;
+
+        let middle = f(center)?;
+        let mut kronrod = mul(middle, KRONROD_WEIGHTS[7])?;
+        let mut gauss = mul(middle, GAUSS_WEIGHTS[3])?;
+        for (i, node) in KRONROD_NODES.iter().enumerate() {
+            let offset = mul(half, *node)?;
+            let pair = add(f(sub(center, offset)?)?, f(add(center, offset)?)?)?;
+            kronrod = add(kronrod, mul(pair, KRONROD_WEIGHTS[i])?)?;
+            if i % 2 == 1 {
+                gauss = add(gauss, mul(pair, GAUSS_WEIGHTS[i / 2])?)?;
+            }
+        }
+
+        Ok(Segment {
+            from,
+            to,
+            value: mul(kronrod, half)?,
+            error: mul(sub(kronrod, gauss)?, half)?.abs(),
+        })
+    }
+}
+
+fn add(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_add(right).ok_or(CalcError::Overflow)
+}
+
+fn sub(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_sub(right).ok_or(CalcError::Overflow)
+}
+
+fn mul(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_mul(right).ok_or(CalcError::Overflow)
+}
+
+fn div(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_div(right).ok_or(CalcError::DivisionByZero)
+}
@@ -1,5 +1,5 @@
-use crate::{errors::CalcError, interpreter::Interpreter};
-use rust_decimal::Decimal;
+use crate::{errors::CalcError, interpreter::Interpreter, value::Value};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Error, Formatter};
 
 use super::{
@@ -7,7 +7,7 @@ use super::{
     operation::FactoryOp,
 };
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Opcode {
     Mul,
     Div,
@@ -16,17 +16,53 @@ pub enum Opcode {
 
     Add,
     Sub,
+
+    Pow,
+
+    /// Перевод величины в другие единицы: `3 km to mi`.
+    Convert,
+
+    /// Побитовые операторы над целыми числами: `0xF0 or 0x0F`, `1 shl 4`.
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+
+    /// Поэлементные операторы над векторами и матрицами: `[1; 2] .* [3; 4]`.
+    ElemMul,
+    ElemDiv,
+    ElemPow,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnaryOpcode {
+    Neg,
+
+    Factorial,
+    Percent,
+
+    /// Побитовое отрицание: `not 0b1010`.
+    Not,
 }
 
 pub trait Operation {
     fn evaluate(
         &self, left: Box<Expr>, right: Box<Expr>, interpreter: &mut Interpreter,
-    ) -> Result<Decimal, CalcError>;
+    ) -> Result<Value, CalcError>;
+}
+
+pub trait UnaryOperation {
+    fn evaluate(&self, value: Box<Expr>, interpreter: &mut Interpreter)
+        -> Result<Value, CalcError>;
 }
 
 impl Debug for Opcode {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
-        use self::Opcode::{Add, Div, IntDiv, Mod, Mul, Sub};
+        use self::Opcode::{
+            Add, And, Convert, Div, ElemDiv, ElemMul, ElemPow, IntDiv, Mod, Mul, Or, Pow, Shl, Shr,
+            Sub, Xor,
+        };
         match *self {
             Mul => write!(fmt, "*"),
             Div => write!(fmt, "/"),
@@ -34,6 +70,58 @@ impl Debug for Opcode {
             Mod => write!(fmt, "mod"),
             Add => write!(fmt, "+"),
             Sub => write!(fmt, "-"),
+            Pow => write!(fmt, "^"),
+            Convert => write!(fmt, "to"),
+            And => write!(fmt, "and"),
+            Or => write!(fmt, "or"),
+            Xor => write!(fmt, "xor"),
+            Shl => write!(fmt, "shl"),
+            Shr => write!(fmt, "shr"),
+            ElemMul => write!(fmt, ".*"),
+            ElemDiv => write!(fmt, "./"),
+            ElemPow => write!(fmt, ".^"),
+        }
+    }
+}
+
+impl Debug for UnaryOpcode {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        use self::UnaryOpcode::{Factorial, Neg, Not, Percent};
+        match *self {
+            Neg => write!(fmt, "-"),
+            Factorial => write!(fmt, "!"),
+            Percent => write!(fmt, "%"),
+            Not => write!(fmt, "not "),
+        }
+    }
+}
+
+impl UnaryOpcode {
+    /// Записывается ли оператор перед операндом (`-x`), а не после него (`x!`).
+    #[must_use]
+    pub fn is_prefix(self) -> bool {
+        matches!(self, UnaryOpcode::Neg | UnaryOpcode::Not)
+    }
+}
+
+impl Opcode {
+    /// Побитовые операторы работают только с целыми числами.
+    #[must_use]
+    pub fn is_bitwise(self) -> bool {
+        matches!(
+            self,
+            Opcode::And | Opcode::Or | Opcode::Xor | Opcode::Shl | Opcode::Shr
+        )
+    }
+
+    /// Оператор, которым поэлементный оператор действует на числа: `.*` - это `*`.
+    #[must_use]
+    pub fn element(self) -> Opcode {
+        match self {
+            Opcode::ElemMul => Opcode::Mul,
+            Opcode::ElemDiv => Opcode::Div,
+            Opcode::ElemPow => Opcode::Pow,
+            op => op,
         }
     }
 }
@@ -41,10 +129,20 @@ impl Debug for Opcode {
 impl Operation for Opcode {
     fn evaluate(
         &self, left: Box<Expr>, right: Box<Expr>, interpreter: &mut Interpreter,
-    ) -> Result<Decimal, CalcError> {
+    ) -> Result<Value, CalcError> {
         let left = left.evaluate(interpreter)?;
         let right = right.evaluate(interpreter)?;
 
         FactoryOp::match_(*self, left, right)
     }
 }
+
+impl UnaryOperation for UnaryOpcode {
+    fn evaluate(
+        &self, value: Box<Expr>, interpreter: &mut Interpreter,
+    ) -> Result<Value, CalcError> {
+        let value = value.evaluate(interpreter)?;
+
+        FactoryOp::match_unary(*self, value)
+    }
+}
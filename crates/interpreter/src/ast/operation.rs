@@ -1,12 +1,120 @@
-use crate::errors::CalcError;
+use crate::{
+    complex::Complex, errors::CalcError, integer::Integer, matrix::Matrix, number::Number,
+    quantity::Quantity, rational::Rational, value::Value,
+};
 use rust_decimal::Decimal;
 
-use super::opcode::Opcode;
+use super::opcode::{Opcode, UnaryOpcode};
 
 pub struct FactoryOp;
 
 impl FactoryOp {
-    pub fn match_(op: Opcode, left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    /// Вычисляет бинарную операцию. Если хотя бы один операнд комплексный,
+    /// то и операция выполняется над комплексными числами.
+    /// Над двумя дробями операция точна, а если результат не помещается в дробь -
+    /// повторяется над `Decimal`. Вещественные операнды разных видов сначала
+    /// приводятся к общему виду через `Value::unify`. Если хотя бы у одного операнда
+    /// есть единицы измерения, операция выполняется над величинами. Целое число `0xFF`
+    /// вместе с другим целым остаётся целым, пока результат операции целый.
+    pub fn match_(op: Opcode, left: Value, right: Value) -> Result<Value, CalcError> {
+        let expression = |value: &Value| matches!(value, Value::Expression(_));
+        if expression(&left) || expression(&right) {
+            return Err(CalcError::ExpressionNotSupported(format!("{op:?}")));
+        }
+        let matrix = |value: &Value| matches!(value, Value::Matrix(_));
+        if matrix(&left) || matrix(&right) {
+            return Self::match_matrix(op, left, right);
+        }
+        let op = op.element();
+        if op.is_bitwise() {
+            return Self::match_bitwise(op, &left, &right).map(Value::Integer);
+        }
+        let quantity = |value: &Value| matches!(value, Value::Quantity(_));
+        if matches!(op, Opcode::Convert) || quantity(&left) || quantity(&right) {
+            return Self::match_quantity(op, &left, &right).map(Quantity::into_value);
+        }
+        if let Some((left, right)) = Integer::pair(&left, &right) {
+            return Self::match_integer(op, left, right);
+        }
+
+        match Value::unify(left, right)? {
+            (Value::Rational(left), Value::Rational(right)) => {
+                Self::match_rational(op, left, right)
+                    .map(Value::Rational)
+                    .or_else(|_| {
+                        Self::match_(op, left.to_decimal().into(), right.to_decimal().into())
+                    })
+            }
+            (Value::Real(left), Value::Real(right)) if !Pow::is_complex(op, &left, &right) => {
+                Self::match_real(op, &left, &right).map(Value::Real)
+            }
+            (Value::Float(left), Value::Float(right)) if !Pow::is_complex(op, &left, &right) => {
+                Self::match_real(op, &left, &right).map(Value::Float)
+            }
+            (Value::Big(left), Value::Big(right)) if !Pow::is_complex(op, &left, &right) => {
+                Self::match_real(op, &left, &right).map(Value::Big)
+            }
+            (left, right) => {
+                Self::match_complex(op, left.to_complex()?, right.to_complex()?).map(Value::from)
+            }
+        }
+    }
+
+    pub fn match_unary(op: UnaryOpcode, value: Value) -> Result<Value, CalcError> {
+        if let Value::Expression(_) = value {
+            let name = format!("{op:?}");
+            return Err(CalcError::ExpressionNotSupported(name.trim().to_string()));
+        }
+        if let Value::Matrix(matrix) = &value {
+            return matrix
+                .map(|value| Self::match_unary(op, value.clone()))
+                .map(Value::Matrix);
+        }
+        if let UnaryOpcode::Not = op {
+            return Integer::from_value(&value, "not")?
+                .not()
+                .map(Value::Integer);
+        }
+
+        match value {
+            Value::Real(value) => Self::match_unary_real(op, &value).map(Value::Real),
+            Value::Float(value) => Self::match_unary_real(op, &value).map(Value::Float),
+            Value::Big(value) => Self::match_unary_real(op, &value).map(Value::Big),
+            Value::Rational(value) => Self::match_unary_rational(op, value).map(Value::Rational),
+            Value::Complex(value) => Self::match_unary_complex(op, value).map(Value::from),
+            Value::Quantity(value) => {
+                Self::match_unary_quantity(op, &value).map(Quantity::into_value)
+            }
+            Value::Integer(value) => Self::match_unary_integer(op, value),
+            Value::Matrix(_) => unreachable!("vectors and matrices are evaluated element-wise"),
+            Value::Expression(_) => unreachable!("expressions are not evaluated"),
+        }
+    }
+
+    /// `*`, `/` и `^` над матрицами - матричные операции. Остальные операторы, в том числе
+    /// `.*`, `./` и `.^`, применяются к элементам, а число - к каждому элементу.
+    fn match_matrix(op: Opcode, left: Value, right: Value) -> Result<Value, CalcError> {
+        match (op, &left, &right) {
+            (Opcode::Mul, Value::Matrix(left), Value::Matrix(right)) => left.mul(right),
+            (Opcode::Div, Value::Matrix(left), Value::Matrix(right)) => left.mul(&right.inv("/")?),
+            (Opcode::Pow, Value::Matrix(left), right) if left.is_matrix() => {
+                left.pow(right).map(Value::Matrix)
+            }
+            _ => Self::match_elements(op.element(), &left, &right).map(Value::Matrix),
+        }
+    }
+
+    fn match_elements(op: Opcode, left: &Value, right: &Value) -> Result<Matrix, CalcError> {
+        let apply = |left: &Value, right: &Value| Self::match_(op, left.clone(), right.clone());
+        match (left, right) {
+            (Value::Matrix(left), Value::Matrix(right)) => left.zip_with(right, apply),
+            (Value::Matrix(left), right) => left.map(|left| apply(left, right)),
+            (left, Value::Matrix(right)) => right.map(|right| apply(left, right)),
+            _ => unreachable!("at least one operand is a matrix"),
+        }
+    }
+
+    fn match_real<N: Number>(op: Opcode, left: &N, right: &N) -> Result<N, CalcError> {
         match op {
             Opcode::Mul => Mul::ahead(left, right),
             Opcode::Div => Div::ahead(left, right),
@@ -14,64 +122,318 @@ impl FactoryOp {
             Opcode::IntDiv => IntDiv::ahead(left, right),
             Opcode::Add => Add::ahead(left, right),
             Opcode::Sub => Sub::ahead(left, right),
+            Opcode::Pow => Pow::ahead(left, right),
+            Opcode::Convert
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Shl
+            | Opcode::Shr
+            | Opcode::ElemMul
+            | Opcode::ElemDiv
+            | Opcode::ElemPow => unreachable!("evaluated before the operands are unified"),
+        }
+    }
+
+    fn match_rational(op: Opcode, left: Rational, right: Rational) -> Result<Rational, CalcError> {
+        match op {
+            Opcode::Mul => left.checked_mul(right),
+            Opcode::Div => left.checked_div(right),
+            Opcode::Mod => left.checked_rem(right),
+            Opcode::IntDiv => left.checked_int_div(right),
+            Opcode::Add => left.checked_add(right),
+            Opcode::Sub => left.checked_sub(right),
+            Opcode::Pow => left.checked_pow(right),
+            Opcode::Convert
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Shl
+            | Opcode::Shr
+            | Opcode::ElemMul
+            | Opcode::ElemDiv
+            | Opcode::ElemPow => unreachable!("evaluated before the operands are unified"),
+        }
+    }
+
+    fn match_complex(op: Opcode, left: Complex, right: Complex) -> Result<Complex, CalcError> {
+        match op {
+            Opcode::Mul => left.checked_mul(right),
+            Opcode::Div => left.checked_div(right),
+            Opcode::Add => left.checked_add(right),
+            Opcode::Sub => left.checked_sub(right),
+            Opcode::Pow => left.powc(right),
+            Opcode::Mod | Opcode::IntDiv => Err(CalcError::ComplexNotSupported(format!("{op:?}"))),
+            Opcode::Convert
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Shl
+            | Opcode::Shr
+            | Opcode::ElemMul
+            | Opcode::ElemDiv
+            | Opcode::ElemPow => unreachable!("evaluated before the operands are unified"),
+        }
+    }
+
+    /// Числа участвуют в операции как величины без единиц.
+    fn match_quantity(op: Opcode, left: &Value, right: &Value) -> Result<Quantity, CalcError> {
+        let name = format!("{op:?}");
+        let left = Quantity::from_value(left, &name)?;
+        let right = Quantity::from_value(right, &name)?;
+        match op {
+            Opcode::Mul => left.checked_mul(&right),
+            Opcode::Div => left.checked_div(&right),
+            Opcode::Mod => left.checked_rem(&right),
+            Opcode::IntDiv => left.checked_int_div(&right),
+            Opcode::Add => left.checked_add(&right),
+            Opcode::Sub => left.checked_sub(&right),
+            Opcode::Pow => left.pow(&right),
+            Opcode::Convert => left.convert(&right),
+            Opcode::And | Opcode::Or | Opcode::Xor | Opcode::Shl | Opcode::Shr => {
+                unreachable!("bitwise operators are evaluated over integers")
+            }
+            Opcode::ElemMul | Opcode::ElemDiv | Opcode::ElemPow => {
+                unreachable!("element-wise operators are replaced before")
+            }
+        }
+    }
+
+    /// Операция над целыми. Если результат не целый или не помещается в `Decimal`,
+    /// она повторяется над `Decimal`: `0x7 / 2` равно `3.5`.
+    fn match_integer(op: Opcode, left: Integer, right: Integer) -> Result<Value, CalcError> {
+        let result = match op {
+            Opcode::Mul => left.checked_mul(&right),
+            Opcode::Div => left.checked_div(&right),
+            Opcode::Mod => left.checked_rem(&right),
+            Opcode::IntDiv => left.checked_int_div(&right),
+            Opcode::Add => left.checked_add(&right),
+            Opcode::Sub => left.checked_sub(&right),
+            Opcode::Pow => left.checked_pow(&right),
+            Opcode::Convert
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Shl
+            | Opcode::Shr
+            | Opcode::ElemMul
+            | Opcode::ElemDiv
+            | Opcode::ElemPow => unreachable!("evaluated before the operands are unified"),
+        };
+        match result {
+            Some(result) => Ok(Value::Integer(result)),
+            None => Self::match_(op, left.to_decimal().into(), right.to_decimal().into()),
+        }
+    }
+
+    /// Результат берёт систему счисления целого операнда: `0xF0 or 15` равно `0xFF`.
+    fn match_bitwise(op: Opcode, left: &Value, right: &Value) -> Result<Integer, CalcError> {
+        let name = format!("{op:?}");
+        let base = match (left, right) {
+            (Value::Integer(integer), _) | (_, Value::Integer(integer)) => integer.base(),
+            _ => Default::default(),
+        };
+        let left = Integer::from_value(left, &name)?.with_base(base);
+        let right = Integer::from_value(right, &name)?;
+        match op {
+            Opcode::And => left.and(&right),
+            Opcode::Or => left.or(&right),
+            Opcode::Xor => left.xor(&right),
+            Opcode::Shl => left.shl(&right),
+            Opcode::Shr => left.shr(&right),
+            _ => unreachable!("only bitwise operators are evaluated over bits"),
+        }
+    }
+
+    fn match_unary_real<N: Number>(op: UnaryOpcode, value: &N) -> Result<N, CalcError> {
+        match op {
+            UnaryOpcode::Neg => Neg::ahead(value),
+            UnaryOpcode::Factorial => Factorial::ahead(value),
+            UnaryOpcode::Percent => Percent::ahead(value),
+            UnaryOpcode::Not => unreachable!("bitwise operators are evaluated over integers"),
+        }
+    }
+
+    fn match_unary_rational(op: UnaryOpcode, value: Rational) -> Result<Rational, CalcError> {
+        match op {
+            UnaryOpcode::Neg => Ok(-value),
+            UnaryOpcode::Factorial => Factorial::ahead(&value.to_decimal()).map(Rational::from),
+            UnaryOpcode::Percent => value.checked_div(Rational::from(Decimal::ONE_HUNDRED)),
+            UnaryOpcode::Not => unreachable!("bitwise operators are evaluated over integers"),
+        }
+    }
+
+    fn match_unary_complex(op: UnaryOpcode, value: Complex) -> Result<Complex, CalcError> {
+        match op {
+            UnaryOpcode::Neg => Ok(-value),
+            UnaryOpcode::Percent => value.checked_div(Complex::from(Decimal::ONE_HUNDRED)),
+            UnaryOpcode::Factorial => Err(CalcError::ComplexNotSupported("Factorial".to_string())),
+            UnaryOpcode::Not => unreachable!("bitwise operators are evaluated over integers"),
+        }
+    }
+
+    fn match_unary_quantity(op: UnaryOpcode, value: &Quantity) -> Result<Quantity, CalcError> {
+        match op {
+            UnaryOpcode::Neg => Ok(value.neg()),
+            UnaryOpcode::Percent => value.percent(),
+            UnaryOpcode::Factorial => Err(CalcError::UnitNotSupported("Factorial".to_string())),
+            UnaryOpcode::Not => unreachable!("bitwise operators are evaluated over integers"),
+        }
+    }
+
+    fn match_unary_integer(op: UnaryOpcode, value: Integer) -> Result<Value, CalcError> {
+        let decimal = value.to_decimal();
+        match op {
+            UnaryOpcode::Neg => value.neg().map(Value::Integer),
+            UnaryOpcode::Factorial => Factorial::ahead(&decimal).map(|result| {
+                Integer::from_decimal(result, value.base())
+                    .map_or(result.into(), |result| Value::Integer(result.like(&value)))
+            }),
+            UnaryOpcode::Percent => Percent::ahead(&decimal).map(Value::Real),
+            UnaryOpcode::Not => unreachable!("bitwise operators are evaluated over integers"),
         }
     }
 }
 
 pub trait Operation {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError>;
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError>;
+}
+
+pub trait UnaryOperation {
+    fn ahead<N: Number>(value: &N) -> Result<N, CalcError>;
+}
+
+/// Возвращает `CalcError::Overflow`, если результат не поместился в тип числа,
+/// и `CalcError::Underflow`, если ненулевые операнды дали ноль из-за потери точности.
+fn checked<N: Number>(result: Option<N>, left: &N, right: &N) -> Result<N, CalcError> {
+    match result {
+        None => Err(CalcError::Overflow),
+        Some(result) if result.is_zero() && !left.is_zero() && !right.is_zero() => {
+            Err(CalcError::Underflow)
+        }
+        Some(result) => Ok(result),
+    }
 }
 
 pub struct Add;
 
 impl Operation for Add {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
-        Ok(left + right)
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        left.checked_add(right).ok_or(CalcError::Overflow)
     }
 }
 
 pub struct Sub;
 
 impl Operation for Sub {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
-        Ok(left - right)
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        left.checked_sub(right).ok_or(CalcError::Overflow)
     }
 }
 
 pub struct IntDiv;
 
 impl Operation for IntDiv {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
-        if right == 0.0.try_into().unwrap() {
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        if right.is_zero() {
             return Err(CalcError::DivisionByZero);
         }
-        Ok((left / right).trunc())
+        left.checked_div(right)
+            .map(|result| result.trunc())
+            .ok_or(CalcError::Overflow)
     }
 }
 
 pub struct Mod;
 
 impl Operation for Mod {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
-        Ok(left % right)
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        if right.is_zero() {
+            return Err(CalcError::ModuloByZero);
+        }
+        left.checked_rem(right).ok_or(CalcError::Overflow)
     }
 }
 
 pub struct Mul;
 
 impl Operation for Mul {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
-        Ok(left * right)
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        checked(left.checked_mul(right), left, right)
     }
 }
 
 pub struct Div;
 
 impl Operation for Div {
-    fn ahead(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
-        if right == 0.0.try_into().unwrap() {
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        if right.is_zero() {
             return Err(CalcError::DivisionByZero);
         }
-        Ok(left / right)
+        checked(left.checked_div(right), left, right)
+    }
+}
+
+pub struct Pow;
+
+impl Pow {
+    /// Степень отрицательного числа с дробным показателем - комплексное число: `(-4)^0.5` равно `2i`.
+    fn is_complex<N: Number>(op: Opcode, left: &N, right: &N) -> bool {
+        matches!(op, Opcode::Pow)
+            && left.is_sign_negative()
+            && !left.is_zero()
+            && !right.is_integer()
+    }
+}
+
+impl Operation for Pow {
+    fn ahead<N: Number>(left: &N, right: &N) -> Result<N, CalcError> {
+        left.pow(right)
+    }
+}
+
+pub struct Neg;
+
+impl UnaryOperation for Neg {
+    fn ahead<N: Number>(value: &N) -> Result<N, CalcError> {
+        Ok(value.neg())
+    }
+}
+
+pub struct Factorial;
+
+impl Factorial {
+    /// Наибольший аргумент факториала. Для `Decimal` и `f64` переполнение наступает раньше,
+    /// а числа произвольной точности иначе считали бы факториал бесконечно долго.
+    const MAX_ARGUMENT: Decimal = Decimal::from_parts(100_000, 0, 0, false, 0);
+}
+
+impl UnaryOperation for Factorial {
+    fn ahead<N: Number>(value: &N) -> Result<N, CalcError> {
+        if value.is_sign_negative() || !value.is_integer() {
+            return Err(CalcError::FactorialOfNotNaturalNumber);
+        }
+        if *value > N::from_decimal(Self::MAX_ARGUMENT) {
+            return Err(CalcError::Overflow);
+        }
+
+        // Множители берут точность аргумента с запасом, чтобы ошибки округления не накапливались.
+        let one = N::from_decimal(Decimal::ONE);
+        let mut result = one.clone();
+        let mut i = value.widened();
+        while i > one {
+            result = i.checked_mul(&result).ok_or(CalcError::Overflow)?;
+            i = i.checked_sub(&one).ok_or(CalcError::Overflow)?;
+        }
+        Ok(result.narrowed(value))
+    }
+}
+
+pub struct Percent;
+
+impl UnaryOperation for Percent {
+    fn ahead<N: Number>(value: &N) -> Result<N, CalcError> {
+        let hundred = N::from_decimal(Decimal::ONE_HUNDRED);
+        checked(value.checked_div(&hundred), value, &hundred).map(Number::normalize)
     }
 }
@@ -0,0 +1,81 @@
+//! Суммы и произведения: `sum(i; 1; 100; i^2)`, `prod(k; 2; n; 1 - 1/k^2)`.
+//!
+//! Индекс пробегает целые числа от нижней границы до верхней и связывается через
+//! `Interpreter::evaluate_with`, поэтому переменная пользователя с тем же именем не меняется.
+//! Члены складываются или перемножаются обычными операторами, так что в рациональном режиме
+//! сумма остаётся точной дробью, а сумма векторов - вектором. Количество членов ограничено
+//! `Config::max_terms`: слишком большой диапазон сразу даёт ошибку, а не вешает консоль.
+//! Вложенные суммы делят этот запас с внешней, поэтому
+//! `sum(i; 1; 100000; sum(j; 1; 100000; 1))` тоже не вычисляется, а ошибка называет,
+//! сколько членов всего запрошено к этому моменту.
+
+use rust_decimal::Decimal;
+
+use super::{
+    expr::{Evaluatable, Expr},
+    opcode::Opcode,
+    operation::FactoryOp,
+};
+use crate::{
+    errors::CalcError, integer::Integer, interpreter::Interpreter, symbolic, value::Value,
+};
+
+/// Вычисляет `sum(index; from; to; body)`, если `op` - сложение, и `prod(...)`, если умножение.
+/// Пустой диапазон даёт `0` для суммы и `1` для произведения.
+pub fn evaluate(
+    interpreter: &mut Interpreter, op: Opcode, index: &str, from: &Expr, to: &Expr, body: &Expr,
+) -> Result<Value, CalcError> {
+    let name = match op {
+        Opcode::Mul => "prod",
+        _ => "sum",
+    };
+    interpreter.check_index_name(index)?;
+    let body = symbolic::expand(interpreter, body, Some(index), 0)?;
+    let from = Integer::from_value(&from.evaluate(interpreter)?, name)?.value();
+    let to = Integer::from_value(&to.evaluate(interpreter)?, name)?.value();
+
+    let max_terms = interpreter.config.max_terms;
+    let left = interpreter.terms_left.unwrap_or(max_terms);
+    let count = (to - from + 1).max(0);
+    let outermost = interpreter.terms_left.is_none();
+    if count > left as i128 && outermost {
+        return Err(CalcError::TooManyTerms(
+            name.to_string(),
+            count.to_string(),
+            max_terms,
+        ));
+    }
+    if count > left as i128 {
+        let total = count + (max_terms - left) as i128;
+        return Err(CalcError::TooManyNestedTerms(total.to_string(), max_terms));
+    }
+
+    interpreter.terms_left = Some(left - count as usize);
+    let result = fold(interpreter, op, index, &body, from, to);
+    if outermost {
+        interpreter.terms_left = None;
+    }
+    result
+}
+
+/// Складывает или перемножает члены `body` для индекса от `from` до `to`.
+fn fold(
+    interpreter: &mut Interpreter, op: Opcode, index: &str, body: &Expr, from: i128, to: i128,
+) -> Result<Value, CalcError> {
+    let mut result: Option<Value> = None;
+    for i in from..=to {
+        let value = interpreter
+            .config
+            .literal(Decimal::from_i128_with_scale(i, 0));
+        let term = interpreter.evaluate_with(body, index, value)?;
+        result = Some(match result {
+            Some(result) => FactoryOp::match_(op, result, term)?,
+            None => term,
+        });
+    }
+    let empty = match op {
+        Opcode::Mul => Decimal::ONE,
+        _ => Decimal::ZERO,
+    };
+    Ok(result.unwrap_or_else(|| interpreter.config.literal(empty)))
+}
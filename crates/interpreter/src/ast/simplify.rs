@@ -0,0 +1,487 @@
+//! Упрощение выражений: `2 * x + x - x * 0 - 3` превращается в `3 * x - 3`.
+//!
+//! Выражение приводится к нормальной форме - сумме слагаемых, каждое из которых есть точный
+//! коэффициент, умноженный на произведение степеней. Числа сворачиваются точными дробями,
+//! одинаковые слагаемые складываются, а одинаковые основания степеней перемножаются:
+//! `x * x^2 / x` - это `x^2`. Число перед суммой раскрывается: `2 * (x + 1)` - это `2 * x + 2`.
+//! Слагаемые и множители выстраиваются в одном порядке, поэтому `y + x` и `x + y` упрощаются
+//! одинаково. Имена считаются числами, а литералы матриц и переменные, в которых хранятся
+//! векторы и матрицы, не переставляются между собой, а деление на них остаётся частным:
+//! `B * A` остаётся `B * A`.
+//!
+//! Имена в делителе считаются ненулевыми, поэтому `x / x` - это `1`, а `0 / x` - `0`.
+//! Вызовы функций не вычисляются: у `sin(30)` в общем случае нет точного значения.
+
+use rust_decimal::Decimal;
+use std::cmp::Ordering;
+
+use super::{
+    expr::{Evaluatable, Expr},
+    opcode::{Opcode, UnaryOpcode},
+};
+use crate::{
+    errors::CalcError,
+    history::History,
+    interpreter::Interpreter,
+    rational::Rational,
+    symbolic::{self, Expression},
+    traits::GetResult,
+    value::Value,
+};
+
+/// Сколько раз проходить по выражению, если оно всё ещё меняется.
+const MAX_PASSES: usize = 16;
+
+/// Наибольший показатель степени, которую сворачиваем в число.
+const MAX_FOLDED_POWER: u32 = 64;
+
+/// Вычисляет `simplify(expr)`: упрощённое выражение или его значение, если в нём нет имён.
+pub fn evaluate(interpreter: &mut Interpreter, expr: &Expr) -> Result<Value, CalcError> {
+    let simplified = simplify_in(interpreter, &symbolic::expand(interpreter, expr, None, 0)?);
+    match symbolic::variables(&simplified).is_empty() {
+        true => simplified.evaluate(interpreter),
+        false => Ok(Value::Expression(Expression::new(simplified))),
+    }
+}
+
+/// Упрощённая запись `expr` без участков ввода. Все имена считаются числами.
+#[must_use]
+pub fn simplify(expr: &Expr) -> Expr {
+    Simplifier { matrices: &[] }.simplify(expr)
+}
+
+/// Упрощённая запись `expr`, в которой имена векторов и матриц интерпретатора сохраняют
+/// свой порядок.
+#[must_use]
+pub fn simplify_in(interpreter: &Interpreter, expr: &Expr) -> Expr {
+    let matrices = symbolic::variables(expr)
+        .into_iter()
+        .filter(|name| holds_matrix(interpreter, name))
+        .collect::<Vec<String>>();
+    Simplifier {
+        matrices: &matrices,
+    }
+    .simplify(expr)
+}
+
+/// Хранит ли имя вектор или матрицу.
+fn holds_matrix(interpreter: &Interpreter, name: &str) -> bool {
+    let value = match History::index_by_name(name) {
+        Some(index) => interpreter.get_history_result(index).and_then(Result::ok),
+        None => interpreter
+            .binding(name)
+            .or_else(|| interpreter.variables.get_result(name)),
+    };
+    matches!(value, Some(Value::Matrix(_)))
+}
+
+/// Слагаемое нормальной формы: `coefficient * x^2 * sin(x)`.
+#[derive(Clone, PartialEq)]
+struct Term {
+    coefficient: Rational,
+    factors: Vec<Factor>,
+}
+
+/// Множитель слагаемого: основание в точной степени.
+#[derive(Clone, PartialEq)]
+struct Factor {
+    base: Expr,
+    exponent: Rational,
+}
+
+/// Упрощение, которое не переставляет имена `matrices`: их произведение некоммутативно.
+struct Simplifier<'a> {
+    matrices: &'a [String],
+}
+
+impl Simplifier<'_> {
+    fn simplify(&self, expr: &Expr) -> Expr {
+        let mut current = expr.clone();
+        for _ in 0..MAX_PASSES {
+            let next = self.normalize(&current);
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        current
+    }
+
+    fn normalize(&self, expr: &Expr) -> Expr {
+        let normalize_all = |exprs: &[Box<Expr>]| {
+            exprs
+                .iter()
+                .map(|expr| Box::new(self.normalize(expr)))
+                .collect()
+        };
+
+        match expr {
+            Expr::Op(_, Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div | Opcode::Pow, _)
+            | Expr::UnaryOp(UnaryOpcode::Neg, _) => self
+                .normalize_sum(expr)
+                .unwrap_or_else(|_| self.normalize_operands(expr)),
+            Expr::Value(_) => rational(expr).map_or_else(|| expr.clone(), rational_expr),
+            Expr::Op(..) | Expr::UnaryOp(..) => self.normalize_operands(expr),
+            Expr::Call(name, args) => Expr::Call(name.clone(), normalize_all(args)),
+            Expr::Matrix(elements) => Expr::Matrix(normalize_all(elements)),
+            Expr::Spanned(_, expr) => self.normalize(expr),
+            _ => expr.clone(),
+        }
+    }
+
+    /// Упрощает только операнды, если сам оператор упростить нельзя: `x mod 2`, `x / 0`.
+    fn normalize_operands(&self, expr: &Expr) -> Expr {
+        match expr {
+            Expr::Op(left, op, right) => Expr::Op(
+                Box::new(self.normalize(left)),
+                *op,
+                Box::new(self.normalize(right)),
+            ),
+            Expr::UnaryOp(op, value) => Expr::UnaryOp(*op, Box::new(self.normalize(value))),
+            _ => self.normalize(expr),
+        }
+    }
+
+    fn normalize_sum(&self, expr: &Expr) -> Result<Expr, CalcError> {
+        let mut terms = Vec::new();
+        self.collect_terms(expr, one(), &mut terms)?;
+        terms.retain(|term| !term.coefficient.numer().is_zero());
+        terms.sort_by(|a, b| self.compare_terms(a, b));
+        // Сумма начинается с положительного слагаемого, если оно есть: `1 - ln(x)`, а не `-ln(x) + 1`.
+        let is_negative = |term: &Term| term.coefficient.numer().is_sign_negative();
+        if let Some(positive) = terms.iter().position(|term| !is_negative(term)) {
+            let term = terms.remove(positive);
+            terms.insert(0, term);
+        }
+
+        let mut terms = terms.into_iter();
+        let Some(first) = terms.next() else {
+            return Ok(Expr::Number(Decimal::ZERO));
+        };
+        Ok(terms.fold(self.build_term(&first), |sum, term| {
+            match is_negative(&term) {
+                true => {
+                    let term = Term {
+                        coefficient: -term.coefficient,
+                        ..term
+                    };
+                    op(sum, Opcode::Sub, self.build_term(&term))
+                }
+                false => op(sum, Opcode::Add, self.build_term(&term)),
+            }
+        }))
+    }
+
+    /// Раскладывает сумму на слагаемые и складывает подобные.
+    fn collect_terms(
+        &self, expr: &Expr, sign: Rational, terms: &mut Vec<Term>,
+    ) -> Result<(), CalcError> {
+        match expr.unspanned() {
+            Expr::Op(left, Opcode::Add, right) => {
+                self.collect_terms(left, sign, terms)?;
+                self.collect_terms(right, sign, terms)
+            }
+            Expr::Op(left, Opcode::Sub, right) => {
+                self.collect_terms(left, sign, terms)?;
+                self.collect_terms(right, -sign, terms)
+            }
+            Expr::UnaryOp(UnaryOpcode::Neg, value) => self.collect_terms(value, -sign, terms),
+            expr => {
+                let mut term = Term {
+                    coefficient: sign,
+                    factors: Vec::new(),
+                };
+                self.collect_factors(expr, one(), &mut term)?;
+                term.factors
+                    .retain(|factor| !factor.exponent.numer().is_zero());
+                term.factors
+                    .sort_by(|a, b| self.compare_bases(&a.base, &b.base));
+
+                // Число, умноженное на сумму, раскрывается: `2 * (x + 1) - 2 * x` - это `2`.
+                if let [Factor { base, exponent }] = term.factors.as_slice() {
+                    if *exponent == one() && is_sum(base) {
+                        return self.collect_terms(base, term.coefficient, terms);
+                    }
+                }
+
+                match terms.iter_mut().find(|like| like.factors == term.factors) {
+                    Some(like) => {
+                        like.coefficient = like.coefficient.checked_add(term.coefficient)?;
+                    }
+                    None => terms.push(term),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Раскладывает произведение на коэффициент и степени. `power` равен `-1` в знаменателе.
+    fn collect_factors(
+        &self, expr: &Expr, power: Rational, term: &mut Term,
+    ) -> Result<(), CalcError> {
+        if let Some(number) = rational(expr) {
+            term.coefficient = match power.numer().is_sign_negative() {
+                true => term.coefficient.checked_div(number)?,
+                false => term.coefficient.checked_mul(number)?,
+            };
+            return Ok(());
+        }
+
+        match expr.unspanned() {
+            Expr::Op(left, Opcode::Mul, right) => {
+                self.collect_factors(left, power, term)?;
+                self.collect_factors(right, power, term)
+            }
+            // Деление на матрицу - умножение на обратную, а деление матрицы на число -
+            // поэлементное, поэтому такое частное остаётся целым множителем.
+            Expr::Op(left, Opcode::Div, right) if !self.commutes(right) => self.multiply(
+                term,
+                op(self.normalize(left), Opcode::Div, self.normalize(right)),
+                power,
+            ),
+            Expr::Op(left, Opcode::Div, right) => {
+                self.collect_factors(left, power, term)?;
+                self.collect_factors(right, -power, term)
+            }
+            Expr::UnaryOp(UnaryOpcode::Neg, value) => {
+                term.coefficient = -term.coefficient;
+                self.collect_factors(value, power, term)
+            }
+            Expr::Op(base, Opcode::Pow, exponent) => {
+                match simplify_power(self.normalize(base), self.normalize(exponent)) {
+                    Expr::Op(base, Opcode::Pow, exponent) => match rational(&exponent) {
+                        Some(exponent) => self.multiply(term, *base, exponent.checked_mul(power)?),
+                        None => self.multiply(term, Expr::Op(base, Opcode::Pow, exponent), power),
+                    },
+                    expr => self.collect_factors(&expr, power, term),
+                }
+            }
+            expr => match self.normalize(expr) {
+                normalized @ (Expr::Op(_, Opcode::Mul | Opcode::Div | Opcode::Pow, _)
+                | Expr::UnaryOp(UnaryOpcode::Neg, _)
+                | Expr::Number(_)) => self.collect_factors(&normalized, power, term),
+                normalized => self.multiply(term, normalized, power),
+            },
+        }
+    }
+
+    /// Домножает слагаемое на `base^exponent`, складывая показатели одинаковых оснований.
+    /// Матрица объединяется только с последней матрицей слагаемого: `A * B * A` - не `A^2 * B`.
+    fn multiply(&self, term: &mut Term, base: Expr, exponent: Rational) -> Result<(), CalcError> {
+        let like = match self.commutes(&base) {
+            true => term.factors.iter_mut().find(|factor| factor.base == base),
+            false => term
+                .factors
+                .iter_mut()
+                .rev()
+                .find(|factor| !self.commutes(&factor.base))
+                .filter(|factor| factor.base == base),
+        };
+        match like {
+            Some(factor) => factor.exponent = factor.exponent.checked_add(exponent)?,
+            None => term.factors.push(Factor { base, exponent }),
+        }
+        Ok(())
+    }
+
+    /// Записывает слагаемое выражением: `2 * x^2 * y / 3`, `-x / y`. Дробный коэффициент
+    /// пишется десятичной дробью, только если у слагаемого нет знаменателя: `0.5 * x`, но `1 / (2 * y)`.
+    fn build_term(&self, term: &Term) -> Expr {
+        let has_divisors = term
+            .factors
+            .iter()
+            .any(|factor| factor.exponent.numer().is_sign_negative());
+        let (coefficient, denominator) = match exact_decimal(term.coefficient) {
+            Some(coefficient) if !has_divisors || term.coefficient.is_integer() => {
+                (coefficient, Decimal::ONE)
+            }
+            _ => (term.coefficient.numer(), term.coefficient.denom()),
+        };
+
+        let mut numerator = Vec::new();
+        let mut divisors = Vec::new();
+        if denominator != Decimal::ONE {
+            divisors.push(Expr::Number(denominator));
+        }
+        for factor in &term.factors {
+            // Отрицательная степень матрицы - степень обратной матрицы, а не деление на неё.
+            match factor.exponent.numer().is_sign_negative() && self.commutes(&factor.base) {
+                true => divisors.push(power_expr(&factor.base, -factor.exponent)),
+                false => numerator.push(power_expr(&factor.base, factor.exponent)),
+            }
+        }
+
+        if coefficient == Decimal::NEGATIVE_ONE && !numerator.is_empty() {
+            let first = numerator.remove(0);
+            numerator.insert(0, Expr::UnaryOp(UnaryOpcode::Neg, Box::new(first)));
+        } else if coefficient != Decimal::ONE || numerator.is_empty() {
+            numerator.insert(0, Expr::Number(coefficient));
+        }
+
+        let numerator = product(numerator).unwrap_or(Expr::Number(Decimal::ONE));
+        match product(divisors) {
+            Some(divisor) => op(numerator, Opcode::Div, divisor),
+            None => numerator,
+        }
+    }
+
+    /// Порядок слагаемых: старшие степени впереди, числа в конце: `x^2 + 2 * x + 1`.
+    fn compare_terms(&self, a: &Term, b: &Term) -> Ordering {
+        let degree = |term: &Term| {
+            term.factors
+                .iter()
+                .map(|factor| factor.exponent.to_decimal())
+                .sum::<Decimal>()
+        };
+        let factors = || {
+            a.factors
+                .iter()
+                .zip(&b.factors)
+                .map(|(a, b)| {
+                    self.compare_bases(&a.base, &b.base)
+                        .then_with(|| b.exponent.to_decimal().cmp(&a.exponent.to_decimal()))
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        };
+
+        a.factors
+            .is_empty()
+            .cmp(&b.factors.is_empty())
+            .then_with(|| degree(b).cmp(&degree(a)))
+            .then_with(factors)
+            .then_with(|| a.factors.len().cmp(&b.factors.len()))
+    }
+
+    /// Порядок множителей: переменные по алфавиту, затем функции. Остальные множители,
+    /// например матрицы, сохраняют свой порядок, потому что их произведение некоммутативно.
+    fn compare_bases(&self, a: &Expr, b: &Expr) -> Ordering {
+        let rank = |expr: &Expr| match expr {
+            expr if !self.commutes(expr) => 2,
+            Expr::Variable(_) => 0,
+            Expr::Call(..) => 1,
+            _ => 2,
+        };
+
+        match (rank(a), rank(b)) {
+            (2, 2) => Ordering::Equal,
+            (a_rank, b_rank) if a_rank != b_rank => a_rank.cmp(&b_rank),
+            _ => a.to_string().cmp(&b.to_string()),
+        }
+    }
+
+    /// Перестановочен ли множитель с остальными: в нём нет ни литералов матриц, ни имён матриц.
+    fn commutes(&self, expr: &Expr) -> bool {
+        !contains_matrix(expr)
+            && !symbolic::variables(expr)
+                .iter()
+                .any(|name| self.matrices.contains(name))
+    }
+}
+
+/// Степень с упрощёнными основанием и показателем: `x^1 = x`, `2^3 = 8`, `(x^2)^3 = x^6`.
+fn simplify_power(base: Expr, exponent: Expr) -> Expr {
+    let power = rational(&exponent);
+    if power.is_some_and(|power| power.numer().is_zero()) || rational(&base) == Some(one()) {
+        return Expr::Number(Decimal::ONE);
+    }
+    if power == Some(one()) {
+        return base;
+    }
+
+    // Целую степень числа и степень степени можно свернуть точно.
+    let Some(power) = power.filter(|power| {
+        power.is_integer() && power.numer().abs() <= Decimal::from(MAX_FOLDED_POWER)
+    }) else {
+        return op(base, Opcode::Pow, exponent);
+    };
+    if let Some(Ok(number)) = rational(&base).map(|base| base.checked_pow(power)) {
+        return rational_expr(number);
+    }
+    match base {
+        Expr::Op(inner, Opcode::Pow, inner_power) => {
+            match rational(&inner_power).map(|inner_power| inner_power.checked_mul(power)) {
+                Some(Ok(power)) => simplify_power(*inner, rational_expr(power)),
+                _ => op(
+                    Expr::Op(inner, Opcode::Pow, inner_power),
+                    Opcode::Pow,
+                    exponent,
+                ),
+            }
+        }
+        base => op(base, Opcode::Pow, exponent),
+    }
+}
+
+fn power_expr(base: &Expr, exponent: Rational) -> Expr {
+    match exponent == one() {
+        true => base.clone(),
+        false => op(base.clone(), Opcode::Pow, rational_expr(exponent)),
+    }
+}
+
+fn product(factors: Vec<Expr>) -> Option<Expr> {
+    factors
+        .into_iter()
+        .reduce(|product, factor| op(product, Opcode::Mul, factor))
+}
+
+fn is_sum(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Op(_, Opcode::Add | Opcode::Sub, _) | Expr::UnaryOp(UnaryOpcode::Neg, _)
+    )
+}
+
+/// Есть ли в выражении литерал вектора или матрицы.
+fn contains_matrix(expr: &Expr) -> bool {
+    match expr {
+        Expr::Matrix(_) | Expr::Value(Value::Matrix(_)) => true,
+        Expr::Op(left, _, right) => contains_matrix(left) || contains_matrix(right),
+        Expr::UnaryOp(_, value) | Expr::Spanned(_, value) => contains_matrix(value),
+        Expr::Call(_, args) => args.iter().any(|arg| contains_matrix(arg)),
+        _ => false,
+    }
+}
+
+/// Точное числовое значение выражения: `2`, `-1 / 3`.
+fn rational(expr: &Expr) -> Option<Rational> {
+    match expr {
+        Expr::Number(number) | Expr::Value(Value::Real(number)) => Some(Rational::from(*number)),
+        Expr::Value(Value::Rational(number)) => Some(*number),
+        Expr::UnaryOp(UnaryOpcode::Neg, value) => rational(value).map(|number| -number),
+        Expr::Op(numer, Opcode::Div, denom) => match (numer.unspanned(), denom.unspanned()) {
+            (Expr::Number(numer), Expr::Number(denom)) => Rational::from(*numer)
+                .checked_div(Rational::from(*denom))
+                .ok(),
+            _ => None,
+        },
+        Expr::Spanned(_, expr) => rational(expr),
+        _ => None,
+    }
+}
+
+/// Записывает дробь числом, если она конечная десятичная, и частным `1 / 3` иначе.
+fn rational_expr(number: Rational) -> Expr {
+    match exact_decimal(number) {
+        Some(number) => Expr::Number(number),
+        None => op(
+            Expr::Number(number.numer()),
+            Opcode::Div,
+            Expr::Number(number.denom()),
+        ),
+    }
+}
+
+fn exact_decimal(number: Rational) -> Option<Decimal> {
+    let decimal = number.to_decimal();
+    (Rational::from(decimal) == number).then_some(decimal)
+}
+
+fn one() -> Rational {
+    Rational::from(Decimal::ONE)
+}
+
+fn op(left: Expr, op: Opcode, right: Expr) -> Expr {
+    Expr::Op(Box::new(left), op, Box::new(right))
+}
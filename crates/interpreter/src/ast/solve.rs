@@ -0,0 +1,338 @@
+//! Решение уравнений: `solve(2x + y = 3; x - y = 0; x; y)`.
+//!
+//! Значение уравнения - невязка `левая часть - правая часть`. Невязка линейного уравнения в
+//! точке, где одна неизвестная равна 1, а остальные 0, отличается от невязки в нуле ровно на
+//! коэффициент при этой неизвестной. Полученная система решается точным исключением
+//! `Matrix::solve`, а корни записываются в переменные. В режиме `Decimal` невязки считаются
+//! точными дробями, поэтому `solve(x / 3 = 1; x)` даёт ровно `3`.
+//!
+//! С начальным приближением `solve(x^3 - 2x = 5; x; 2)` корень одного уравнения ищется
+//! численно методом секущих. Как только невязка меняет знак, корень зажат в отрезке, и шаг,
+//! выходящий за него, заменяется делением отрезка пополам. Количество шагов и точность
+//! задаются `Config::max_iterations` и `Config::tolerance`. Невязка, которая меньше
+//! наименьшего числа `Decimal`, не считается нулём: у `expe(x) = 0` корня нет.
+
+use rust_decimal::Decimal;
+
+use super::{
+    expr::{Evaluatable, Expr},
+    numeric,
+    opcode::{Opcode, UnaryOpcode},
+    operation::FactoryOp,
+};
+use crate::{
+    config::NumberMode, errors::CalcError, interpreter::Interpreter, matrix::Matrix, symbolic,
+    value::Value,
+};
+
+/// Допустимое относительное расхождение невязки с линейной моделью.
+/// Оно покрывает округление `f64` и `Decimal`, но не нелинейные слагаемые.
+const LINEARITY_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 9);
+
+/// Первый шаг метода секущих относительно начального приближения.
+const INITIAL_STEP: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// Решает уравнения из `args` и записывает корни в переменные. Возвращает корень, если
+/// неизвестная одна, и вектор корней в порядке неизвестных, если их несколько.
+pub fn solve(interpreter: &mut Interpreter, args: &[Box<Expr>]) -> Result<Value, CalcError> {
+    let (equations, unknowns, guess) = split_args(args)?;
+    for name in &unknowns {
+        interpreter.check_variable_name(name)?;
+    }
+    check_unknowns(interpreter, equations, &unknowns)?;
+
+    if let Some(guess) = guess {
+        let root = find_root(interpreter, &equations[0], &unknowns[0], guess)?;
+        interpreter.assign(&unknowns[0], root.clone())?;
+        return Ok(root);
+    }
+
+    let mode = interpreter.config.number_mode;
+    if matches!(mode, NumberMode::Decimal) {
+        interpreter.config.number_mode = NumberMode::Rational;
+    }
+    let solution = solve_linear(interpreter, equations, &unknowns);
+    interpreter.config.number_mode = mode;
+    let mut solution = solution?;
+    if matches!(mode, NumberMode::Decimal) {
+        solution = solution.map(|root| Ok(root.clone().inexact()))?;
+    }
+
+    for (name, root) in unknowns.iter().zip(solution.elements()) {
+        interpreter.assign(name, root.clone())?;
+    }
+    match solution.elements() {
+        [root] => Ok(root.clone()),
+        _ => Ok(Value::Matrix(solution)),
+    }
+}
+
+/// Корни линейной системы в порядке неизвестных.
+fn solve_linear(
+    interpreter: &mut Interpreter, equations: &[Box<Expr>], unknowns: &[String],
+) -> Result<Matrix, CalcError> {
+    let origin = vec![interpreter.config.literal(Decimal::ZERO); unknowns.len()];
+    let constants = residuals(interpreter, equations, unknowns, &origin)?;
+
+    let mut columns = Vec::with_capacity(unknowns.len());
+    for i in 0..unknowns.len() {
+        let mut point = origin.clone();
+        point[i] = interpreter.config.literal(Decimal::ONE);
+        let column = residuals(interpreter, equations, unknowns, &point)?
+            .into_iter()
+            .zip(&constants)
+            .map(|(residual, constant)| FactoryOp::match_(Opcode::Sub, residual, constant.clone()))
+            .collect::<Result<Vec<Value>, CalcError>>()?;
+        columns.push(column);
+    }
+    check_linear(interpreter, equations, unknowns, &constants, &columns)?;
+
+    let coefficients = Matrix::from_rows(
+        (0..equations.len())
+            .map(|row| columns.iter().map(|column| column[row].clone()).collect())
+            .collect(),
+    )?;
+    let rhs = constants
+        .into_iter()
+        .map(|constant| FactoryOp::match_unary(UnaryOpcode::Neg, constant))
+        .collect::<Result<Vec<Value>, CalcError>>()?;
+    coefficients.solve(&Matrix::vector(rhs)?)
+}
+
+/// Уравнения, имена неизвестных и начальное приближение корня.
+type SolveArgs<'a> = (&'a [Box<Expr>], Vec<String>, Option<&'a Expr>);
+
+/// Делит аргументы `solve` на уравнения, имена неизвестных, которые идут после них, и
+/// начальное приближение корня в конце: `solve(x^3 = 5; x; 2)`.
+fn split_args(args: &[Box<Expr>]) -> Result<SolveArgs<'_>, CalcError> {
+    let count = args
+        .iter()
+        .take_while(|arg| matches!(arg.unspanned(), Expr::Equation(..)))
+        .count();
+    let (equations, rest) = args.split_at(count);
+    let (rest, guess) = match rest.split_last() {
+        Some((last, names))
+            if !matches!(last.unspanned(), Expr::Variable(_) | Expr::Equation(..)) =>
+        {
+            (names, Some(&**last))
+        }
+        _ => (rest, None),
+    };
+
+    let unknowns = rest
+        .iter()
+        .map(|arg| match arg.unspanned() {
+            Expr::Variable(name) => Ok(name.clone()),
+            _ => Err(CalcError::InvalidSolveArguments),
+        })
+        .collect::<Result<Vec<String>, CalcError>>()?;
+
+    let repeated = (0..unknowns.len()).any(|i| unknowns[..i].contains(&unknowns[i]));
+    let numeric = guess.is_some() && (equations.len() > 1 || unknowns.len() > 1);
+    if equations.is_empty() || unknowns.is_empty() || repeated || numeric {
+        return Err(CalcError::InvalidSolveArguments);
+    }
+    Ok((equations, unknowns, guess))
+}
+
+/// Возвращает `CalcError::UnknownNotInEquations`, если одной из неизвестных нет в уравнениях:
+/// `solve(x^2 = 2; y; 1)`. Функции и выражения из переменных раскрываются. Если раскрыть
+/// уравнения не удалось, ошибку сообщит их вычисление.
+fn check_unknowns(
+    interpreter: &Interpreter, equations: &[Box<Expr>], unknowns: &[String],
+) -> Result<(), CalcError> {
+    let mut names = Vec::new();
+    for equation in equations {
+        let sides = match equation.unspanned() {
+            Expr::Equation(left, right) => vec![left, right],
+            _ => vec![equation],
+        };
+        for side in sides {
+            match symbolic::expand(interpreter, side, None, 0) {
+                Ok(side) => names.extend(symbolic::variables(&side)),
+                Err(_) => return Ok(()),
+            }
+        }
+    }
+    match unknowns.iter().find(|name| !names.contains(name)) {
+        Some(name) => Err(CalcError::UnknownNotInEquations(name.clone())),
+        None => Ok(()),
+    }
+}
+
+/// Ищет корень уравнения с неизвестной `name` численно, начиная с `guess`.
+fn find_root(
+    interpreter: &mut Interpreter, equation: &Expr, name: &str, guess: &Expr,
+) -> Result<Value, CalcError> {
+    let guess = guess.evaluate(interpreter)?.real("solve")?;
+    numeric::in_decimal(interpreter, |interpreter| {
+        secant(interpreter, equation, name, guess)
+    })
+}
+
+/// Метод секущих, который после смены знака невязки не выходит из отрезка с корнем.
+fn secant(
+    interpreter: &mut Interpreter, equation: &Expr, name: &str, guess: Decimal,
+) -> Result<Decimal, CalcError> {
+    let tolerance = interpreter.config.tolerance;
+    let max_iterations = interpreter.config.max_iterations;
+    // `None` - невязка меньше наименьшего числа `Decimal`: её знак неизвестен.
+    let mut residual = |x: Decimal| match equation
+        .substitute(&[name.to_string()], &[Value::Real(x)])
+        .evaluate(interpreter)
+        .and_then(|value| value.real("solve"))
+    {
+        Ok(value) => Ok(Some(value)),
+        Err(CalcError::Underflow) => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    let Some(mut f0) = residual(guess)? else {
+        return Err(CalcError::NoConvergence(0));
+    };
+    let mut x0 = guess;
+    if f0.is_zero() {
+        return Ok(x0);
+    }
+    let mut x1 = checked(x0.abs().max(Decimal::ONE).checked_mul(INITIAL_STEP))
+        .and_then(|step| checked(x0.checked_add(step)))?;
+    let mut bracket: Option<Bracket> = None;
+
+    for iteration in 1..=max_iterations {
+        // Точка с исчезающей невязкой не может быть корнем: внутри отрезка с корнем поиск
+        // продолжается с его середины, а без отрезка корень не найден.
+        let Some(f1) = residual(x1)? else {
+            match &bracket {
+                Some(bracket) => {
+                    x1 = bracket.middle()?;
+                    continue;
+                }
+                None => return Err(CalcError::NoConvergence(iteration)),
+            }
+        };
+        if f1.is_zero() {
+            return Ok(numeric::round_to_tolerance(x1, tolerance));
+        }
+        bracket = match bracket {
+            Some(bracket) => Some(bracket.narrow(x1, f1)),
+            None if f0.is_sign_negative() != f1.is_sign_negative() => {
+                Some(Bracket::new(x0, f0, x1))
+            }
+            None => None,
+        };
+
+        // x2 = x1 - f1 / slope, slope = (f1 - f0) / (x1 - x0). Наклон считается первым:
+        // произведение двух малых чисел `f1 * (x1 - x0)` теряет знаки `Decimal`.
+        let next = f1
+            .checked_sub(f0)
+            .zip(x1.checked_sub(x0))
+            .and_then(|(rise, run)| rise.checked_div(run))
+            .and_then(|slope| f1.checked_div(slope))
+            .and_then(|shift| x1.checked_sub(shift));
+        let next = match (next, &bracket) {
+            (Some(next), Some(bracket)) if bracket.contains(next) => next,
+            (_, Some(bracket)) => bracket.middle()?,
+            (Some(next), None) => next,
+            (None, None) => break,
+        };
+
+        let step = checked(next.checked_sub(x1))?;
+        if step.abs() <= numeric::precision(next, tolerance) {
+            return Ok(numeric::round_to_tolerance(next, tolerance));
+        }
+        (x0, f0, x1) = (x1, f1, next);
+    }
+    Err(CalcError::NoConvergence(max_iterations))
+}
+
+/// Отрезок, на концах которого невязка имеет разные знаки.
+struct Bracket {
+    negative: Decimal,
+    positive: Decimal,
+}
+
+impl Bracket {
+    /// Отрезок между точками `a` и `b`, если невязка в `a` равна `residual`.
+    fn new(a: Decimal, residual: Decimal, b: Decimal) -> Self {
+        match residual.is_sign_negative() {
+            true => Bracket {
+                negative: a,
+                positive: b,
+            },
+            false => Bracket {
+                negative: b,
+                positive: a,
+            },
+        }
+    }
+
+    /// Заменяет конец отрезка с тем же знаком невязки на точку `x`.
+    fn narrow(mut self, x: Decimal, residual: Decimal) -> Self {
+        match residual.is_sign_negative() {
+            true => self.negative = x,
+            false => self.positive = x,
+        }
+        self
+    }
+
+    fn contains(&self, x: Decimal) -> bool {
+        let (low, high) = match self.negative < self.positive {
+            true => (self.negative, self.positive),
+            false => (self.positive, self.negative),
+        };
+        low < x && x < high
+    }
+
+    fn middle(&self) -> Result<Decimal, CalcError> {
+        let half = checked(self.positive.checked_sub(self.negative))? / Decimal::TWO;
+        checked(self.negative.checked_add(half))
+    }
+}
+
+fn checked(value: Option<Decimal>) -> Result<Decimal, CalcError> {
+    value.ok_or(CalcError::Overflow)
+}
+
+/// Невязки уравнений, в которые вместо неизвестных подставлены `values`. Ошибка вычисления в
+/// одной из точек, например деление на ноль в `1/x = 1`, означает нелинейное уравнение.
+fn residuals(
+    interpreter: &mut Interpreter, equations: &[Box<Expr>], unknowns: &[String], values: &[Value],
+) -> Result<Vec<Value>, CalcError> {
+    equations
+        .iter()
+        .map(|equation| equation.substitute(unknowns, values).evaluate(interpreter))
+        .collect::<Result<Vec<Value>, CalcError>>()
+        .map_err(|error| match error {
+            CalcError::DivisionByZero
+            | CalcError::ModuloByZero
+            | CalcError::OutOfDomain(_)
+            | CalcError::Overflow => CalcError::NonlinearSystem,
+            error => error,
+        })
+}
+
+/// Сравнивает невязки в точке `(2, 3, 4...)` с теми, что даёт линейная модель
+/// `constants + columns * x`. Расхождение означает нелинейное уравнение: `x^2 = 4`.
+fn check_linear(
+    interpreter: &mut Interpreter, equations: &[Box<Expr>], unknowns: &[String],
+    constants: &[Value], columns: &[Vec<Value>],
+) -> Result<(), CalcError> {
+    let point = (0..unknowns.len())
+        .map(|i| interpreter.config.literal(Decimal::from(i + 2)))
+        .collect::<Vec<Value>>();
+    let actual = residuals(interpreter, equations, unknowns, &point)?;
+
+    for (row, (actual, constant)) in actual.into_iter().zip(constants).enumerate() {
+        let mut expected = constant.clone();
+        for (column, x) in columns.iter().zip(&point) {
+            let term = FactoryOp::match_(Opcode::Mul, column[row].clone(), x.clone())?;
+            expected = FactoryOp::match_(Opcode::Add, expected, term)?;
+        }
+        let scale = actual.magnitude()?.max(Decimal::ONE);
+        let error = FactoryOp::match_(Opcode::Sub, actual, expected)?.magnitude()?;
+        if error > scale * LINEARITY_TOLERANCE {
+            return Err(CalcError::NonlinearSystem);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,852 @@
+//! Десятичные числа произвольной точности для режима `big`.
+//!
+//! Число хранится как целая мантисса из десятичных цифр и порядок: `mantissa * 10^exponent`.
+//! Результат каждой операции округляется до заданного количества значащих цифр,
+//! поэтому точность ограничена только настройкой `Config::digits`. С этой точностью
+//! считаются и `exp`, `ln`, дробные степени и константы `PI` и `E`.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+use crate::{config::Config, errors::CalcError, math::DecimalMath};
+
+/// Наибольший по модулю десятичный порядок. Больший порядок - переполнение, меньший - ноль.
+const MAX_EXPONENT: i64 = 1_000_000_000;
+/// Наибольший сдвиг мантиссы при выравнивании порядков двух чисел.
+const MAX_SHIFT: i64 = 100_000;
+/// Наибольшая целая степень. Для больших показателей результат слишком велик или мал.
+const MAX_POWER: i64 = 1_000_000_000_000;
+/// Количество значащих цифр, которое помещается в `Decimal`.
+const DECIMAL_DIGITS: u32 = 28;
+/// Дополнительные цифры для промежуточных результатов длинных вычислений.
+const GUARD_DIGITS: u32 = 5;
+/// Ограничение на количество итераций метода Ньютона для корня.
+const MAX_SQRT_ITERATIONS: usize = 10_000;
+/// Ограничение на количество итераций метода Галлея для логарифма. Каждая утраивает
+/// количество верных цифр, поэтому хватает и для миллионов цифр.
+const MAX_LN_ITERATIONS: usize = 20;
+/// Наибольший показатель `exp`: `ln(10^MAX_EXPONENT)`, округлённый вверх.
+const MAX_EXP_ARGUMENT: i64 = 2_302_585_093;
+
+/// Число с точностью `digits` значащих цифр.
+///
+/// `digits == 0` означает точное число без округления. Такие числа получаются из `Decimal`,
+/// а результат операции берёт наибольшую точность из двух операндов.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "BigRepr", try_from = "BigRepr")]
+pub struct BigNumber {
+    negative: bool,
+    /// Цифры мантиссы от старшей к младшей, без ведущих и конечных нулей. У нуля пусто.
+    mantissa: Vec<u8>,
+    exponent: i64,
+    digits: u32,
+}
+
+impl BigNumber {
+    /// Переводит `value` в число с точностью `digits`.
+    #[must_use]
+    pub fn from_decimal(value: Decimal, digits: u32) -> Self {
+        let mantissa = value
+            .mantissa()
+            .unsigned_abs()
+            .to_string()
+            .bytes()
+            .map(|digit| digit - b'0')
+            .collect();
+        let exponent = -i64::from(value.scale());
+        BigNumber::new(value.is_sign_negative(), mantissa, exponent, digits)
+            .expect("Decimal fits into BigNumber")
+    }
+
+    /// Переводит `value` в число с точностью `digits`. Берётся кратчайшая запись `f64`.
+    pub fn from_f64(value: f64, digits: u32) -> Result<Self, CalcError> {
+        format!("{value:e}")
+            .parse::<BigNumber>()
+            .map(|number| number.with_digits(digits))
+    }
+
+    /// Разбирает десятичную запись числа из ввода с точностью `config.digits`.
+    pub fn parse(literal: &str, config: &Config) -> Result<Self, CalcError> {
+        literal
+            .parse::<BigNumber>()
+            .map(|number| number.with_digits(config.digits))
+    }
+
+    /// Возвращает число с новой точностью `digits`.
+    #[must_use]
+    pub fn with_digits(self, digits: u32) -> Self {
+        BigNumber::new(self.negative, self.mantissa, self.exponent, digits)
+            .expect("rounding does not overflow")
+    }
+
+    #[must_use]
+    pub fn digits(&self) -> u32 {
+        self.digits
+    }
+
+    /// Число с `GUARD_DIGITS` дополнительными цифрами точности. Точное число не меняется.
+    #[must_use]
+    pub fn widened(&self) -> Self {
+        match self.digits {
+            0 => self.clone(),
+            digits => self.clone().with_digits(digits + GUARD_DIGITS),
+        }
+    }
+
+    /// Значение, округлённое до точности `Decimal`.
+    /// Слишком большие числа возвращают `CalcError::Overflow`, а слишком маленькие округляются до нуля.
+    pub fn to_decimal(&self) -> Result<Decimal, CalcError> {
+        let mut value = self.clone().rounded(DECIMAL_DIGITS);
+        if value.exponent < -i64::from(DECIMAL_DIGITS) {
+            let keep = value.mantissa.len() as i64 + value.exponent + i64::from(DECIMAL_DIGITS);
+            if keep <= 0 {
+                return Ok(Decimal::ZERO);
+            }
+            value = value.rounded(keep as u32);
+        }
+        if value.is_zero() {
+            return Ok(Decimal::ZERO);
+        }
+        if value.adjusted() >= i64::from(DECIMAL_DIGITS) {
+            return Err(CalcError::Overflow);
+        }
+
+        let mut mantissa = value
+            .mantissa
+            .iter()
+            .fold(0_i128, |acc, digit| acc * 10 + i128::from(*digit));
+        for _ in 0..value.exponent.max(0) {
+            mantissa *= 10;
+        }
+        if value.negative {
+            mantissa = -mantissa;
+        }
+        Ok(Decimal::from_i128_with_scale(
+            mantissa,
+            (-value.exponent).max(0) as u32,
+        ))
+    }
+
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_empty()
+    }
+
+    #[must_use]
+    pub fn is_sign_negative(&self) -> bool {
+        self.negative
+    }
+
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    #[must_use]
+    pub fn abs(&self) -> Self {
+        BigNumber {
+            negative: false,
+            ..self.clone()
+        }
+    }
+
+    #[must_use]
+    pub fn neg(&self) -> Self {
+        BigNumber {
+            negative: !self.negative && !self.is_zero(),
+            ..self.clone()
+        }
+    }
+
+    /// Отбрасывает дробную часть.
+    #[must_use]
+    pub fn trunc(&self) -> Self {
+        if self.exponent >= 0 {
+            return self.clone();
+        }
+        let keep = self.mantissa.len() as i64 + self.exponent;
+        match keep > 0 {
+            true => BigNumber::new(
+                self.negative,
+                self.mantissa[..keep as usize].to_vec(),
+                0,
+                self.digits,
+            )
+            .expect("truncation does not overflow"),
+            false => BigNumber::zero(self.digits),
+        }
+    }
+
+    pub fn checked_add(&self, rhs: &BigNumber) -> Option<Self> {
+        let digits = self.digits.max(rhs.digits);
+        if self.is_zero() || rhs.is_zero() {
+            let value = if self.is_zero() { rhs } else { self };
+            return Some(value.clone().with_digits(digits));
+        }
+
+        // Слагаемое, которое меньше последней значащей цифры другого, не влияет на результат.
+        let (large, small) = match self.adjusted() >= rhs.adjusted() {
+            true => (self, rhs),
+            false => (rhs, self),
+        };
+        if digits > 0 && large.adjusted() - small.adjusted() > i64::from(digits) + 1 {
+            return Some(large.clone().with_digits(digits));
+        }
+
+        let (left, right, exponent) = self.aligned(rhs)?;
+        match (self.negative == rhs.negative, compare_digits(&left, &right)) {
+            (true, _) => BigNumber::new(self.negative, add_digits(&left, &right), exponent, digits),
+            (false, Ordering::Less) => {
+                BigNumber::new(rhs.negative, sub_digits(&right, &left), exponent, digits)
+            }
+            (false, _) => {
+                BigNumber::new(self.negative, sub_digits(&left, &right), exponent, digits)
+            }
+        }
+    }
+
+    pub fn checked_sub(&self, rhs: &BigNumber) -> Option<Self> {
+        self.checked_add(&rhs.neg())
+    }
+
+    pub fn checked_mul(&self, rhs: &BigNumber) -> Option<Self> {
+        BigNumber::new(
+            self.negative != rhs.negative,
+            mul_digits(&self.mantissa, &rhs.mantissa),
+            self.exponent + rhs.exponent,
+            self.digits.max(rhs.digits),
+        )
+    }
+
+    /// Частное, округлённое до точности операндов. Делитель не должен быть нулём.
+    pub fn checked_div(&self, rhs: &BigNumber) -> Option<Self> {
+        let digits = precision(self.digits.max(rhs.digits));
+        // Частное должно получить хотя бы одну цифру сверх точности, чтобы его можно было округлить.
+        let shift =
+            (rhs.mantissa.len() as i64 + i64::from(digits) + 1 - self.mantissa.len() as i64).max(0);
+        let mut dividend = self.mantissa.clone();
+        dividend.resize(dividend.len() + shift as usize, 0);
+        let (quotient, _) = div_digits(&dividend, &rhs.mantissa);
+        BigNumber::new(
+            self.negative != rhs.negative,
+            quotient,
+            self.exponent - rhs.exponent - shift,
+            digits,
+        )
+    }
+
+    /// Остаток со знаком делимого. Делитель не должен быть нулём.
+    pub fn checked_rem(&self, rhs: &BigNumber) -> Option<Self> {
+        let (left, right, exponent) = self.aligned(rhs)?;
+        let (_, remainder) = div_digits(&left, &right);
+        BigNumber::new(
+            self.negative,
+            remainder,
+            exponent,
+            self.digits.max(rhs.digits),
+        )
+    }
+
+    /// Возводит число в степень. Целые степени вычисляются умножениями, а дробные -
+    /// как `exp(exponent * ln(self))`. Основание дробной степени не отрицательно.
+    pub fn pow(&self, exponent: &BigNumber) -> Result<Self, CalcError> {
+        let digits = self.digits.max(exponent.digits);
+        if !exponent.is_integer() && !self.is_zero() {
+            return self.fractional_pow(exponent, precision(digits));
+        }
+        if exponent.is_zero() {
+            return Ok(BigNumber::one(digits));
+        }
+        if self.is_zero() {
+            return match exponent.negative {
+                true => Err(CalcError::DivisionByZero),
+                false => Ok(BigNumber::zero(digits)),
+            };
+        }
+
+        let mut rest = exponent
+            .to_integer()
+            .ok_or(CalcError::Overflow)?
+            .unsigned_abs();
+        let work = digits + GUARD_DIGITS;
+        let (mut result, mut base) = (BigNumber::one(work), self.clone().with_digits(work));
+        while rest > 0 {
+            if rest % 2 == 1 {
+                result = result.checked_mul(&base).ok_or(CalcError::Overflow)?;
+            }
+            rest /= 2;
+            if rest > 0 {
+                base = base.checked_mul(&base).ok_or(CalcError::Overflow)?;
+            }
+        }
+        if exponent.negative {
+            result = BigNumber::one(work)
+                .checked_div(&result)
+                .ok_or(CalcError::Overflow)?;
+        }
+        match result.is_zero() {
+            true => Err(CalcError::Underflow),
+            false => Ok(result.with_digits(digits)),
+        }
+    }
+
+    /// Квадратный корень методом Ньютона. Начальное приближение берётся больше корня,
+    /// поэтому последовательность убывает, пока не перестанет меняться.
+    pub fn sqrt(&self) -> Result<Self, CalcError> {
+        if self.negative {
+            return Err(CalcError::MathError);
+        }
+        if self.is_zero() {
+            return Ok(self.clone());
+        }
+
+        let digits = precision(self.digits);
+        let work = digits + GUARD_DIGITS;
+        let value = self.clone().with_digits(work);
+        let two = BigNumber::from_decimal(Decimal::TWO, work);
+        let mut root = BigNumber::new(false, vec![1], (value.adjusted() + 2) / 2, work)
+            .ok_or(CalcError::Overflow)?;
+        for _ in 0..MAX_SQRT_ITERATIONS {
+            let next = value
+                .checked_div(&root)
+                .and_then(|quotient| root.checked_add(&quotient))
+                .and_then(|sum| sum.checked_div(&two))
+                .ok_or(CalcError::Overflow)?;
+            if next >= root {
+                break;
+            }
+            root = next;
+        }
+        Ok(root.with_digits(digits))
+    }
+
+    /// Экспонента `e^self`. Аргумент делится на `2^n`, пока не станет меньше `10^-3`,
+    /// экспонента от него считается рядом Тейлора и `n` раз возводится в квадрат.
+    pub fn exp(&self) -> Result<Self, CalcError> {
+        let digits = precision(self.digits);
+        if self.is_zero() {
+            return Ok(BigNumber::one(digits));
+        }
+        if self.abs() > BigNumber::from_decimal(Decimal::from(MAX_EXP_ARGUMENT), 0) {
+            return Err(match self.negative {
+                true => CalcError::Underflow,
+                false => CalcError::Overflow,
+            });
+        }
+
+        // Каждое возведение в квадрат удваивает относительную погрешность.
+        let halvings = ((self.adjusted() + 4).max(0) * 10 / 3) as u32;
+        let work = digits + GUARD_DIGITS + halvings / 3 + 1;
+        let mut x = self.clone().with_digits(work);
+        for _ in 0..halvings {
+            x = x.div_small(2);
+        }
+
+        let (mut sum, mut term) = (BigNumber::one(work), BigNumber::one(work));
+        for k in 1.. {
+            term = term
+                .checked_mul(&x)
+                .ok_or(CalcError::Overflow)?
+                .div_small(k);
+            if term.is_zero() || term.adjusted() < sum.adjusted() - i64::from(work) - 1 {
+                break;
+            }
+            sum = sum.checked_add(&term).ok_or(CalcError::Overflow)?;
+        }
+        for _ in 0..halvings {
+            sum = sum.checked_mul(&sum).ok_or(CalcError::Overflow)?;
+        }
+        match sum.is_zero() {
+            true => Err(CalcError::Underflow),
+            false => Ok(sum.with_digits(digits)),
+        }
+    }
+
+    /// Натуральный логарифм положительного числа. Число записывается как `m * 10^k`,
+    /// где `1 <= m < 10`, и `ln(self) = ln(m) + k * ln(10)`. Числа от `0.1` до `1`
+    /// не раскладываются, чтобы не терять цифры на вычитании близких значений.
+    pub fn ln(&self) -> Result<Self, CalcError> {
+        if self.negative || self.is_zero() {
+            return Err(CalcError::MathError);
+        }
+        let digits = precision(self.digits);
+        let work = digits + GUARD_DIGITS;
+        let power = match self.adjusted() {
+            -1 => 0,
+            adjusted => adjusted,
+        };
+        let mantissa = BigNumber {
+            exponent: self.exponent - power,
+            ..self.clone()
+        }
+        .with_digits(work);
+
+        let mut result = ln_reduced(&mantissa, work)?;
+        if power != 0 {
+            let ln_ten = ln_reduced(&BigNumber::from_decimal(Decimal::TEN, work), work)?;
+            let power = BigNumber::from_decimal(Decimal::from(power), work);
+            result = ln_ten
+                .checked_mul(&power)
+                .and_then(|shift| result.checked_add(&shift))
+                .ok_or(CalcError::Overflow)?;
+        }
+        Ok(result.with_digits(digits))
+    }
+
+    /// Логарифм по основанию `base`. Оба логарифма считаются с запасом точности,
+    /// поэтому `log(1000; 10)` равно ровно `3`.
+    pub fn log(&self, base: &BigNumber) -> Result<Self, CalcError> {
+        let digits = precision(self.digits.max(base.digits));
+        let work = digits + GUARD_DIGITS;
+        let ln_base = base.clone().with_digits(work).ln()?;
+        if ln_base.is_zero() {
+            return Err(CalcError::DivisionByZero);
+        }
+        self.clone()
+            .with_digits(work)
+            .ln()?
+            .checked_div(&ln_base)
+            .map(|result| result.with_digits(digits))
+            .ok_or(CalcError::Overflow)
+    }
+
+    /// Число пи по формуле Мэчина: `PI = 16 * atan(1/5) - 4 * atan(1/239)`.
+    #[must_use]
+    pub fn pi(digits: u32) -> Self {
+        let work = digits + GUARD_DIGITS;
+        let term = |k: u64, factor: i64| {
+            atan_inverse(k, work)
+                .checked_mul(&BigNumber::from_decimal(Decimal::from(factor), work))
+                .expect("the series does not overflow")
+        };
+        term(5, 16)
+            .checked_sub(&term(239, 4))
+            .expect("the series does not overflow")
+            .with_digits(digits)
+    }
+
+    /// Число Эйлера `e`.
+    #[must_use]
+    pub fn e(digits: u32) -> Self {
+        BigNumber::one(digits).exp().expect("e^1 does not overflow")
+    }
+
+    /// Дробная степень положительного числа. Погрешность логарифма умножается на
+    /// показатель, поэтому для больших `exponent * ln(self)` берутся дополнительные цифры.
+    fn fractional_pow(&self, exponent: &BigNumber, digits: u32) -> Result<Self, CalcError> {
+        if self.negative {
+            return Err(CalcError::MathError);
+        }
+        let power = |work: u32| {
+            self.clone()
+                .with_digits(work)
+                .ln()?
+                .checked_mul(&exponent.clone().with_digits(work))
+                .ok_or(CalcError::Overflow)
+        };
+        let work = digits + GUARD_DIGITS;
+        let mut product = power(work)?;
+        if product.adjusted() > 0 {
+            product = power(work + product.adjusted() as u32)?;
+        }
+        product.exp().map(|result| result.with_digits(digits))
+    }
+
+    /// Частное от деления на небольшое целое число. В отличие от `checked_div`,
+    /// занимает время, линейное по количеству цифр.
+    fn div_small(&self, divisor: u64) -> Self {
+        let digits = precision(self.digits);
+        // Цифр частного должно хватить на точность и ещё одну для округления.
+        let extra = digits as usize + 2 + divisor.ilog10() as usize;
+        let mut quotient = Vec::with_capacity(self.mantissa.len() + extra);
+        let mut remainder = 0_u128;
+        for digit in self
+            .mantissa
+            .iter()
+            .copied()
+            .chain(std::iter::repeat_n(0, extra))
+        {
+            remainder = remainder * 10 + u128::from(digit);
+            quotient.push((remainder / u128::from(divisor)) as u8);
+            remainder %= u128::from(divisor);
+        }
+        BigNumber::new(
+            self.negative,
+            quotient,
+            self.exponent - extra as i64,
+            digits,
+        )
+        .expect("division by an integer does not overflow")
+    }
+
+    /// Создаёт число из цифр мантиссы и округляет его до `digits` значащих цифр.
+    /// Возвращает `None`, если порядок результата слишком велик.
+    fn new(negative: bool, mantissa: Vec<u8>, exponent: i64, digits: u32) -> Option<Self> {
+        let start = mantissa
+            .iter()
+            .position(|digit| *digit != 0)
+            .unwrap_or(mantissa.len());
+        let number = BigNumber {
+            negative,
+            mantissa: mantissa[start..].to_vec(),
+            exponent,
+            digits,
+        }
+        .rounded(digits);
+
+        match number.adjusted() {
+            _ if number.is_zero() => Some(number),
+            adjusted if adjusted > MAX_EXPONENT => None,
+            adjusted if adjusted < -MAX_EXPONENT => Some(BigNumber::zero(digits)),
+            _ => Some(number),
+        }
+    }
+
+    /// Округляет мантиссу до `digits` значащих цифр, половина - от нуля,
+    /// и убирает конечные нули.
+    fn rounded(mut self, digits: u32) -> Self {
+        let digits = digits as usize;
+        if digits > 0 && self.mantissa.len() > digits {
+            let round_up = self.mantissa[digits] >= 5;
+            self.exponent += (self.mantissa.len() - digits) as i64;
+            self.mantissa.truncate(digits);
+            if round_up {
+                self.mantissa = add_digits(&self.mantissa, &[1]);
+            }
+        }
+        while self.mantissa.last() == Some(&0) {
+            self.mantissa.pop();
+            self.exponent += 1;
+        }
+        if self.mantissa.is_empty() {
+            self.negative = false;
+            self.exponent = 0;
+        }
+        self
+    }
+
+    fn zero(digits: u32) -> Self {
+        BigNumber {
+            negative: false,
+            mantissa: Vec::new(),
+            exponent: 0,
+            digits,
+        }
+    }
+
+    fn one(digits: u32) -> Self {
+        BigNumber {
+            negative: false,
+            mantissa: vec![1],
+            exponent: 0,
+            digits,
+        }
+    }
+
+    /// Порядок старшей цифры: у `123.4` это `2`.
+    fn adjusted(&self) -> i64 {
+        self.exponent + self.mantissa.len() as i64 - 1
+    }
+
+    /// Целое значение числа, если оно не больше `MAX_POWER`.
+    fn to_integer(&self) -> Option<i64> {
+        if self.adjusted() >= 13 {
+            return None;
+        }
+        let mut value = self
+            .mantissa
+            .iter()
+            .fold(0_i64, |acc, digit| acc * 10 + i64::from(*digit));
+        for _ in 0..self.exponent {
+            value *= 10;
+        }
+        match value <= MAX_POWER {
+            true => Some(if self.negative { -value } else { value }),
+            false => None,
+        }
+    }
+
+    /// Мантиссы обоих чисел, приведённые к общему, меньшему порядку.
+    fn aligned(&self, rhs: &BigNumber) -> Option<(Vec<u8>, Vec<u8>, i64)> {
+        let exponent = self.exponent.min(rhs.exponent);
+        let shift = |number: &BigNumber| {
+            let shift = number.exponent - exponent;
+            if shift > MAX_SHIFT && !number.is_zero() {
+                return None;
+            }
+            let mut mantissa = number.mantissa.clone();
+            if !mantissa.is_empty() {
+                mantissa.resize(mantissa.len() + shift as usize, 0);
+            }
+            Some(mantissa)
+        };
+        Some((shift(self)?, shift(rhs)?, exponent))
+    }
+}
+
+impl PartialEq for BigNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.negative == other.negative
+            && self.exponent == other.exponent
+            && self.mantissa == other.mantissa
+    }
+}
+
+impl PartialOrd for BigNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let magnitude = match (self.is_zero(), other.is_zero()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self
+                .adjusted()
+                .cmp(&other.adjusted())
+                .then_with(|| self.mantissa.cmp(&other.mantissa)),
+        };
+        Some(match (self.negative, other.negative) {
+            (false, false) => magnitude,
+            (true, true) => magnitude.reverse(),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        })
+    }
+}
+
+/// Разбирает `123.45`, `123,45`, `.5` и научную запись `1.2e-30`.
+impl FromStr for BigNumber {
+    type Err = CalcError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (negative, input) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+        let (number, exponent) = match input.split_once(['e', 'E']) {
+            Some((number, exponent)) => (
+                number,
+                exponent
+                    .parse::<i64>()
+                    .map_err(|_| CalcError::InputTooBig)?,
+            ),
+            None => (input, 0),
+        };
+        let (integer, fraction) = number.split_once(['.', ',']).unwrap_or((number, ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(CalcError::MathError);
+        }
+
+        let mut mantissa = Vec::with_capacity(integer.len() + fraction.len());
+        for digit in integer.bytes().chain(fraction.bytes()) {
+            if !digit.is_ascii_digit() {
+                return Err(CalcError::MathError);
+            }
+            mantissa.push(digit - b'0');
+        }
+        BigNumber::new(negative, mantissa, exponent - fraction.len() as i64, 0)
+            .ok_or(CalcError::InputTooBig)
+    }
+}
+
+/// Обычная запись, пока число не длиннее своей точности, иначе научная: `1.5e+100`.
+impl Display for BigNumber {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        if self.is_zero() {
+            return write!(fmt, "0");
+        }
+        if self.negative {
+            write!(fmt, "-")?;
+        }
+
+        let digits: String = self
+            .mantissa
+            .iter()
+            .map(|digit| char::from(b'0' + digit))
+            .collect();
+        let adjusted = self.adjusted();
+        let plain_limit = i64::from(self.digits.max(DECIMAL_DIGITS));
+        if adjusted >= plain_limit || adjusted < -i64::from(DECIMAL_DIGITS) {
+            let (first, rest) = digits.split_at(1);
+            let point = if rest.is_empty() { "" } else { "." };
+            let sign = if adjusted < 0 { "-" } else { "+" };
+            return write!(fmt, "{first}{point}{rest}e{sign}{}", adjusted.abs());
+        }
+
+        let point = self.mantissa.len() as i64 + self.exponent;
+        match point {
+            _ if self.exponent >= 0 => {
+                write!(fmt, "{digits}{}", "0".repeat(self.exponent as usize))
+            }
+            point if point > 0 => {
+                let (integer, fraction) = digits.split_at(point as usize);
+                write!(fmt, "{integer}.{fraction}")
+            }
+            point => write!(
+                fmt,
+                "0.{}{digits}",
+                "0".repeat(point.unsigned_abs() as usize)
+            ),
+        }
+    }
+}
+
+/// Число сохраняется строкой вместе с точностью: `{"big": "1e+40", "digits": 50}`.
+#[derive(Serialize, Deserialize)]
+struct BigRepr {
+    big: String,
+    digits: u32,
+}
+
+impl From<BigNumber> for BigRepr {
+    fn from(number: BigNumber) -> Self {
+        BigRepr {
+            big: number.to_string(),
+            digits: number.digits,
+        }
+    }
+}
+
+impl TryFrom<BigRepr> for BigNumber {
+    type Error = CalcError;
+
+    fn try_from(repr: BigRepr) -> Result<Self, Self::Error> {
+        repr.big
+            .parse::<BigNumber>()
+            .map(|number| number.with_digits(repr.digits))
+    }
+}
+
+/// Точность результата: у точных чисел её нет, и берётся точность по умолчанию.
+fn precision(digits: u32) -> u32 {
+    match digits {
+        0 => Config::DEFAULT_DIGITS,
+        digits => digits,
+    }
+}
+
+/// Логарифм числа, которое помещается в `Decimal`, уточнённый методом Галлея:
+/// `y = y + 2 * (x - e^y) / (x + e^y)`. Начальное приближение - логарифм в `Decimal`.
+fn ln_reduced(x: &BigNumber, work: u32) -> Result<BigNumber, CalcError> {
+    let two = BigNumber::from_decimal(Decimal::TWO, work);
+    let mut y = BigNumber::from_decimal(x.to_decimal()?.ln()?, work);
+    for _ in 0..MAX_LN_ITERATIONS {
+        let exp = y.exp()?;
+        let step = x
+            .checked_sub(&exp)
+            .and_then(|difference| difference.checked_mul(&two))
+            .zip(x.checked_add(&exp))
+            .and_then(|(numer, denom)| numer.checked_div(&denom))
+            .ok_or(CalcError::Overflow)?;
+        if step.is_zero() || step.adjusted() < y.adjusted() - i64::from(work) {
+            break;
+        }
+        y = y.checked_add(&step).ok_or(CalcError::Overflow)?;
+    }
+    Ok(y)
+}
+
+/// `atan(1/k)` рядом `1/k - 1/(3k^3) + 1/(5k^5) - ...`.
+fn atan_inverse(k: u64, work: u32) -> BigNumber {
+    let mut power = BigNumber::one(work).div_small(k);
+    let mut sum = power.clone();
+    for n in 1_u64.. {
+        power = power.div_small(k * k);
+        let term = power.div_small(2 * n + 1);
+        if term.is_zero() || term.adjusted() < sum.adjusted() - i64::from(work) - 1 {
+            break;
+        }
+        sum = match n % 2 {
+            1 => sum.checked_sub(&term),
+            _ => sum.checked_add(&term),
+        }
+        .expect("the series does not overflow");
+    }
+    sum
+}
+
+/// Сравнивает два целых числа из цифр без ведущих нулей.
+fn compare_digits(left: &[u8], right: &[u8]) -> Ordering {
+    left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+}
+
+fn add_digits(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let (mut left, mut right) = (left.iter().rev(), right.iter().rev());
+    let mut carry = 0;
+    loop {
+        let (l, r) = (left.next(), right.next());
+        if l.is_none() && r.is_none() {
+            break;
+        }
+        let sum = l.unwrap_or(&0) + r.unwrap_or(&0) + carry;
+        result.push(sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        result.push(carry);
+    }
+    result.reverse();
+    result
+}
+
+/// Разность `left - right`, где `left >= right`, без ведущих нулей.
+fn sub_digits(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(left.len());
+    let mut right = right.iter().rev();
+    let mut borrow = 0;
+    for digit in left.iter().rev() {
+        let mut difference = i16::from(*digit) - i16::from(*right.next().unwrap_or(&0)) - borrow;
+        borrow = i16::from(difference < 0);
+        difference += borrow * 10;
+        result.push(difference as u8);
+    }
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+    result.reverse();
+    result
+}
+
+fn mul_digits(left: &[u8], right: &[u8]) -> Vec<u8> {
+    if left.is_empty() || right.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0_u64; left.len() + right.len()];
+    for (i, l) in left.iter().rev().enumerate() {
+        for (j, r) in right.iter().rev().enumerate() {
+            result[i + j] += u64::from(*l) * u64::from(*r);
+        }
+        // Переносим сразу, чтобы сумма в разряде не переполнилась на длинных мантиссах.
+        let mut carry = 0;
+        for digit in result.iter_mut().skip(i) {
+            *digit += carry;
+            carry = *digit / 10;
+            *digit %= 10;
+        }
+    }
+    result.iter().rev().map(|digit| *digit as u8).collect()
+}
+
+/// Деление столбиком: частное и остаток, оба без ведущих нулей.
+fn div_digits(dividend: &[u8], divisor: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut quotient = Vec::with_capacity(dividend.len());
+    let mut remainder: Vec<u8> = Vec::with_capacity(divisor.len() + 1);
+    for digit in dividend {
+        if !remainder.is_empty() || *digit != 0 {
+            remainder.push(*digit);
+        }
+        let mut count = 0;
+        while compare_digits(&remainder, divisor) != Ordering::Less {
+            remainder = sub_digits(&remainder, divisor);
+            count += 1;
+        }
+        quotient.push(count);
+    }
+    let start = quotient
+        .iter()
+        .position(|digit| *digit != 0)
+        .unwrap_or(quotient.len());
+    (quotient.split_off(start), remainder)
+}
@@ -0,0 +1,233 @@
+//! Комплексные числа с частями типа `Decimal`.
+//!
+//! Все операции проверяют переполнение так же, как и операции над вещественными числами.
+//! Трансцендентные функции считаются через вещественные функции модуля `math`.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::ops::Neg;
+
+use crate::{
+    config::AngleUnit,
+    errors::CalcError,
+    math::{self, DecimalMath},
+};
+
+/// Количество значащих цифр в результатах трансцендентных функций.
+/// Меньше, чем у вещественных функций: аргумент числа вносит свою погрешность.
+const SIGNIFICANT_DIGITS: i64 = 24;
+
+/// Комплексное число `re + im * i`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Complex {
+    pub re: Decimal,
+    pub im: Decimal,
+}
+
+impl Complex {
+    /// Мнимая единица.
+    pub const I: Complex = Complex {
+        re: Decimal::ZERO,
+        im: Decimal::ONE,
+    };
+
+    #[must_use]
+    pub fn new(re: Decimal, im: Decimal) -> Self {
+        Complex { re, im }
+    }
+
+    /// Создаёт число с модулем `modulus` и аргументом `angle` в единицах `unit`.
+    pub fn from_polar(
+        modulus: Decimal, angle: Decimal, unit: AngleUnit,
+    ) -> Result<Self, CalcError> {
+        let re = mul(modulus, angle.cos(unit)?)?;
+        let im = mul(modulus, angle.sin(unit)?)?;
+        Ok(Complex::new(re, im).round())
+    }
+
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    /// Комплексно сопряжённое число.
+    #[must_use]
+    pub fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    pub fn checked_add(self, rhs: Complex) -> Result<Self, CalcError> {
+        Ok(Complex::new(add(self.re, rhs.re)?, add(self.im, rhs.im)?))
+    }
+
+    pub fn checked_sub(self, rhs: Complex) -> Result<Self, CalcError> {
+        Ok(Complex::new(sub(self.re, rhs.re)?, sub(self.im, rhs.im)?))
+    }
+
+    pub fn checked_mul(self, rhs: Complex) -> Result<Self, CalcError> {
+        let re = sub(mul(self.re, rhs.re)?, mul(self.im, rhs.im)?)?;
+        let im = add(mul(self.re, rhs.im)?, mul(self.im, rhs.re)?)?;
+        Ok(Complex::new(re, im))
+    }
+
+    /// Деление по методу Смита: делитель не возводится в квадрат, поэтому не переполняется.
+    pub fn checked_div(self, rhs: Complex) -> Result<Self, CalcError> {
+        if rhs.is_zero() {
+            return Err(CalcError::DivisionByZero);
+        }
+
+        let (a, b, c, d) = (self.re, self.im, rhs.re, rhs.im);
+        let (re, im, denominator) = if c.abs() >= d.abs() {
+            let ratio = div(d, c)?;
+            let denominator = add(c, mul(d, ratio)?)?;
+            (
+                add(a, mul(b, ratio)?)?,
+                sub(b, mul(a, ratio)?)?,
+                denominator,
+            )
+        } else {
+            let ratio = div(c, d)?;
+            let denominator = add(mul(c, ratio)?, d)?;
+            (
+                add(mul(a, ratio)?, b)?,
+                sub(mul(b, ratio)?, a)?,
+                denominator,
+            )
+        };
+        Ok(Complex::new(div(re, denominator)?, div(im, denominator)?))
+    }
+
+    /// Модуль числа. Сумма квадратов частей считается с запасом точности и не переполняется.
+    pub fn abs(self) -> Result<Decimal, CalcError> {
+        math::hypot(self.re, self.im)
+    }
+
+    /// Аргумент числа в радианах, от `-PI` до `PI`.
+    pub fn arg(self) -> Result<Decimal, CalcError> {
+        if self.is_zero() {
+            return Err(CalcError::MathError);
+        }
+        self.im.atan2(self.re, AngleUnit::Radians)
+    }
+
+    /// `e` в степени `self`.
+    pub fn exp(self) -> Result<Self, CalcError> {
+        Complex::from_polar(self.re.exp()?, self.im, AngleUnit::Radians)
+    }
+
+    /// Главное значение натурального логарифма.
+    pub fn ln(self) -> Result<Self, CalcError> {
+        if self.is_zero() {
+            return Err(CalcError::MathError);
+        }
+        Ok(Complex::new(self.abs()?.ln()?, self.arg()?))
+    }
+
+    /// Главное значение степени. Целые степени считаются умножением и поэтому точны: `i^2` равно `-1`.
+    pub fn powc(self, exponent: Complex) -> Result<Self, CalcError> {
+        if exponent.im.is_zero() && exponent.re.fract().is_zero() {
+            return self.powi(exponent.re);
+        }
+        if self.is_zero() {
+            return match exponent.re.is_sign_positive() && !exponent.re.is_zero() {
+                true => Ok(self),
+                false => Err(CalcError::DivisionByZero),
+            };
+        }
+        exponent.checked_mul(self.ln()?)?.exp()
+    }
+
+    /// Главное значение квадратного корня.
+    /// Корень из отрицательного числа и из точных квадратов считается без потери точности: `sqrt(-4)` равно `2i`.
+    pub fn sqrt(self) -> Result<Self, CalcError> {
+        if self.im.is_zero() {
+            let root = self.re.abs().nth_root(Decimal::TWO)?;
+            return match self.re.is_sign_negative() {
+                true => Ok(Complex::new(Decimal::ZERO, root)),
+                false => Ok(Complex::new(root, Decimal::ZERO)),
+            };
+        }
+
+        let modulus = self.abs()?;
+        let re = (add(modulus, self.re)? / Decimal::TWO).nth_root(Decimal::TWO)?;
+        let mut im = (sub(modulus, self.re)? / Decimal::TWO).nth_root(Decimal::TWO)?;
+        im.set_sign_negative(self.im.is_sign_negative());
+        Ok(Complex::new(re, im))
+    }
+
+    fn powi(self, exponent: Decimal) -> Result<Self, CalcError> {
+        let n = exponent.to_i64().ok_or(CalcError::Overflow)?;
+        if self.is_zero() && n < 0 {
+            return Err(CalcError::DivisionByZero);
+        }
+
+        let (mut result, mut base, mut rest) = (
+            Complex::new(Decimal::ONE, Decimal::ZERO),
+            self,
+            n.unsigned_abs(),
+        );
+        while rest > 0 {
+            if rest % 2 == 1 {
+                result = result.checked_mul(base)?;
+            }
+            rest /= 2;
+            if rest > 0 {
+                base = base.checked_mul(base)?;
+            }
+        }
+
+        match n < 0 {
+            true => Complex::new(Decimal::ONE, Decimal::ZERO).checked_div(result),
+            false => Ok(result),
+        }
+    }
+
+    /// Округляет обе части результата трансцендентной функции до одного и того же разряда,
+    /// оставляя у большей из них `SIGNIFICANT_DIGITS` значащих цифр.
+    /// Так погрешность числа пи не превращается в мнимую часть: `(-4)^0.5` равно ровно `2i`.
+    fn round(self) -> Self {
+        let max = self.re.abs().max(self.im.abs()).normalize();
+        if max.is_zero() {
+            return self;
+        }
+
+        let digits = max.mantissa().to_string().len() as i64;
+        let exponent = digits - 1 - i64::from(max.scale());
+        let dp = (SIGNIFICANT_DIGITS - 1 - exponent).clamp(0, 28) as u32;
+        Complex::new(
+            self.re.round_dp(dp).normalize(),
+            self.im.round_dp(dp).normalize(),
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl From<Decimal> for Complex {
+    fn from(re: Decimal) -> Self {
+        Complex::new(re, Decimal::ZERO)
+    }
+}
+
+fn add(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_add(right).ok_or(CalcError::Overflow)
+}
+
+fn sub(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_sub(right).ok_or(CalcError::Overflow)
+}
+
+fn mul(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_mul(right).ok_or(CalcError::Overflow)
+}
+
+fn div(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_div(right).ok_or(CalcError::Overflow)
+}
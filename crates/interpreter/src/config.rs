@@ -1,19 +1,176 @@
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+use crate::{
+    big::BigNumber, errors::CalcError, integer::Base, number::Number, rational::Rational,
+    value::Value,
+};
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct Config {
     pub max_size_history: usize,
     pub max_number_variable: usize,
+    #[serde(default)]
+    pub angle_unit: AngleUnit,
+    #[serde(default)]
+    pub number_mode: NumberMode,
+    /// Количество значащих цифр чисел в режиме `NumberMode::Big`.
+    #[serde(default = "Config::default_digits")]
+    pub digits: u32,
+    /// Наибольшая точность режима `NumberMode::Big`: вычисления с большей точностью идут слишком долго.
+    #[serde(default = "Config::default_max_digits")]
+    pub max_digits: u32,
+    /// Система счисления, в которой показываются целые результаты.
+    #[serde(default)]
+    pub output_base: Base,
+    /// Наибольшее количество шагов численного поиска корня в `solve`.
+    #[serde(default = "Config::default_max_iterations")]
+    pub max_iterations: usize,
+    /// Точность численного поиска корня: шаг, после которого поиск останавливается.
+    #[serde(default = "Config::default_tolerance")]
+    pub tolerance: Decimal,
+    /// Наибольшее количество слагаемых `sum` и множителей `prod`.
+    #[serde(default = "Config::default_max_terms")]
+    pub max_terms: usize,
 }
 
 impl Config {
+    /// Точность режима `NumberMode::Big` по умолчанию.
+    pub const DEFAULT_DIGITS: u32 = 50;
+    /// Наибольшая точность режима `NumberMode::Big` по умолчанию.
+    pub const DEFAULT_MAX_DIGITS: u32 = 1000;
+    /// Количество шагов поиска корня по умолчанию.
+    pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+    /// Количество слагаемых `sum` и множителей `prod` по умолчанию.
+    pub const DEFAULT_MAX_TERMS: usize = 100_000;
+    /// Точность поиска корня по умолчанию: `1e-20`.
+    pub const DEFAULT_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 20);
+
     #[must_use]
     pub fn new(max_size_history: usize, max_number_variable: usize) -> Self {
         Config {
             max_size_history,
             max_number_variable,
+            ..Default::default()
         }
     }
+
+    #[must_use]
+    pub fn with_angle_unit(mut self, angle_unit: AngleUnit) -> Self {
+        self.angle_unit = angle_unit;
+        self
+    }
+
+    #[must_use]
+    pub fn with_number_mode(mut self, number_mode: NumberMode) -> Self {
+        self.number_mode = number_mode;
+        self
+    }
+
+    #[must_use]
+    pub fn with_digits(mut self, digits: u32) -> Self {
+        self.digits = digits.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_digits(mut self, max_digits: u32) -> Self {
+        self.max_digits = max_digits.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn with_output_base(mut self, output_base: Base) -> Self {
+        self.output_base = output_base;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: Decimal) -> Self {
+        self.tolerance = tolerance.abs();
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_terms(mut self, max_terms: usize) -> Self {
+        self.max_terms = max_terms;
+        self
+    }
+
+    /// Возвращает `CalcError::TooManyDigits`, если в режиме `NumberMode::Big` точность
+    /// больше `max_digits`.
+    pub fn check_digits(&self) -> Result<(), CalcError> {
+        if self.number_mode == NumberMode::Big && self.digits > self.max_digits {
+            return Err(CalcError::TooManyDigits(self.digits, self.max_digits));
+        }
+        Ok(())
+    }
+
+    /// Значение числа `value` из ввода в текущем режиме.
+    #[must_use]
+    pub fn literal(&self, value: Decimal) -> Value {
+        match self.number_mode {
+            NumberMode::Rational => Value::Rational(Rational::from(value)),
+            _ => self.number(value),
+        }
+    }
+
+    /// Значение числа из ввода, которое не помещается в `Decimal` без потерь. В режиме `big`
+    /// оно читается со всеми цифрами, в режиме `float` - как `f64`, а в остальных
+    /// округляется до `Decimal`.
+    pub fn parse_literal(&self, literal: &str) -> Result<Value, CalcError> {
+        match self.number_mode {
+            NumberMode::Big => BigNumber::parse(literal, self).map(Value::Big),
+            NumberMode::Float => literal
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(Value::Float)
+                .ok_or(CalcError::InputTooBig),
+            _ => Decimal::from_str(literal)
+                .map(|value| self.literal(value))
+                .map_err(|_| CalcError::InputTooBig),
+        }
+    }
+
+    /// Переводит вещественный результат, вычисленный в `Decimal`, в числа текущего режима.
+    /// В рациональном режиме результат остаётся `Decimal`: он уже не точен.
+    #[must_use]
+    pub fn number(&self, value: Decimal) -> Value {
+        match self.number_mode {
+            NumberMode::Decimal | NumberMode::Rational => Value::Real(value),
+            NumberMode::Float => Value::Float(f64::from_decimal(value)),
+            NumberMode::Big => Value::Big(BigNumber::from_decimal(value, self.digits)),
+        }
+    }
+
+    fn default_digits() -> u32 {
+        Config::DEFAULT_DIGITS
+    }
+
+    fn default_max_digits() -> u32 {
+        Config::DEFAULT_MAX_DIGITS
+    }
+
+    fn default_max_iterations() -> usize {
+        Config::DEFAULT_MAX_ITERATIONS
+    }
+
+    fn default_tolerance() -> Decimal {
+        Config::DEFAULT_TOLERANCE
+    }
+
+    fn default_max_terms() -> usize {
+        Config::DEFAULT_MAX_TERMS
+    }
 }
 
 impl Default for Config {
@@ -21,6 +178,123 @@ impl Default for Config {
         Self {
             max_size_history: 50,
             max_number_variable: 50,
+            angle_unit: AngleUnit::default(),
+            number_mode: NumberMode::default(),
+            digits: Config::DEFAULT_DIGITS,
+            max_digits: Config::DEFAULT_MAX_DIGITS,
+            output_base: Base::default(),
+            max_iterations: Config::DEFAULT_MAX_ITERATIONS,
+            tolerance: Config::DEFAULT_TOLERANCE,
+            max_terms: Config::DEFAULT_MAX_TERMS,
+        }
+    }
+}
+
+/// Единица измерения углов, в которой тригонометрические функции принимают аргумент.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+    Gradians,
+}
+
+impl AngleUnit {
+    /// Возвращает единицу измерения по её имени или сокращению: `degrees`, `deg`, `rad`...
+    pub fn from_name(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "degrees" | "deg" => Ok(AngleUnit::Degrees),
+            "radians" | "rad" => Ok(AngleUnit::Radians),
+            "gradians" | "grad" => Ok(AngleUnit::Gradians),
+            _ => Err(CalcError::UnknownAngleUnit(name.to_string())),
+        }
+    }
+
+    /// Переводит угол `value`, заданный в этих единицах, в радианы.
+    pub fn to_radians(self, value: Decimal) -> Result<Decimal, CalcError> {
+        match self {
+            AngleUnit::Radians => Ok(value),
+            _ => value
+                .checked_mul(Decimal::PI)
+                .and_then(|value| value.checked_div(self.half_turn()))
+                .ok_or(CalcError::MathError),
+        }
+    }
+
+    /// Переводит угол `value`, заданный в радианах, в эти единицы.
+    pub fn from_radians(self, value: Decimal) -> Result<Decimal, CalcError> {
+        match self {
+            AngleUnit::Radians => Ok(value),
+            _ => value
+                .checked_mul(self.half_turn())
+                .and_then(|value| value.checked_div(Decimal::PI))
+                .ok_or(CalcError::MathError),
+        }
+    }
+
+    /// Величина полного оборота в этих единицах.
+    #[must_use]
+    pub fn full_turn(self) -> Decimal {
+        self.half_turn() * Decimal::TWO
+    }
+
+    /// Величина развёрнутого угла в этих единицах.
+    fn half_turn(self) -> Decimal {
+        match self {
+            AngleUnit::Degrees => Decimal::from(180),
+            AngleUnit::Radians => Decimal::PI,
+            AngleUnit::Gradians => Decimal::from(200),
+        }
+    }
+}
+
+impl Display for AngleUnit {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match self {
+            AngleUnit::Degrees => write!(fmt, "degrees"),
+            AngleUnit::Radians => write!(fmt, "radians"),
+            AngleUnit::Gradians => write!(fmt, "gradians"),
+        }
+    }
+}
+
+/// Способ хранения чисел из ввода.
+/// В рациональном режиме `+ - * / div mod` над числами дают точные дроби: `1/3 * 3` равно `1`.
+/// В `Decimal` дробь переводится, только когда её передают в функцию.
+/// `Float` считает в `f64`: быстрее, но с ошибками округления двоичных дробей.
+/// `Big` хранит `Config::digits` значащих цифр и не ограничен диапазоном `Decimal`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberMode {
+    #[default]
+    Decimal,
+    Rational,
+    Float,
+    Big,
+}
+
+impl NumberMode {
+    /// Возвращает режим по его имени или сокращению: `decimal`, `dec`, `rational`, `rat`,
+    /// `float`, `f64`, `big`.
+    pub fn from_name(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "decimal" | "dec" => Ok(NumberMode::Decimal),
+            "rational" | "rat" => Ok(NumberMode::Rational),
+            "float" | "f64" => Ok(NumberMode::Float),
+            "big" => Ok(NumberMode::Big),
+            _ => Err(CalcError::UnknownNumberMode(name.to_string())),
+        }
+    }
+}
+
+impl Display for NumberMode {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match self {
+            NumberMode::Decimal => write!(fmt, "decimal"),
+            NumberMode::Rational => write!(fmt, "rational"),
+            NumberMode::Float => write!(fmt, "float"),
+            NumberMode::Big => write!(fmt, "big"),
         }
     }
 }
@@ -1,27 +1,72 @@
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::traits::GetResult;
+use rust_decimal::Decimal;
+
+use crate::{
+    big::BigNumber,
+    complex::Complex,
+    config::{Config, NumberMode},
+    quantity::Quantity,
+    traits::GetResult,
+    value::Value,
+};
 
 #[derive(Deserialize, Serialize)]
 pub struct Constant {
     name: String,
-    value: Decimal,
+    value: Value,
 }
 
 impl Constant {
-    pub fn new(name: &str, value: Decimal) -> Self {
+    pub fn new(name: &str, value: Value) -> Self {
         Constant {
             name: name.to_string(),
             value,
         }
     }
+
+    fn quantity(name: &str, amount: Decimal, units: &str) -> Self {
+        let quantity = Quantity::parse(amount, units).expect("built-in units exist");
+        Constant::new(name, Value::Quantity(quantity))
+    }
+
+    /// Значение константы `name` в режиме чисел из `config`: в режиме `big` `PI` и `E`
+    /// вычисляются с точностью `Config::digits`, а не берутся из `Decimal`.
+    #[must_use]
+    pub fn in_mode(name: &str, value: Value, config: &Config) -> Value {
+        match (config.number_mode, name) {
+            (NumberMode::Big, "PI") => Value::Big(BigNumber::pi(config.digits)),
+            (NumberMode::Big, "E") => Value::Big(BigNumber::e(config.digits)),
+            _ => value,
+        }
+    }
+
+    /// Есть ли среди встроенных констант константа `name`.
+    #[must_use]
+    pub fn is_builtin(name: &str) -> bool {
+        Constant::builtins()
+            .iter()
+            .any(|constant| constant.name == name)
+    }
+
+    /// Константы, доступные в каждом выражении.
+    #[must_use]
+    pub fn builtins() -> Vec<Constant> {
+        vec![
+            Constant::new("PI", Decimal::PI.into()),
+            Constant::new("E", Decimal::E.into()),
+            Constant::quantity("c", Decimal::new(299_792_458, 0), "m/s"), // СКОРОСТЬ СВЕТА
+            Constant::quantity("g", Decimal::new(980_665, 5), "m/s^2"),   // УСКОРЕНИЕ ПАДЕНИЯ
+            Constant::quantity("G", Decimal::new(66_743, 15), "m^3/(kg*s^2)"), // ГРАВИТАЦИЯ
+            Constant::new("i", Value::Complex(Complex::I)),               // МНИМАЯ ЕДИНИЦА
+        ]
+    }
 }
 
-impl GetResult<Option<Decimal>> for Vec<Constant> {
-    fn get_result(&self, input: &str) -> Option<Decimal> {
+impl GetResult<Option<Value>> for Vec<Constant> {
+    fn get_result(&self, input: &str) -> Option<Value> {
         self.iter()
             .find(|history| history.name == input)
-            .map(|history| history.value)
+            .map(|history| history.value.clone())
     }
 }
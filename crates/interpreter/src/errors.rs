@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
-#[derive(Error, Debug, Serialize, Deserialize, Clone)]
+#[derive(Error, Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum CalcError {
     #[error("Input too big")]
     InputTooBig,
@@ -32,4 +33,188 @@ pub enum CalcError {
 
     #[error("Math error")]
     MathError,
+
+    #[error("Calling nonexistent function: {0}")]
+    CallingNonexistentFunction(String),
+
+    #[error("Cannot create function with name of built-in function: {0}")]
+    CannotCreateFunctionWithBuiltInName(String),
+
+    #[error("Function parameters must be unique variable names")]
+    InvalidFunctionParameters,
+
+    #[error("{0}: every optional argument needs a default value")]
+    MissingDefaultValues(String),
+
+    #[error("Too deep nesting of function calls")]
+    RecursionLimitExceeded,
+
+    #[error("Factorial is defined only for non-negative integers")]
+    FactorialOfNotNaturalNumber,
+
+    #[error("Unknown angle unit: {0}. Expected degrees, radians or gradians")]
+    UnknownAngleUnit(String),
+
+    #[error("Argument is out of domain of function {0}")]
+    OutOfDomain(String),
+
+    #[error("Result is too big")]
+    Overflow,
+
+    #[error("Result is too close to zero")]
+    Underflow,
+
+    #[error("Modulo by zero")]
+    ModuloByZero,
+
+    #[error("There is no result in history for: {0}")]
+    HistoryEntryNotFound(String),
+
+    #[error("Cannot create variable with name of history result: {0}")]
+    CannotCreateVariableWithHistoryName(String),
+
+    #[error("{0} is not defined for complex numbers")]
+    ComplexNotSupported(String),
+
+    #[error("Unknown number mode: {0}. Expected decimal, rational, float or big")]
+    UnknownNumberMode(String),
+
+    #[error("Precision of {0} digits is too big, at most {1} digits are allowed")]
+    TooManyDigits(u32, u32),
+
+    #[error("Incompatible units: {0} and {1}")]
+    UnitMismatch(String, String),
+
+    #[error("{0} is not defined for quantities with units")]
+    UnitNotSupported(String),
+
+    #[error("Cannot raise {0} to this power: unit exponents must stay integer")]
+    InvalidUnitPower(String),
+
+    #[error("Expected a unit after 'to', found {0}")]
+    ExpectedUnit(String),
+
+    #[error("{0} is defined only for integers")]
+    NotAnInteger(String),
+
+    #[error("Unknown base: {0}. Expected dec, hex, bin or oct")]
+    UnknownBase(String),
+
+    #[error("'{0}' is not a digit of a {1} number")]
+    InvalidDigit(String, String),
+
+    #[error("A matrix must list numbers or vectors of the same length")]
+    InvalidMatrix,
+
+    #[error("Incompatible dimensions: {0} and {1}")]
+    DimensionMismatch(String, String),
+
+    #[error("{0} is not defined for vectors and matrices")]
+    MatrixNotSupported(String),
+
+    #[error("{0} is defined only for vectors and matrices")]
+    NotAMatrix(String),
+
+    #[error("{0} is defined only for square matrices")]
+    NotSquareMatrix(String),
+
+    #[error("The matrix is singular")]
+    SingularMatrix,
+
+    #[error("Index {0} is out of range")]
+    IndexOutOfRange(String),
+
+    #[error("The equations contradict each other, the system has no solution")]
+    SingularSystem,
+
+    #[error(
+        "The system has infinitely many solutions: {1} unknowns, but the rank of the system is {0}"
+    )]
+    UnderdeterminedSystem(usize, usize),
+
+    #[error("The equations are not linear in the unknowns")]
+    NonlinearSystem,
+
+    #[error(
+        "solve expects equations followed by the unknowns: solve(2x + y = 3; x - y = 0; x; y), or one equation, its unknown and a starting guess: solve(x^3 = 5; x; 2)"
+    )]
+    InvalidSolveArguments,
+
+    #[error("The root was not found in {0} iterations, try another starting guess")]
+    NoConvergence(usize),
+
+    #[error("The equations do not contain the unknown {0}")]
+    UnknownNotInEquations(String),
+
+    #[error("The result did not reach the required accuracy, the error estimate is {0}")]
+    AccuracyNotReached(String),
+
+    #[error("{0} has {1} terms, but at most {2} are allowed")]
+    TooManyTerms(String, String, usize),
+
+    #[error(
+        "Nested sums and products request at least {0} terms in total, but at most {1} are allowed"
+    )]
+    TooManyNestedTerms(String, usize),
+
+    #[error("{0} cannot be differentiated")]
+    NotDifferentiable(String),
+
+    #[error("{0} is not defined for expressions")]
+    ExpressionNotSupported(String),
+}
+
+/// Участок ввода `start..end` в байтах.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    #[must_use]
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// Ошибка вместе с местом во вводе, где она возникла.
+///
+/// * `error` - сама ошибка.
+/// * `span` - участок ввода с ошибкой, если его удалось определить.
+/// * `expected` - токены, которые парсер ожидал увидеть на месте ошибки.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub error: CalcError,
+    pub span: Option<Span>,
+    pub expected: Vec<String>,
+}
+
+impl Diagnostic {
+    #[must_use]
+    pub fn new(error: CalcError, span: Option<Span>) -> Self {
+        Diagnostic {
+            error,
+            span,
+            expected: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_expected(mut self, expected: Vec<String>) -> Self {
+        self.expected = expected;
+        self
+    }
+}
+
+impl From<CalcError> for Diagnostic {
+    fn from(error: CalcError) -> Self {
+        Diagnostic::new(error, None)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        write!(fmt, "{}", self.error)
+    }
 }
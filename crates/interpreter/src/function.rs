@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::expr::Expr,
+    traits::{GetElementByName, GetResult, RemoveElementIfMaxValue},
+};
+
+/// Максимальная глубина вложенных вызовов пользовательских функций.
+/// Защищает от бесконечной рекурсии, например `f(x) = f(x)`.
+pub const MAX_CALL_DEPTH: usize = 64;
+
+/// Пользовательская функция, созданная вводом вида `f(x; y) = x * y`.
+///
+/// * `name` - имя, по которому функция вызывается.
+/// * `params` - имена параметров в порядке их передачи.
+/// * `body` - выражение, вычисляемое при вызове.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Function {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: Expr,
+}
+
+impl Function {
+    #[must_use]
+    pub fn new(name: String, params: Vec<String>, body: Expr) -> Self {
+        Function { name, params, body }
+    }
+}
+
+impl GetResult<Option<Function>> for Vec<Function> {
+    fn get_result(&self, input: &str) -> Option<Function> {
+        self.iter().find(|function| function.name == input).cloned()
+    }
+}
+
+impl RemoveElementIfMaxValue for Vec<Function> {
+    fn remove_element_if_max_value(&mut self, max_value: usize) {
+        if self.len() > max_value {
+            self.remove(0);
+        }
+    }
+}
+
+impl<'a> GetElementByName<'a, Option<&'a mut Function>> for Vec<Function> {
+    fn get_element_by_name(&'a mut self, name: &str) -> Option<&'a mut Function> {
+        self.iter_mut().find(|function| function.name == name)
+    }
+}
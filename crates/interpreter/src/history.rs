@@ -1,21 +1,21 @@
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     errors::CalcError,
     interpreter::Interpreter,
     traits::{GetResult, RemoveElementIfMaxValue},
+    value::Value,
 };
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct History {
     pub input: String,
-    pub result: Result<Decimal, CalcError>,
+    pub result: Result<Value, CalcError>,
 }
 
 impl History {
     #[must_use]
-    pub fn new(input: &str, result: Result<Decimal, CalcError>) -> Self {
+    pub fn new(input: &str, result: Result<Value, CalcError>) -> Self {
         History {
             input: input.to_string(),
             result,
@@ -44,10 +44,24 @@ impl History {
     pub fn get_len_history(interpreter: &Interpreter, to: usize) -> usize {
         interpreter.request_history.len().min(to)
     }
+
+    /// Возвращает номер записи истории с конца, на которую ссылается имя `name`.
+    /// `ans` и `_` - последний результат, `m1` - тоже последний, `m2` - предпоследний и т.д.
+    /// Для остальных имён возвращает `None`.
+    #[must_use]
+    pub fn index_by_name(name: &str) -> Option<usize> {
+        match name {
+            "ans" | "_" => Some(1),
+            _ => name
+                .strip_prefix('m')
+                .filter(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+                .map(|index| index.parse().unwrap_or(usize::MAX)),
+        }
+    }
 }
 
-impl GetResult<Option<Result<Decimal, CalcError>>> for Vec<History> {
-    fn get_result(&self, input: &str) -> Option<Result<Decimal, CalcError>> {
+impl GetResult<Option<Result<Value, CalcError>>> for Vec<History> {
+    fn get_result(&self, input: &str) -> Option<Result<Value, CalcError>> {
         self.iter()
             .find(|history| history.input == input)
             .map(|history| history.result.clone())
@@ -0,0 +1,502 @@
+//! Целые числа для программистов: литералы `0xFF`, `0b1010`, `0o17` и побитовые операторы.
+//!
+//! Целое число помнит систему счисления, в которой оно записано, и показывается в ней же.
+//! Операции над целыми точны, пока результат остаётся целым и помещается в диапазон `Decimal`.
+//! Целое фиксированной ширины, например `int32(x)`, вместо этого заворачивается по модулю `2^bits`.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Error, Formatter},
+};
+
+use crate::{errors::CalcError, value::Value};
+
+/// Система счисления, в которой показывается целое число.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Base {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
+    Octal,
+}
+
+impl Base {
+    /// Возвращает систему счисления по её имени или сокращению: `dec`, `hex`, `bin`, `oct`.
+    pub fn from_name(name: &str) -> Result<Self, CalcError> {
+        match name {
+            "decimal" | "dec" => Ok(Base::Decimal),
+            "hexadecimal" | "hex" => Ok(Base::Hex),
+            "binary" | "bin" => Ok(Base::Binary),
+            "octal" | "oct" => Ok(Base::Octal),
+            _ => Err(CalcError::UnknownBase(name.to_string())),
+        }
+    }
+
+    fn radix(self) -> u32 {
+        match self {
+            Base::Decimal => 10,
+            Base::Hex => 16,
+            Base::Binary => 2,
+            Base::Octal => 8,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Base::Decimal => "",
+            Base::Hex => "0x",
+            Base::Binary => "0b",
+            Base::Octal => "0o",
+        }
+    }
+}
+
+impl Display for Base {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Base::Decimal => write!(fmt, "decimal"),
+            Base::Hex => write!(fmt, "hex"),
+            Base::Binary => write!(fmt, "binary"),
+            Base::Octal => write!(fmt, "octal"),
+        }
+    }
+}
+
+/// Целочисленный тип фиксированной ширины, как в C: `int32` - это `int32_t`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntType {
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+}
+
+impl IntType {
+    pub const ALL: [IntType; 8] = [
+        IntType::Int8,
+        IntType::Int16,
+        IntType::Int32,
+        IntType::Int64,
+        IntType::UInt8,
+        IntType::UInt16,
+        IntType::UInt32,
+        IntType::UInt64,
+    ];
+
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        match self {
+            IntType::Int8 | IntType::UInt8 => 8,
+            IntType::Int16 | IntType::UInt16 => 16,
+            IntType::Int32 | IntType::UInt32 => 32,
+            IntType::Int64 | IntType::UInt64 => 64,
+        }
+    }
+
+    #[must_use]
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            IntType::Int8 | IntType::Int16 | IntType::Int32 | IntType::Int64
+        )
+    }
+
+    /// Приводит `value` к типу в дополнительном коде: лишние старшие биты отбрасываются.
+    #[must_use]
+    pub fn wrap(self, value: i128) -> i128 {
+        let modulus = 1_i128 << self.bits();
+        let value = value.rem_euclid(modulus);
+        match self.is_signed() && value >= modulus / 2 {
+            true => value - modulus,
+            false => value,
+        }
+    }
+
+    /// Общий тип операндов разных типов, как в C: шире тот, у кого больше битов,
+    /// а при равной ширине - беззнаковый.
+    #[must_use]
+    pub fn common(self, other: IntType) -> IntType {
+        match (self.bits().cmp(&other.bits()), self.is_signed()) {
+            (Ordering::Greater, _) | (Ordering::Equal, false) => self,
+            _ => other,
+        }
+    }
+
+    /// Биты числа `value` этого типа как беззнаковое число: `int8(-1)` - это `0xFF`.
+    fn unsigned(self, value: i128) -> u128 {
+        value.rem_euclid(1_i128 << self.bits()).unsigned_abs()
+    }
+}
+
+impl Display for IntType {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        let sign = if self.is_signed() { "" } else { "u" };
+        write!(fmt, "{sign}int{}", self.bits())
+    }
+}
+
+/// Целое число вместе с системой счисления его записи и, возможно, типом фиксированной ширины.
+/// Сохраняется объектом `{"integer": "255", "base": "hex"}`, тип - полем `"type": "uint8"`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(into = "IntegerRepr", try_from = "IntegerRepr")]
+pub struct Integer {
+    value: i128,
+    base: Base,
+    int_type: Option<IntType>,
+}
+
+impl Integer {
+    /// Наибольшее по модулю целое число, которое помещается в `Decimal`.
+    pub const MAX: i128 = (1 << 96) - 1;
+
+    /// Возвращает `CalcError::Overflow`, если число не помещается в `Decimal`.
+    pub fn new(value: i128, base: Base) -> Result<Self, CalcError> {
+        match value.abs() <= Self::MAX {
+            true => Ok(Integer {
+                value,
+                base,
+                int_type: None,
+            }),
+            false => Err(CalcError::Overflow),
+        }
+    }
+
+    /// Читает литерал `0xFF`, `0b1010` или `0o17`. Цифра вне системы счисления, как в `0b102`,
+    /// даёт ошибку `CalcError::InvalidDigit`.
+    pub fn parse(literal: &str) -> Result<Self, CalcError> {
+        let base = [Base::Hex, Base::Binary, Base::Octal]
+            .into_iter()
+            .find(|base| literal.starts_with(base.prefix()))
+            .ok_or(CalcError::SyntaxError)?;
+        let digits = &literal[base.prefix().len()..];
+        if let Some(digit) = digits.chars().find(|digit| !digit.is_digit(base.radix())) {
+            return Err(CalcError::InvalidDigit(digit.to_string(), base.to_string()));
+        }
+        i128::from_str_radix(digits, base.radix())
+            .map_err(|_| CalcError::InputTooBig)
+            .and_then(|value| Integer::new(value, base))
+            .map_err(|_| CalcError::InputTooBig)
+    }
+
+    /// Целое число из `value` или `None`, если у него есть дробная часть.
+    #[must_use]
+    pub fn from_decimal(value: Decimal, base: Base) -> Option<Self> {
+        if !value.fract().is_zero() {
+            return None;
+        }
+        let value = value.trunc();
+        let value = value.mantissa() / 10_i128.pow(value.scale());
+        Some(Integer {
+            value,
+            base,
+            int_type: None,
+        })
+    }
+
+    /// Переводит значение в целое число для функции или оператора `name`.
+    /// Дробное значение приводит к ошибке `CalcError::NotAnInteger`.
+    pub fn from_value(value: &Value, name: &str) -> Result<Self, CalcError> {
+        match value {
+            Value::Integer(integer) => Ok(*integer),
+            value => Integer::from_decimal(value.real(name)?, Base::Decimal)
+                .ok_or_else(|| CalcError::NotAnInteger(name.to_string())),
+        }
+    }
+
+    /// Целое число из значения без дробной части. `None` для дробных и комплексных
+    /// значений и величин с единицами.
+    #[must_use]
+    pub fn exact(value: &Value) -> Option<Self> {
+        match value {
+            Value::Integer(integer) => Some(*integer),
+            Value::Complex(_) | Value::Quantity(_) | Value::Matrix(_) | Value::Expression(_) => {
+                None
+            }
+            value => Integer::from_decimal(value.real("").ok()?, Base::Decimal),
+        }
+    }
+
+    /// Операнды бинарной операции как целые числа, если один из них записан целым,
+    /// а другой не имеет дробной части. Оба операнда получают систему счисления первого.
+    #[must_use]
+    pub fn pair(left: &Value, right: &Value) -> Option<(Self, Self)> {
+        match (left, right) {
+            (Value::Integer(left), right) => Some((*left, Integer::exact(right)?)),
+            (left, Value::Integer(right)) => {
+                Some((Integer::exact(left)?.with_base(right.base()), *right))
+            }
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn value(&self) -> i128 {
+        self.value
+    }
+
+    #[must_use]
+    pub fn base(&self) -> Base {
+        self.base
+    }
+
+    #[must_use]
+    pub fn int_type(&self) -> Option<IntType> {
+        self.int_type
+    }
+
+    #[must_use]
+    pub fn with_base(self, base: Base) -> Self {
+        Integer { base, ..self }
+    }
+
+    /// Приведение к типу фиксированной ширины с отбрасыванием старших битов: `int8(200)` - это `-56`.
+    #[must_use]
+    pub fn cast(self, int_type: IntType) -> Self {
+        Integer {
+            value: int_type.wrap(self.value),
+            int_type: Some(int_type),
+            ..self
+        }
+    }
+
+    /// Число с системой счисления и типом `other`.
+    #[must_use]
+    pub fn like(self, other: &Integer) -> Self {
+        let integer = self.with_base(other.base);
+        match other.int_type {
+            Some(int_type) => integer.cast(int_type),
+            None => integer,
+        }
+    }
+
+    #[must_use]
+    pub fn to_decimal(&self) -> Decimal {
+        Decimal::from_i128_with_scale(self.value, 0)
+    }
+
+    /// Результат в системе счисления этого числа.
+    /// `None`, если результат не помещается в `Decimal`.
+    fn result(&self, value: Option<i128>) -> Option<Self> {
+        value.and_then(|value| Integer::new(value, self.base).ok())
+    }
+
+    /// Общий тип операндов. `None`, если оба без типа.
+    fn common_type(&self, rhs: &Integer) -> Option<IntType> {
+        match (self.int_type, rhs.int_type) {
+            (Some(left), Some(right)) => Some(left.common(right)),
+            (int_type, None) | (None, int_type) => int_type,
+        }
+    }
+
+    /// Операция над числами без типа выполняется через `checked` и не выходит
+    /// за диапазон `Decimal`. Если у операнда есть тип, оба операнда приводятся
+    /// к общему типу, а результат `wrapping` заворачивается по его ширине.
+    fn apply(
+        &self, rhs: &Integer, checked: impl Fn(i128, i128) -> Option<i128>,
+        wrapping: impl Fn(i128, i128) -> Option<i128>,
+    ) -> Option<Self> {
+        let Some(int_type) = self.common_type(rhs) else {
+            return self.result(checked(self.value, rhs.value));
+        };
+        let value = wrapping(int_type.wrap(self.value), int_type.wrap(rhs.value))?;
+        Some(Integer {
+            value: int_type.wrap(value),
+            base: self.base,
+            int_type: Some(int_type),
+        })
+    }
+
+    #[must_use]
+    pub fn checked_add(&self, rhs: &Integer) -> Option<Self> {
+        self.apply(rhs, i128::checked_add, |left, right| {
+            Some(left.wrapping_add(right))
+        })
+    }
+
+    #[must_use]
+    pub fn checked_sub(&self, rhs: &Integer) -> Option<Self> {
+        self.apply(rhs, i128::checked_sub, |left, right| {
+            Some(left.wrapping_sub(right))
+        })
+    }
+
+    #[must_use]
+    pub fn checked_mul(&self, rhs: &Integer) -> Option<Self> {
+        self.apply(rhs, i128::checked_mul, |left, right| {
+            Some(left.wrapping_mul(right))
+        })
+    }
+
+    /// Частное, если деление нацело. Иначе `None`.
+    /// Числа с типом, как в C, делятся с округлением к нулю.
+    #[must_use]
+    pub fn checked_div(&self, rhs: &Integer) -> Option<Self> {
+        let exact = |left: i128, right: i128| match left.checked_rem(right) {
+            Some(0) => left.checked_div(right),
+            _ => None,
+        };
+        self.apply(rhs, exact, i128::checked_div)
+    }
+
+    /// Частное, округлённое к нулю, как у `div` над `Decimal`.
+    #[must_use]
+    pub fn checked_int_div(&self, rhs: &Integer) -> Option<Self> {
+        self.apply(rhs, i128::checked_div, i128::checked_div)
+    }
+
+    /// Остаток со знаком делимого, как у `mod` над `Decimal`.
+    #[must_use]
+    pub fn checked_rem(&self, rhs: &Integer) -> Option<Self> {
+        self.apply(rhs, i128::checked_rem, i128::checked_rem)
+    }
+
+    /// Степень с неотрицательным показателем.
+    #[must_use]
+    pub fn checked_pow(&self, exponent: &Integer) -> Option<Self> {
+        let checked = |base: i128, exponent: i128| base.checked_pow(u32::try_from(exponent).ok()?);
+        let wrapping =
+            |base: i128, exponent: i128| Some(base.wrapping_pow(u32::try_from(exponent).ok()?));
+        self.apply(exponent, checked, wrapping)
+    }
+
+    /// Противоположное число. `-int8(-128)` заворачивается в `-128`.
+    pub fn neg(&self) -> Result<Self, CalcError> {
+        self.unary(-self.value)
+    }
+
+    pub fn abs(&self) -> Result<Self, CalcError> {
+        self.unary(self.value.abs())
+    }
+
+    fn unary(&self, value: i128) -> Result<Self, CalcError> {
+        match self.int_type {
+            Some(int_type) => Ok(Integer {
+                value: int_type.wrap(value),
+                ..*self
+            }),
+            None => Integer::new(value, self.base),
+        }
+    }
+
+    fn bitwise(&self, rhs: &Integer, op: fn(i128, i128) -> i128) -> Result<Self, CalcError> {
+        let op = |left, right| Some(op(left, right));
+        self.apply(rhs, op, op).ok_or(CalcError::Overflow)
+    }
+
+    pub fn and(&self, rhs: &Integer) -> Result<Self, CalcError> {
+        self.bitwise(rhs, |left, right| left & right)
+    }
+
+    pub fn or(&self, rhs: &Integer) -> Result<Self, CalcError> {
+        self.bitwise(rhs, |left, right| left | right)
+    }
+
+    pub fn xor(&self, rhs: &Integer) -> Result<Self, CalcError> {
+        self.bitwise(rhs, |left, right| left ^ right)
+    }
+
+    /// Побитовое отрицание в дополнительном коде: `not x` равно `-x - 1`.
+    pub fn not(&self) -> Result<Self, CalcError> {
+        self.unary(!self.value)
+    }
+
+    /// Сдвиг влево на `shift` битов, то есть умножение на `2^shift`.
+    /// У числа с типом выдвинутые за его ширину биты теряются.
+    pub fn shl(&self, shift: &Integer) -> Result<Self, CalcError> {
+        let shift = Self::shift(shift, "shl")?;
+        if let Some(int_type) = self.int_type {
+            let value = match shift < int_type.bits() {
+                true => self.value.wrapping_shl(shift),
+                false => 0,
+            };
+            return self.unary(value);
+        }
+        let value = match self.value {
+            0 => Some(0),
+            value => (shift < 127)
+                .then(|| 1_i128 << shift)
+                .and_then(|power| value.checked_mul(power)),
+        };
+        self.result(value).ok_or(CalcError::Overflow)
+    }
+
+    /// Арифметический сдвиг вправо на `shift` битов: знак числа сохраняется.
+    /// Беззнаковые типы сдвигаются логически.
+    pub fn shr(&self, shift: &Integer) -> Result<Self, CalcError> {
+        let shift = Self::shift(shift, "shr")?.min(127);
+        Ok(Integer {
+            value: self.value >> shift,
+            ..*self
+        })
+    }
+
+    fn shift(shift: &Integer, name: &str) -> Result<u32, CalcError> {
+        u32::try_from(shift.value).map_err(|_| CalcError::OutOfDomain(name.to_string()))
+    }
+}
+
+/// `255`, `0xFF`, `-0b101`, `0o17`. Тип записывается после числа: `-128 (int8)`,
+/// а в других системах счисления показываются биты: `0x80 (int8)`.
+impl Display for Integer {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        let (sign, magnitude) = match self.int_type {
+            Some(int_type) if self.base != Base::Decimal => ("", int_type.unsigned(self.value)),
+            _ if self.value < 0 => ("-", self.value.unsigned_abs()),
+            _ => ("", self.value.unsigned_abs()),
+        };
+        let prefix = self.base.prefix();
+        match self.base {
+            Base::Decimal => write!(fmt, "{sign}{magnitude}"),
+            Base::Hex => write!(fmt, "{sign}{prefix}{magnitude:X}"),
+            Base::Binary => write!(fmt, "{sign}{prefix}{magnitude:b}"),
+            Base::Octal => write!(fmt, "{sign}{prefix}{magnitude:o}"),
+        }?;
+        match self.int_type {
+            Some(int_type) => write!(fmt, " ({int_type})"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IntegerRepr {
+    integer: String,
+    base: Base,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    int_type: Option<IntType>,
+}
+
+impl From<Integer> for IntegerRepr {
+    fn from(integer: Integer) -> Self {
+        IntegerRepr {
+            integer: integer.value.to_string(),
+            base: integer.base,
+            int_type: integer.int_type,
+        }
+    }
+}
+
+impl TryFrom<IntegerRepr> for Integer {
+    type Error = CalcError;
+
+    fn try_from(repr: IntegerRepr) -> Result<Self, Self::Error> {
+        let value = repr
+            .integer
+            .parse::<i128>()
+            .map_err(|_| CalcError::InputTooBig)?;
+        let integer = Integer::new(value, repr.base)?;
+        Ok(match repr.int_type {
+            Some(int_type) => integer.cast(int_type),
+            None => integer,
+        })
+    }
+}
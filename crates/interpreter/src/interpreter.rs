@@ -1,6 +1,4 @@
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::f64::consts::{E, PI};
 
 use crate::traits::{GetElementByName, GetResult, RemoveElementIfMaxValue};
 use crate::{
@@ -10,8 +8,11 @@ use crate::{
     },
     config::Config,
     constante::Constant,
-    errors::CalcError,
+    errors::{CalcError, Span},
+    function::{Function, MAX_CALL_DEPTH},
     history::History,
+    registry::Registry,
+    value::Value,
     variable::Variable,
 };
 
@@ -19,43 +20,59 @@ use crate::{
 pub struct Interpreter {
     pub request_history: Vec<History>,
     pub variables: Vec<Variable>,
+    #[serde(default)]
+    pub functions: Vec<Function>,
+    /// Константы не сохраняются, чтобы после обновления появлялись новые.
+    #[serde(skip, default = "Constant::builtins")]
     pub constants: Vec<Constant>,
     pub config: Config,
+    /// Встроенные функции. Не сохраняются, а заново создаются при загрузке.
+    #[serde(skip)]
+    pub registry: Registry,
+    /// Временные значения связанных переменных `integrate`, `derivative`, `sum` и `prod`, а
+    /// также корни `solve`, найденные внутри них.
+    /// Последняя связь закрывает предыдущие и переменные с тем же именем.
+    #[serde(skip)]
+    bindings: Vec<Variable>,
+    /// Сколько ещё членов могут вычислить вложенные `sum` и `prod`. `None` вне них.
+    #[serde(skip)]
+    pub(crate) terms_left: Option<usize>,
+    #[serde(skip)]
+    call_depth: usize,
+    #[serde(skip)]
+    error_span: Option<Span>,
 }
 
 impl Interpreter {
     pub fn new(config: Config) -> Result<Self, CalcError> {
-        let speed_light: Decimal = 299_792_458.0.try_into().map_err(|_| CalcError::MathError)?; // СКОРОСТЬ СВЕТА
-        let acceleration_free_fall: Decimal =
-            9.80665.try_into().map_err(|_| CalcError::MathError)?; // СКОРОСТЬ СВОБОДНОГО ПАДЕНИЯ
-        let gravitational_constant: Decimal = 0.000_000_000_006_672_0
-            .try_into()
-            .map_err(|_| CalcError::MathError)?; // ГРАВИТАЦИОННАЯ ПОСТОЯННАЯ
-        let pi: Decimal = PI.try_into().unwrap();
-        let e: Decimal = E.try_into().unwrap();
-
-        let constants = vec![
-            Constant::new("PI", pi),
-            Constant::new("E", e),
-            Constant::new("c", speed_light),
-            Constant::new("g", acceleration_free_fall),
-            Constant::new("G", gravitational_constant),
-        ];
-
         Ok(Interpreter {
             request_history: Vec::with_capacity(config.max_size_history),
             variables: Vec::with_capacity(config.max_size_history),
-            constants,
+            functions: Vec::new(),
+            constants: Constant::builtins(),
             config,
+            registry: Registry::default(),
+            bindings: Vec::new(),
+            terms_left: None,
+            call_depth: 0,
+            error_span: None,
         })
     }
 
-    pub fn eval(&mut self, calc: Calc, input: &str) -> Result<Option<Decimal>, CalcError> {
+    pub fn eval(&mut self, calc: Calc, input: &str) -> Result<Option<Value>, CalcError> {
+        self.error_span = None;
+        self.config.check_digits()?;
         match calc {
-            Calc::InitVariable(name, expr) => match self.init_variable(name, *expr) {
+            Calc::InitVariable(name, expr) => match self.init_variable(&name, *expr) {
                 Some(err) => Err(err),
                 None => Ok(None),
             },
+            Calc::InitFunction(name, params, body) => {
+                match self.init_function(&name, params, *body) {
+                    Some(err) => Err(err),
+                    None => Ok(None),
+                }
+            }
             Calc::Expr(expr) => match self.eval_expr(&expr, input) {
                 Ok(result) => Ok(Some(result)),
                 Err(err) => Err(err),
@@ -64,7 +81,7 @@ impl Interpreter {
     }
 
     #[must_use]
-    pub fn get_request_history(&self, to: usize) -> Vec<(String, Result<Decimal, CalcError>)> {
+    pub fn get_request_history(&self, to: usize) -> Vec<(String, Result<Value, CalcError>)> {
         self.request_history
             .iter()
             .rev()
@@ -73,15 +90,151 @@ impl Interpreter {
             .collect()
     }
 
-    fn eval_expr(&mut self, expr: &Expr, input: &str) -> Result<Decimal, CalcError> {
+    /// Возвращает результат `index`-й с конца записи истории, начиная с единицы.
+    #[must_use]
+    pub fn get_history_result(&self, index: usize) -> Option<Result<Value, CalcError>> {
+        self.request_history
+            .iter()
+            .rev()
+            .nth(index.checked_sub(1)?)
+            .map(|history| history.result.clone())
+    }
+
+    /// Участок последнего ввода, вычисление которого закончилось ошибкой.
+    #[must_use]
+    pub fn error_span(&self) -> Option<Span> {
+        self.error_span
+    }
+
+    /// Запоминает участок ввода с ошибкой, если более точный участок ещё не известен.
+    pub(crate) fn mark_error(&mut self, span: Span) {
+        self.error_span.get_or_insert(span);
+    }
+
+    /// Вызывает функцию `name` из реестра, а если её там нет - пользовательскую функцию,
+    /// подставляя в её тело вычисленные `args`.
+    pub fn call_function(&mut self, name: &str, args: &[Box<Expr>]) -> Result<Value, CalcError> {
+        if let Some(native) = self.registry.get(name).cloned() {
+            native.check_arity(args.len())?;
+            let values = args
+                .iter()
+                .map(|arg| arg.evaluate(self))
+                .collect::<Result<Vec<Value>, CalcError>>()?;
+            return native.call(&values, self);
+        }
+
+        let function = self
+            .functions
+            .get_result(name)
+            .ok_or(CalcError::CallingNonexistentFunction(name.to_string()))?;
+
+        if args.len() != function.params.len() {
+            return Err(CalcError::IncorrectNumberOfArguments(
+                function.params.len(),
+                args.len(),
+            ));
+        }
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(CalcError::RecursionLimitExceeded);
+        }
+
+        let values = args
+            .iter()
+            .map(|arg| arg.evaluate(self))
+            .collect::<Result<Vec<Value>, CalcError>>()?;
+
+        self.call_depth += 1;
+        let result = function
+            .body
+            .substitute(&function.params, &values)
+            .evaluate(self);
+        self.call_depth -= 1;
+
+        // Участки тела функции относятся к вводу, в котором её определили,
+        // поэтому ошибку показываем на самом вызове.
+        if result.is_err() {
+            self.error_span = None;
+        }
+
+        result
+    }
+
+    /// Вычисляет `expr`, пока имя `name` временно равно `value`. Переменные интерпретатора
+    /// не меняются: связь и всё, что присвоено во время вычисления, снимаются сразу после
+    /// него, в том числе при ошибке.
+    pub fn evaluate_with(
+        &mut self, expr: &Expr, name: &str, value: Value,
+    ) -> Result<Value, CalcError> {
+        let depth = self.bindings.len();
+        self.bindings.push(Variable::new(name.to_string(), value));
+        let result = expr.evaluate(self);
+        self.bindings.truncate(depth);
+        result
+    }
+
+    /// Записывает значение в переменную `name`, а внутри `Interpreter::evaluate_with` -
+    /// во временную связь, которая исчезнет вместе с ним. Так `solve` внутри `integrate`
+    /// не оставляет в переменных значение последнего узла.
+    pub(crate) fn assign(&mut self, name: &str, value: Value) -> Result<(), CalcError> {
+        if self.bindings.is_empty() {
+            return self.set_variable(name, value);
+        }
+        self.check_variable_name(name)?;
+        self.bindings.push(Variable::new(name.to_string(), value));
+        Ok(())
+    }
+
+    /// Индексом `sum` и `prod` может быть любое имя, кроме имён результатов истории. Индекс
+    /// закрывает и константы: в `sum(i; 1; 10; i^2)` `i` - индекс, а не мнимая единица.
+    pub(crate) fn check_index_name(&self, name: &str) -> Result<(), CalcError> {
+        match History::index_by_name(name) {
+            Some(_) => Err(CalcError::CannotCreateVariableWithHistoryName(
+                name.to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Значение временно связанного имени `name`, если оно есть.
+    pub(crate) fn binding(&self, name: &str) -> Option<Value> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|binding| binding.name == name)
+            .map(|binding| binding.value.clone())
+    }
+
+    fn eval_expr(&mut self, expr: &Expr, input: &str) -> Result<Value, CalcError> {
         let result = expr.evaluate(self)?;
-        self.insert_history(input, result);
+        self.insert_history(input, result.clone());
         Ok(result)
     }
 
-    fn init_variable(&mut self, name: &str, expr: Expr) -> Option<CalcError> {
+    /// Записывает значение в переменную `name`, создавая её при необходимости.
+    pub fn set_variable(&mut self, name: &str, value: Value) -> Result<(), CalcError> {
+        self.check_variable_name(name)?;
+        match self.add_or_change_variable(name, value) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Имена констант и результатов истории нельзя занять переменной.
+    pub(crate) fn check_variable_name(&self, name: &str) -> Result<(), CalcError> {
         if self.constants.get_result(name).is_some() {
-            return Some(CalcError::CannotCreateVariablesWithNameConstant);
+            return Err(CalcError::CannotCreateVariablesWithNameConstant);
+        }
+        if History::index_by_name(name).is_some() {
+            return Err(CalcError::CannotCreateVariableWithHistoryName(
+                name.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn init_variable(&mut self, name: &str, expr: Expr) -> Option<CalcError> {
+        if let Err(err) = self.check_variable_name(name) {
+            return Some(err);
         }
 
         match expr.evaluate(self) {
@@ -90,7 +243,33 @@ impl Interpreter {
         }
     }
 
-    fn add_or_change_variable(&mut self, name: &str, result: Decimal) -> Option<CalcError> {
+    fn init_function(&mut self, name: &str, params: Vec<String>, body: Expr) -> Option<CalcError> {
+        if self.registry.get(name).is_some() {
+            return Some(CalcError::CannotCreateFunctionWithBuiltInName(
+                name.to_string(),
+            ));
+        }
+        if params
+            .iter()
+            .enumerate()
+            .any(|(i, param)| params[..i].contains(param))
+        {
+            return Some(CalcError::InvalidFunctionParameters);
+        }
+
+        if let Some(function) = self.functions.get_element_by_name(name) {
+            function.params = params;
+            function.body = body;
+            return None;
+        }
+        self.functions
+            .remove_element_if_max_value(self.config.max_number_variable);
+        self.functions
+            .push(Function::new(name.to_string(), params, body));
+        None
+    }
+
+    fn add_or_change_variable(&mut self, name: &str, result: Value) -> Option<CalcError> {
         if let Some(variable) = self.variables.get_element_by_name(name) {
             if variable.value == result {
                 return None;
@@ -104,7 +283,7 @@ impl Interpreter {
         None
     }
 
-    fn insert_history(&mut self, input: &str, result: Decimal) {
+    fn insert_history(&mut self, input: &str, result: Value) {
         self.request_history
             .remove_element_if_max_value(self.config.max_size_history);
         self.request_history.push(History::new(input, Ok(result)));
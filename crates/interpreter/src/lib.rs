@@ -1,8 +1,21 @@
 pub mod ast;
+pub mod big;
+pub mod complex;
 pub mod config;
 mod constante;
 pub mod errors;
+pub mod function;
 pub mod history;
+pub mod integer;
 pub mod interpreter;
+mod math;
+pub mod matrix;
+pub mod number;
+pub mod quantity;
+pub mod rational;
+pub mod registry;
+pub mod symbolic;
 mod traits;
+pub mod unit;
+pub mod value;
 mod variable;
@@ -0,0 +1,796 @@
+//! Трансцендентные функции над `Decimal` без перевода в `f64`.
+//!
+//! Аргумент сводится к небольшому отрезку, на котором быстро сходится степенной ряд.
+//! Ряды считаются в `Wide` с `WIDE_DIGITS` значащими цифрами, на девять больше, чем у
+//! `Decimal`. Ошибки округлений остаются в лишних цифрах, поэтому результат верно
+//! округляется до `SIGNIFICANT_DIGITS` значащих цифр.
+
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::{config::AngleUnit, errors::CalcError};
+
+/// Количество значащих цифр в результатах функций.
+const SIGNIFICANT_DIGITS: u32 = 28;
+
+/// Количество значащих цифр промежуточных результатов.
+const WIDE_DIGITS: u32 = 37;
+
+/// Наибольшее количество знаков после запятой в `Decimal`.
+const MAX_SCALE: i64 = 28;
+
+/// Ограничение на количество членов ряда.
+const MAX_ITERATIONS: u64 = 256;
+
+/// Показатель степени, начиная с которого `e^x` не помещается в `Decimal`.
+const MAX_EXPONENT: Decimal = Decimal::from_parts(66, 0, 0, false, 0);
+
+/// Модуль показателя, начиная с которого `e^x` не считается и в `Wide`.
+const MAX_WIDE_EXPONENT: i64 = 1000;
+
+pub(crate) trait DecimalMath {
+    fn sin(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn cos(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn tan(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn cot(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn asin(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn acos(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn atan(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn acot(&self, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn atan2(&self, x: Decimal, unit: AngleUnit) -> Result<Decimal, CalcError>;
+    fn sinh(&self) -> Result<Decimal, CalcError>;
+    fn cosh(&self) -> Result<Decimal, CalcError>;
+    fn tanh(&self) -> Result<Decimal, CalcError>;
+    fn asinh(&self) -> Result<Decimal, CalcError>;
+    fn acosh(&self) -> Result<Decimal, CalcError>;
+    fn atanh(&self) -> Result<Decimal, CalcError>;
+    fn exp(&self) -> Result<Decimal, CalcError>;
+    fn ln(&self) -> Result<Decimal, CalcError>;
+    fn log10(&self) -> Result<Decimal, CalcError>;
+    fn log2(&self) -> Result<Decimal, CalcError>;
+    fn log(&self, base: Decimal) -> Result<Decimal, CalcError>;
+    fn powf(&self, exponent: Decimal) -> Result<Decimal, CalcError>;
+    fn nth_root(&self, degree: Decimal) -> Result<Decimal, CalcError>;
+}
+
+impl DecimalMath for Decimal {
+    fn sin(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        sin_cos(*self, unit).0.to_decimal()
+    }
+
+    fn cos(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        sin_cos(*self, unit).1.to_decimal()
+    }
+
+    fn tan(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        let (sin, cos) = sin_cos(*self, unit);
+        sin.checked_div(cos)
+            .ok_or(CalcError::MathError)?
+            .to_decimal()
+    }
+
+    fn cot(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        let (sin, cos) = sin_cos(*self, unit);
+        cos.checked_div(sin)
+            .ok_or(CalcError::MathError)?
+            .to_decimal()
+    }
+
+    fn asin(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        from_radians(asin(Wide::from(*self))?, unit).to_decimal()
+    }
+
+    fn acos(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        from_radians(acos(Wide::from(*self))?, unit).to_decimal()
+    }
+
+    fn atan(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        from_radians(atan(Wide::from(*self)), unit).to_decimal()
+    }
+
+    fn acot(&self, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        from_radians(Wide::HALF_PI - atan(Wide::from(*self)), unit).to_decimal()
+    }
+
+    fn atan2(&self, x: Decimal, unit: AngleUnit) -> Result<Decimal, CalcError> {
+        let (y, x) = (Wide::from(*self), Wide::from(x));
+        let angle = match y.checked_div(x) {
+            Some(ratio) if !x.negative => atan(ratio),
+            Some(ratio) if y.negative => atan(ratio) - Wide::PI,
+            Some(ratio) => atan(ratio) + Wide::PI,
+            None if y.is_zero() => return Err(CalcError::MathError),
+            None if y.negative => -Wide::HALF_PI,
+            None => Wide::HALF_PI,
+        };
+        from_radians(angle, unit).to_decimal()
+    }
+
+    fn sinh(&self) -> Result<Decimal, CalcError> {
+        let x = Wide::from(*self);
+        if x.abs() < Wide::ONE {
+            let x2 = x * x;
+            return series(x, |term, n| (term * x2).div_int((2 * n) * (2 * n + 1))).to_decimal();
+        }
+        let (exp, exp_neg) = (exp(x)?, exp(-x)?);
+        (exp - exp_neg).div_int(2).to_decimal()
+    }
+
+    fn cosh(&self) -> Result<Decimal, CalcError> {
+        let x = Wide::from(*self);
+        let (exp, exp_neg) = (exp(x)?, exp(-x)?);
+        (exp + exp_neg).div_int(2).to_decimal()
+    }
+
+    fn tanh(&self) -> Result<Decimal, CalcError> {
+        if self.abs() > MAX_EXPONENT {
+            return Ok(with_sign_of(Decimal::ONE, *self));
+        }
+        let x = Wide::from(*self);
+        let (exp, exp_neg) = (exp(x)?, exp(-x)?);
+        ((exp - exp_neg) / (exp + exp_neg)).to_decimal()
+    }
+
+    fn asinh(&self) -> Result<Decimal, CalcError> {
+        // asinh(x) = ln(1 + x + x^2 / (1 + sqrt(x^2 + 1))): у нуля нет вычитания близких чисел.
+        let x = Wide::from(*self).abs();
+        let x2 = x * x;
+        let result = ln_1p(x + x2 / (Wide::ONE + (x2 + Wide::ONE).sqrt()));
+        Ok(with_sign_of(result.to_decimal()?, *self))
+    }
+
+    fn acosh(&self) -> Result<Decimal, CalcError> {
+        let x = Wide::from(*self);
+        if x < Wide::ONE {
+            return Err(CalcError::MathError);
+        }
+        let shifted = x - Wide::ONE;
+        ln_1p(shifted + (shifted * (x + Wide::ONE)).sqrt()).to_decimal()
+    }
+
+    fn atanh(&self) -> Result<Decimal, CalcError> {
+        let x = Wide::from(*self);
+        let ratio = (x + x)
+            .checked_div(Wide::ONE - x)
+            .ok_or(CalcError::MathError)?;
+        if ratio <= -Wide::ONE {
+            return Err(CalcError::MathError);
+        }
+        ln_1p(ratio).div_int(2).to_decimal()
+    }
+
+    fn exp(&self) -> Result<Decimal, CalcError> {
+        let result = exp(Wide::from(*self))?.to_decimal()?;
+        match result.is_zero() {
+            true => Err(CalcError::Underflow),
+            false => Ok(result),
+        }
+    }
+
+    fn ln(&self) -> Result<Decimal, CalcError> {
+        ln(Wide::from(*self))?.to_decimal()
+    }
+
+    fn log10(&self) -> Result<Decimal, CalcError> {
+        (ln(Wide::from(*self))? / Wide::LN_10).to_decimal()
+    }
+
+    fn log2(&self) -> Result<Decimal, CalcError> {
+        (ln(Wide::from(*self))? / Wide::LN_2).to_decimal()
+    }
+
+    fn log(&self, base: Decimal) -> Result<Decimal, CalcError> {
+        ln(Wide::from(*self))?
+            .checked_div(ln(Wide::from(base))?)
+            .ok_or(CalcError::MathError)?
+            .to_decimal()
+    }
+
+    fn powf(&self, exponent: Decimal) -> Result<Decimal, CalcError> {
+        let base = *self;
+
+        if exponent.fract().is_zero() {
+            let n = exponent.to_i64().ok_or(CalcError::MathError)?;
+            if base.is_zero() && n < 0 {
+                return Err(CalcError::DivisionByZero);
+            }
+            let result = match (n < 0, powi(base, n.unsigned_abs())) {
+                (false, power) => power.ok_or(CalcError::Overflow)?,
+                (true, Some(power)) if !power.is_zero() => {
+                    Decimal::ONE.checked_div(power).ok_or(CalcError::Overflow)?
+                }
+                (true, Some(_)) => return Err(CalcError::Overflow),
+                (true, None) => return Err(CalcError::Underflow),
+            };
+            if result.is_zero() && !base.is_zero() {
+                return Err(CalcError::Underflow);
+            }
+            return Ok(result);
+        }
+
+        if base.is_zero() {
+            return match exponent.is_sign_positive() {
+                true => Ok(Decimal::ZERO),
+                false => Err(CalcError::DivisionByZero),
+            };
+        }
+        if base.is_sign_negative() {
+            return Err(CalcError::MathError);
+        }
+
+        let power = Wide::from(exponent) * ln(Wide::from(base))?;
+        let result = exp(power)?.to_decimal()?;
+        match result.is_zero() {
+            true => Err(CalcError::Underflow),
+            false => Ok(result),
+        }
+    }
+
+    fn nth_root(&self, degree: Decimal) -> Result<Decimal, CalcError> {
+        let value = *self;
+
+        if degree.is_zero() {
+            return Err(CalcError::MathError);
+        }
+        if !degree.fract().is_zero() {
+            if value.is_sign_negative() && !value.is_zero() {
+                return Err(CalcError::ImpossibleToExtractRootCorrectly);
+            }
+            return value.powf(Decimal::ONE / degree);
+        }
+
+        let n = degree.to_i64().ok_or(CalcError::MathError)?;
+        let root = match (value.is_sign_negative() && !value.is_zero(), n % 2 == 0) {
+            (true, true) => return Err(CalcError::ImpossibleToExtractRootCorrectly),
+            (true, false) => -nth_root(-Wide::from(value), n.unsigned_abs())?,
+            (false, _) => nth_root(Wide::from(value), n.unsigned_abs())?,
+        };
+
+        match n {
+            n if n < 0 => Wide::ONE
+                .checked_div(root)
+                .ok_or(CalcError::DivisionByZero)?
+                .to_decimal(),
+            _ => root.to_decimal(),
+        }
+    }
+}
+
+/// Округляет результат до `SIGNIFICANT_DIGITS` значащих цифр и убирает лишние нули.
+pub(crate) fn round(value: Decimal) -> Decimal {
+    value
+        .round_sf(SIGNIFICANT_DIGITS)
+        .unwrap_or(value)
+        .normalize()
+}
+
+/// Переводит угол `angle` из единиц `from` в единицы `to`.
+pub(crate) fn convert_angle(
+    angle: Decimal, from: AngleUnit, to: AngleUnit,
+) -> Result<Decimal, CalcError> {
+    (Wide::from(angle) * quarter_turn(to) / quarter_turn(from)).to_decimal()
+}
+
+/// Длина гипотенузы `sqrt(a^2 + b^2)`. Квадраты считаются в `Wide` и не переполняются.
+pub(crate) fn hypot(a: Decimal, b: Decimal) -> Result<Decimal, CalcError> {
+    let (a, b) = (Wide::from(a), Wide::from(b));
+    (a * a + b * b).sqrt().to_decimal()
+}
+
+/// Возвращает `value` со знаком числа `sign`.
+fn with_sign_of(mut value: Decimal, sign: Decimal) -> Decimal {
+    value.set_sign_negative(sign.is_sign_negative());
+    value
+}
+
+/// Возводит `base` в натуральную степень `exponent` быстрым возведением в степень.
+fn powi(base: Decimal, exponent: u64) -> Option<Decimal> {
+    let (mut result, mut base, mut exponent) = (Decimal::ONE, base, exponent);
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = result.checked_mul(base)?;
+        }
+        exponent /= 2;
+        if exponent > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(result)
+}
+
+/// Четверть оборота в единицах `unit`.
+fn quarter_turn(unit: AngleUnit) -> Wide {
+    match unit {
+        AngleUnit::Radians => Wide::HALF_PI,
+        unit => Wide::from(unit.full_turn()).div_int(4),
+    }
+}
+
+/// Переводит угол `angle` из радиан в единицы `unit`.
+fn from_radians(angle: Wide, unit: AngleUnit) -> Wide {
+    match unit {
+        AngleUnit::Radians => angle,
+        unit => angle * quarter_turn(unit) / Wide::HALF_PI,
+    }
+}
+
+/// Суммирует ряд, каждый член которого получается из предыдущего функцией `next`,
+/// пока члены не станут меньше последнего разряда суммы.
+fn series(first: Wide, next: impl Fn(Wide, u64) -> Wide) -> Wide {
+    let mut sum = first;
+    let mut term = first;
+    for n in 1..MAX_ITERATIONS {
+        term = next(term, n);
+        if term.is_zero() || term.exponent + i64::from(WIDE_DIGITS) < sum.exponent {
+            break;
+        }
+        sum = sum + term;
+    }
+    sum
+}
+
+/// Синус и косинус угла `angle` в единицах `unit`.
+/// Угол сводится к четверти круга в своих единицах, поэтому `sin(180)` в градусах - ровно
+/// ноль, а четверть - к отрезку от нуля до её половины.
+fn sin_cos(angle: Decimal, unit: AngleUnit) -> (Wide, Wide) {
+    let quarter = quarter_turn(unit);
+    let angle = match unit {
+        AngleUnit::Radians => Wide::from(angle),
+        unit => Wide::from(angle % unit.full_turn()),
+    };
+
+    let quadrant = (angle / quarter).floor();
+    let reduced = angle - quadrant * quarter;
+    let scale = Wide::HALF_PI / quarter;
+    let (sin, cos) = if reduced + reduced <= quarter {
+        let reduced = reduced * scale;
+        (sin_series(reduced), cos_series(reduced))
+    } else {
+        let complement = (quarter - reduced) * scale;
+        (cos_series(complement), sin_series(complement))
+    };
+
+    match quadrant.to_i64().unwrap_or(0).rem_euclid(4) {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        2 => (-sin, -cos),
+        _ => (-cos, sin),
+    }
+}
+
+fn sin_series(x: Wide) -> Wide {
+    let x2 = x * x;
+    series(x, |term, n| -(term * x2).div_int((2 * n) * (2 * n + 1)))
+}
+
+fn cos_series(x: Wide) -> Wide {
+    let x2 = x * x;
+    series(Wide::ONE, |term, n| {
+        -(term * x2).div_int((2 * n - 1) * (2 * n))
+    })
+}
+
+/// Арктангенс в радианах.
+/// Аргумент сводится к `[0; 1]`, а затем дважды уменьшается по формуле
+/// `atan(x) = 2 * atan(x / (1 + sqrt(1 + x^2)))`, чтобы ряд сходился быстрее.
+fn atan(x: Wide) -> Wide {
+    if x.negative {
+        return -atan(-x);
+    }
+    if x > Wide::ONE {
+        return Wide::HALF_PI - atan(Wide::ONE / x);
+    }
+
+    let mut x = x;
+    for _ in 0..2 {
+        x = x / (Wide::ONE + (Wide::ONE + x * x).sqrt());
+    }
+
+    let x2 = x * x;
+    let sum = series(x, |term, n| {
+        -(term * x2 * Wide::from(2 * n as i64 - 1)).div_int(2 * n + 1)
+    });
+    sum * Wide::from(4)
+}
+
+/// Арксинус в радианах. Аргумент должен лежать в `[-1; 1]`.
+fn asin(x: Wide) -> Result<Wide, CalcError> {
+    match x.abs().partial_cmp(&Wide::ONE) {
+        Some(Ordering::Greater) => Err(CalcError::MathError),
+        Some(Ordering::Equal) if x.negative => Ok(-Wide::HALF_PI),
+        Some(Ordering::Equal) => Ok(Wide::HALF_PI),
+        _ => Ok(atan(x / ((Wide::ONE - x) * (Wide::ONE + x)).sqrt())),
+    }
+}
+
+/// Арккосинус в радианах. Считается через арктангенс, а не как `PI/2 - asin(x)`,
+/// чтобы у единицы не вычитать близкие числа.
+fn acos(x: Wide) -> Result<Wide, CalcError> {
+    if x.abs() > Wide::ONE {
+        return Err(CalcError::MathError);
+    }
+    if x.is_zero() {
+        return Ok(Wide::HALF_PI);
+    }
+    let angle = atan(((Wide::ONE - x) * (Wide::ONE + x)).sqrt() / x.abs());
+    match x.negative {
+        true => Ok(Wide::PI - angle),
+        false => Ok(angle),
+    }
+}
+
+/// Экспонента. Показатель раскладывается в `k * ln(10) + r`, где `0 <= r < ln(10)`,
+/// тогда `e^x = e^r * 10^k`, и рядом считается только `e^r`.
+fn exp(x: Wide) -> Result<Wide, CalcError> {
+    if x.abs() > Wide::from(MAX_WIDE_EXPONENT) {
+        return Err(match x.negative {
+            true => CalcError::Underflow,
+            false => CalcError::Overflow,
+        });
+    }
+
+    let k = (x / Wide::LN_10).floor();
+    let r = x - k * Wide::LN_10;
+    let result = series(Wide::ONE, |term, n| (term * r).div_int(n));
+    Ok(Wide {
+        exponent: result.exponent + k.to_i64().unwrap_or(0),
+        ..result
+    })
+}
+
+/// Натуральный логарифм положительного числа.
+/// Числа от половины до двух сразу идут в `ln_1p`, остальные раскладываются в
+/// `m * 2^j * 10^k`, где `1 <= m < 2`.
+fn ln(x: Wide) -> Result<Wide, CalcError> {
+    if x.negative || x.is_zero() {
+        return Err(CalcError::MathError);
+    }
+    if x >= Wide::HALF && x < Wide::TWO {
+        return Ok(ln_1p(x - Wide::ONE));
+    }
+
+    let exponent = x.exponent + i64::from(WIDE_DIGITS) - 1;
+    let mut mantissa = Wide {
+        exponent: 1 - i64::from(WIDE_DIGITS),
+        ..x
+    };
+    let mut halvings = 0;
+    while mantissa >= Wide::TWO {
+        mantissa = mantissa * Wide::HALF;
+        halvings += 1;
+    }
+
+    Ok(Wide::from(exponent) * Wide::LN_10
+        + Wide::from(halvings) * Wide::LN_2
+        + ln_1p(mantissa - Wide::ONE))
+}
+
+/// `ln(1 + x)` рядом `2 * atanh(z)`, где `z = x / (2 + x)`.
+/// У нуля не вычитаются близкие числа, поэтому маленький `x` не теряет цифр.
+fn ln_1p(x: Wide) -> Wide {
+    let z = x / (Wide::TWO + x);
+    let z2 = z * z;
+    let atanh = series(z, |term, n| {
+        (term * z2 * Wide::from(2 * n as i64 - 1)).div_int(2 * n + 1)
+    });
+    atanh + atanh
+}
+
+/// Корень натуральной степени `n` из неотрицательного числа: `exp(ln(x) / n)`.
+/// Результат точнее последней цифры `Decimal`, поэтому `rt(8; 3)` округляется ровно до 2.
+fn nth_root(x: Wide, n: u64) -> Result<Wide, CalcError> {
+    if x.is_zero() || n == 1 {
+        return Ok(x);
+    }
+    exp(ln(x)?.div_int(n))
+}
+
+/// Десятичное число с плавающей точкой для промежуточных результатов: `mantissa * 10^exponent`.
+/// У ненулевого числа в мантиссе ровно `WIDE_DIGITS` цифр, а у нуля мантисса и порядок нулевые.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Wide {
+    negative: bool,
+    mantissa: u128,
+    exponent: i64,
+}
+
+/// `10^n`. Помещается в `u128` при `n <= 38`.
+const fn pow10(n: u32) -> u128 {
+    10_u128.pow(n)
+}
+
+impl Wide {
+    const HALF: Wide = Wide::constant(5 * pow10(WIDE_DIGITS - 1), -(WIDE_DIGITS as i64));
+    /// 1.570796326794896619231321691639751442
+    const HALF_PI: Wide = Wide::constant(1_570_796_326_794_896_619_231_321_691_639_751_442, -36);
+    /// 2.302585092994045684017991454684364208
+    const LN_10: Wide = Wide::constant(2_302_585_092_994_045_684_017_991_454_684_364_208, -36);
+    /// 0.6931471805599453094172321214581765681
+    const LN_2: Wide = Wide::constant(6_931_471_805_599_453_094_172_321_214_581_765_681, -37);
+    const ONE: Wide = Wide::constant(pow10(WIDE_DIGITS - 1), 1 - WIDE_DIGITS as i64);
+    /// 3.141592653589793238462643383279502884
+    const PI: Wide = Wide::constant(3_141_592_653_589_793_238_462_643_383_279_502_884, -36);
+    const TWO: Wide = Wide::constant(2 * pow10(WIDE_DIGITS - 1), 1 - WIDE_DIGITS as i64);
+    const ZERO: Wide = Wide::constant(0, 0);
+
+    const fn constant(mantissa: u128, exponent: i64) -> Self {
+        Wide {
+            negative: false,
+            mantissa,
+            exponent,
+        }
+    }
+
+    /// Создаёт число и округляет мантиссу до `WIDE_DIGITS` цифр, половина - от нуля.
+    fn new(negative: bool, mantissa: u128, exponent: i64) -> Self {
+        if mantissa == 0 {
+            return Wide::ZERO;
+        }
+        let digits = mantissa.ilog10() + 1;
+        if digits <= WIDE_DIGITS {
+            let shift = WIDE_DIGITS - digits;
+            return Wide {
+                negative,
+                mantissa: mantissa * pow10(shift),
+                exponent: exponent - i64::from(shift),
+            };
+        }
+
+        let divisor = pow10(digits - WIDE_DIGITS);
+        let mut rounded = mantissa / divisor + u128::from(mantissa % divisor * 2 >= divisor);
+        let mut exponent = exponent + i64::from(digits - WIDE_DIGITS);
+        if rounded == pow10(WIDE_DIGITS) {
+            rounded /= 10;
+            exponent += 1;
+        }
+        Wide {
+            negative,
+            mantissa: rounded,
+            exponent,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    fn abs(self) -> Self {
+        Wide {
+            negative: false,
+            ..self
+        }
+    }
+
+    /// Частное или `None` при делении на ноль. Частное считается на цифру длиннее
+    /// мантиссы, и эта цифра округляет результат.
+    fn checked_div(self, rhs: Wide) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if self.is_zero() {
+            return Some(Wide::ZERO);
+        }
+        let mut quotient = self.mantissa / rhs.mantissa;
+        let mut rest = self.mantissa % rhs.mantissa;
+        let mut exponent = self.exponent - rhs.exponent;
+        while quotient < pow10(WIDE_DIGITS) {
+            rest *= 10;
+            quotient = quotient * 10 + rest / rhs.mantissa;
+            rest %= rhs.mantissa;
+            exponent -= 1;
+        }
+        Some(Wide::new(self.negative != rhs.negative, quotient, exponent))
+    }
+
+    /// Частное от деления на натуральное число. Быстрее `checked_div` для небольших делителей.
+    fn div_int(self, divisor: u64) -> Self {
+        if self.is_zero() {
+            return self;
+        }
+        let divisor = u128::from(divisor);
+        let mut quotient = self.mantissa / divisor;
+        let mut rest = self.mantissa % divisor;
+        let mut exponent = self.exponent;
+        while quotient < pow10(WIDE_DIGITS) {
+            rest *= 10;
+            quotient = quotient * 10 + rest / divisor;
+            rest %= divisor;
+            exponent -= 1;
+        }
+        Wide::new(self.negative, quotient, exponent)
+    }
+
+    /// Наибольшее целое число, не превышающее это.
+    fn floor(self) -> Self {
+        if self.exponent >= 0 {
+            return self;
+        }
+        let fraction_digits = -self.exponent;
+        if fraction_digits > i64::from(WIDE_DIGITS) {
+            return match self.negative {
+                true => -Wide::ONE,
+                false => Wide::ZERO,
+            };
+        }
+        let divisor = pow10(fraction_digits as u32);
+        let integer = self.mantissa / divisor;
+        let round_down = self.negative && !self.mantissa.is_multiple_of(divisor);
+        Wide::new(self.negative, integer + u128::from(round_down), 0)
+    }
+
+    /// Значение целого числа, если оно помещается в `i64`.
+    fn to_i64(self) -> Option<i64> {
+        let magnitude = match self.exponent {
+            exponent if exponent < 0 => self.mantissa / pow10(u32::try_from(-exponent).ok()?),
+            exponent => self
+                .mantissa
+                .checked_mul(pow10(u32::try_from(exponent).ok()?))?,
+        };
+        let magnitude = i64::try_from(magnitude).ok()?;
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// Значение, верно округлённое до `SIGNIFICANT_DIGITS` значащих цифр, но не больше чем
+    /// до `MAX_SCALE` знаков после запятой. Слишком большие числа - `CalcError::Overflow`.
+    fn to_decimal(self) -> Result<Decimal, CalcError> {
+        if self.is_zero() {
+            return Ok(Decimal::ZERO);
+        }
+
+        let drop = i64::from(WIDE_DIGITS - SIGNIFICANT_DIGITS).max(-self.exponent - MAX_SCALE);
+        if drop > i64::from(WIDE_DIGITS) {
+            return Ok(Decimal::ZERO);
+        }
+        let divisor = pow10(drop as u32);
+        let (mut mantissa, rest) = (self.mantissa / divisor, self.mantissa % divisor);
+        if rest * 2 > divisor || (rest * 2 == divisor && mantissa % 2 == 1) {
+            mantissa += 1;
+        }
+
+        let exponent = self.exponent + drop;
+        if exponent > i64::from(SIGNIFICANT_DIGITS) {
+            return Err(CalcError::Overflow);
+        }
+        let mantissa = (0..exponent.max(0))
+            .try_fold(mantissa, |mantissa, _| mantissa.checked_mul(10))
+            .and_then(|mantissa| i128::try_from(mantissa).ok())
+            .ok_or(CalcError::Overflow)?;
+        let mantissa = if self.negative { -mantissa } else { mantissa };
+        Decimal::try_from_i128_with_scale(mantissa, (-exponent).max(0) as u32)
+            .map(|value| value.normalize())
+            .map_err(|_| CalcError::Overflow)
+    }
+
+    /// Квадратный корень неотрицательного числа методом Ньютона. Начальное приближение
+    /// берётся из `f64`, а каждый шаг удваивает количество верных цифр.
+    fn sqrt(self) -> Self {
+        if self.is_zero() || self.negative {
+            return Wide::ZERO;
+        }
+        let (mut mantissa, mut exponent) = (self.mantissa, self.exponent);
+        if exponent % 2 != 0 {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+        let mut root = Wide::new(false, (mantissa as f64).sqrt() as u128, exponent / 2);
+        for _ in 0..4 {
+            root = (root + self / root).div_int(2);
+        }
+        root
+    }
+}
+
+impl From<Decimal> for Wide {
+    fn from(value: Decimal) -> Self {
+        Wide::new(
+            value.is_sign_negative(),
+            value.mantissa().unsigned_abs(),
+            -i64::from(value.scale()),
+        )
+    }
+}
+
+impl From<i64> for Wide {
+    fn from(value: i64) -> Self {
+        Wide::new(value < 0, u128::from(value.unsigned_abs()), 0)
+    }
+}
+
+impl Neg for Wide {
+    type Output = Wide;
+
+    fn neg(self) -> Wide {
+        Wide {
+            negative: !self.negative && !self.is_zero(),
+            ..self
+        }
+    }
+}
+
+impl Add for Wide {
+    type Output = Wide;
+
+    /// Мантисса большего по порядку слагаемого получает ещё одну цифру, поэтому сумма
+    /// округляется только один раз.
+    fn add(self, rhs: Wide) -> Wide {
+        if self.is_zero() {
+            return rhs;
+        }
+        if rhs.is_zero() {
+            return self;
+        }
+        let (big, small) = match self.exponent >= rhs.exponent {
+            true => (self, rhs),
+            false => (rhs, self),
+        };
+
+        let high = big.mantissa * 10;
+        let low = match big.exponent - small.exponent - 1 {
+            -1 => small.mantissa * 10,
+            shift if shift > i64::from(WIDE_DIGITS) + 1 => 0,
+            shift => {
+                let divisor = pow10(shift as u32);
+                small.mantissa / divisor + u128::from(small.mantissa % divisor * 2 >= divisor)
+            }
+        };
+        let exponent = big.exponent - 1;
+        match (big.negative == small.negative, high >= low) {
+            (true, _) => Wide::new(big.negative, high + low, exponent),
+            (false, true) => Wide::new(big.negative, high - low, exponent),
+            (false, false) => Wide::new(small.negative, low - high, exponent),
+        }
+    }
+}
+
+impl Sub for Wide {
+    type Output = Wide;
+
+    fn sub(self, rhs: Wide) -> Wide {
+        self + -rhs
+    }
+}
+
+impl Mul for Wide {
+    type Output = Wide;
+
+    /// Мантиссы делятся на половины по 18 цифр, чтобы произведения частей поместились в `u128`.
+    fn mul(self, rhs: Wide) -> Wide {
+        const SPLIT: u128 = pow10(18);
+        if self.is_zero() || rhs.is_zero() {
+            return Wide::ZERO;
+        }
+        let (high_l, low_l) = (self.mantissa / SPLIT, self.mantissa % SPLIT);
+        let (high_r, low_r) = (rhs.mantissa / SPLIT, rhs.mantissa % SPLIT);
+
+        let middle = high_l * low_r + low_l * high_r + low_l * low_r / SPLIT;
+        let high = high_l * high_r + middle / SPLIT + u128::from(middle % SPLIT * 2 >= SPLIT);
+        Wide::new(
+            self.negative != rhs.negative,
+            high,
+            self.exponent + rhs.exponent + 36,
+        )
+    }
+}
+
+impl Div for Wide {
+    type Output = Wide;
+
+    /// # Panics
+    /// Паникует при делении на ноль, как и `Decimal`.
+    fn div(self, rhs: Wide) -> Wide {
+        self.checked_div(rhs).expect("division by zero")
+    }
+}
+
+impl PartialOrd for Wide {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let difference = *self - *other;
+        Some(match (difference.is_zero(), difference.negative) {
+            (true, _) => Ordering::Equal,
+            (false, true) => Ordering::Less,
+            (false, false) => Ordering::Greater,
+        })
+    }
+}
@@ -0,0 +1,552 @@
+//! Векторы и матрицы: `[1; 2; 3]`, `[[1; 2]; [3; 4]]`.
+//!
+//! Элементы - обычные значения, поэтому матрицы считаются в любом режиме чисел, а элементами
+//! могут быть комплексные числа и величины с единицами. Над элементами работает `FactoryOp`.
+//! Исключение Гаусса ведётся в точных дробях и переходит на `Decimal`, только если дробь
+//! переполнилась, поэтому `det([[1; 2]; [3; 4]])` равен ровно `-2`.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Error, Formatter};
+
+use crate::{
+    ast::{
+        opcode::{Opcode, UnaryOpcode},
+        operation::FactoryOp,
+    },
+    errors::CalcError,
+    integer::Integer,
+    rational::Rational,
+    value::Value,
+};
+
+/// Длина вектора или количество строк и столбцов матрицы.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+    Vector(usize),
+    Matrix(usize, usize),
+}
+
+/// `3` у вектора, `2x3` у матрицы.
+impl Display for Shape {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Shape::Vector(len) => write!(fmt, "{len}"),
+            Shape::Matrix(rows, cols) => write!(fmt, "{rows}x{cols}"),
+        }
+    }
+}
+
+/// Вектор или матрица. Элементы хранятся по строкам.
+/// Сохраняется объектом `{"vector": [1, 2]}` или `{"matrix": [[1, 2], [3, 4]]}`.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(into = "MatrixRepr", try_from = "MatrixRepr")]
+pub struct Matrix {
+    shape: Shape,
+    elements: Vec<Value>,
+}
+
+impl Matrix {
+    /// Вектор из чисел. Пустой вектор и вектор из векторов приводят к `CalcError::InvalidMatrix`.
+    pub fn vector(elements: Vec<Value>) -> Result<Self, CalcError> {
+        if elements.is_empty()
+            || elements
+                .iter()
+                .any(|value| matches!(value, Value::Matrix(_)))
+        {
+            return Err(CalcError::InvalidMatrix);
+        }
+        Ok(Matrix {
+            shape: Shape::Vector(elements.len()),
+            elements,
+        })
+    }
+
+    /// Матрица из строк одинаковой длины.
+    pub fn from_rows(rows: Vec<Vec<Value>>) -> Result<Self, CalcError> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != cols) {
+            return Err(CalcError::InvalidMatrix);
+        }
+        let shape = Shape::Matrix(rows.len(), cols);
+        let elements = Matrix::vector(rows.into_iter().flatten().collect())?.elements;
+        Ok(Matrix { shape, elements })
+    }
+
+    /// Значение литерала `[...]`: из чисел получается вектор, из векторов одной длины -
+    /// матрица, строками которой они становятся.
+    pub fn from_values(values: Vec<Value>) -> Result<Self, CalcError> {
+        if !values.iter().any(|value| matches!(value, Value::Matrix(_))) {
+            return Matrix::vector(values);
+        }
+        let rows = values
+            .into_iter()
+            .map(|value| match value {
+                Value::Matrix(Matrix {
+                    shape: Shape::Vector(_),
+                    elements,
+                }) => Ok(elements),
+                _ => Err(CalcError::InvalidMatrix),
+            })
+            .collect::<Result<Vec<Vec<Value>>, CalcError>>()?;
+        Matrix::from_rows(rows)
+    }
+
+    /// Единичная матрица размера `size`.
+    #[must_use]
+    pub fn identity(size: usize) -> Self {
+        let elements = (0..size * size)
+            .map(|i| match i / size == i % size {
+                true => Value::Real(Decimal::ONE),
+                false => Value::Real(Decimal::ZERO),
+            })
+            .collect();
+        Matrix {
+            shape: Shape::Matrix(size, size),
+            elements,
+        }
+    }
+
+    #[must_use]
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    #[must_use]
+    pub fn elements(&self) -> &[Value] {
+        &self.elements
+    }
+
+    /// Матрица, а не вектор.
+    #[must_use]
+    pub fn is_matrix(&self) -> bool {
+        matches!(self.shape, Shape::Matrix(..))
+    }
+
+    /// Применяет `f` к каждому элементу.
+    pub fn map(&self, f: impl Fn(&Value) -> Result<Value, CalcError>) -> Result<Self, CalcError> {
+        Ok(Matrix {
+            shape: self.shape,
+            elements: self.elements.iter().map(f).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Применяет `f` к парам элементов с одинаковыми индексами.
+    pub fn zip_with(
+        &self, rhs: &Matrix, f: impl Fn(&Value, &Value) -> Result<Value, CalcError>,
+    ) -> Result<Self, CalcError> {
+        self.check_shape(rhs)?;
+        Ok(Matrix {
+            shape: self.shape,
+            elements: self
+                .elements
+                .iter()
+                .zip(&rhs.elements)
+                .map(|(left, right)| f(left, right))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Транспонированная матрица. Вектор не меняется.
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let Shape::Matrix(rows, cols) = self.shape else {
+            return self.clone();
+        };
+        let elements = (0..rows * cols)
+            .map(|i| self.elements[(i % rows) * cols + i / rows].clone())
+            .collect();
+        Matrix {
+            shape: Shape::Matrix(cols, rows),
+            elements,
+        }
+    }
+
+    /// Матричное произведение. Вектор слева - строка, справа - столбец,
+    /// поэтому произведение двух векторов - их скалярное произведение.
+    pub fn mul(&self, rhs: &Matrix) -> Result<Value, CalcError> {
+        let (rows, inner) = match self.shape {
+            Shape::Vector(len) => (1, len),
+            Shape::Matrix(rows, cols) => (rows, cols),
+        };
+        let (rhs_rows, cols) = match rhs.shape {
+            Shape::Vector(len) => (len, 1),
+            Shape::Matrix(rows, cols) => (rows, cols),
+        };
+        if inner != rhs_rows {
+            return Err(self.mismatch(rhs));
+        }
+
+        let elements = (0..rows * cols)
+            .map(|i| {
+                let (row, col) = (i / cols, i % cols);
+                sum((0..inner).map(|k| {
+                    mul(
+                        &self.elements[row * inner + k],
+                        &rhs.elements[k * cols + col],
+                    )
+                }))
+            })
+            .collect::<Result<Vec<Value>, CalcError>>()?;
+
+        match (self.shape, rhs.shape) {
+            (Shape::Vector(_), Shape::Vector(_)) => Ok(elements[0].clone()),
+            (Shape::Matrix(..), Shape::Matrix(..)) => Ok(Value::Matrix(Matrix {
+                shape: Shape::Matrix(rows, cols),
+                elements,
+            })),
+            _ => Matrix::vector(elements).map(Value::Matrix),
+        }
+    }
+
+    /// Скалярное произведение векторов одной длины.
+    pub fn dot(&self, rhs: &Matrix) -> Result<Value, CalcError> {
+        match (self.shape, rhs.shape) {
+            (Shape::Vector(_), Shape::Vector(_)) => self.mul(rhs),
+            _ => Err(CalcError::DimensionMismatch(
+                self.shape.to_string(),
+                rhs.shape.to_string(),
+            )),
+        }
+    }
+
+    /// Векторное произведение векторов длины 3.
+    pub fn cross(&self, rhs: &Matrix) -> Result<Self, CalcError> {
+        if self.shape != Shape::Vector(3) || rhs.shape != Shape::Vector(3) {
+            return Err(self.mismatch(rhs));
+        }
+        let (a, b) = (&self.elements, &rhs.elements);
+        let component = |i: usize, j: usize| sub(&mul(&a[i], &b[j])?, &mul(&a[j], &b[i])?);
+        Matrix::vector(vec![component(1, 2)?, component(2, 0)?, component(0, 1)?])
+    }
+
+    /// Определитель квадратной матрицы.
+    pub fn det(&self) -> Result<Value, CalcError> {
+        let size = self.square("det")?;
+        let mut rows = self.exact_rows();
+        let mut det = Value::Rational(Rational::from(Decimal::ONE));
+
+        for col in 0..size {
+            let Some(pivot) = pivot_row(&rows, col, col)? else {
+                return Ok(Value::Real(Decimal::ZERO));
+            };
+            if pivot != col {
+                rows.swap(col, pivot);
+                det = FactoryOp::match_unary(UnaryOpcode::Neg, det)?;
+            }
+            det = mul(&det, &rows[col][col])?;
+
+            for row in col + 1..size {
+                let factor = div(&rows[row][col], &rows[col][col])?;
+                subtract_row(&mut rows, row, col, &factor, col)?;
+            }
+        }
+        Ok(self.restore(det))
+    }
+
+    /// Обратная матрица. Вырожденная матрица приводит к `CalcError::SingularMatrix`.
+    pub fn inv(&self, name: &str) -> Result<Self, CalcError> {
+        let size = self.square(name)?;
+        let (rows, rank) = self.eliminate(Matrix::identity(size).rows())?;
+        if rank < size {
+            return Err(CalcError::SingularMatrix);
+        }
+        Ok(Matrix {
+            shape: self.shape,
+            elements: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Решение системы `self * x = rhs`. Вектор `self` - одно уравнение. Правая часть - вектор
+    /// или матрица, столбцы которой - правые части нескольких систем. Уравнений может быть
+    /// больше, чем неизвестных, если они не противоречат друг другу.
+    pub fn solve(&self, rhs: &Matrix) -> Result<Self, CalcError> {
+        let (equations, unknowns) = match self.shape {
+            Shape::Vector(len) => (1, len),
+            Shape::Matrix(rows, cols) => (rows, cols),
+        };
+        let rhs_rows = match rhs.shape {
+            Shape::Vector(_) => rhs
+                .elements
+                .iter()
+                .map(|value| vec![value.clone()])
+                .collect(),
+            Shape::Matrix(..) => rhs.rows(),
+        };
+        if rhs_rows.len() != equations {
+            return Err(self.mismatch(rhs));
+        }
+
+        let (mut rows, rank) = self.eliminate(rhs_rows)?;
+        for value in rows[rank..].iter().flatten() {
+            if !is_zero(value)? {
+                return Err(CalcError::SingularSystem);
+            }
+        }
+        if rank < unknowns {
+            return Err(CalcError::UnderdeterminedSystem(rank, unknowns));
+        }
+
+        rows.truncate(unknowns);
+        match rhs.shape {
+            Shape::Vector(_) => Matrix::vector(rows.into_iter().flatten().collect()),
+            Shape::Matrix(_, cols) => Ok(Matrix {
+                shape: Shape::Matrix(unknowns, cols),
+                elements: rows.into_iter().flatten().collect(),
+            }),
+        }
+    }
+
+    /// Степень квадратной матрицы с целым показателем. Отрицательная степень -
+    /// степень обратной матрицы.
+    pub fn pow(&self, exponent: &Value) -> Result<Self, CalcError> {
+        let size = self.square("^")?;
+        let exponent = Integer::from_value(exponent, "^")?.value();
+        let mut base = match exponent < 0 {
+            true => self.inv("^")?,
+            false => self.clone(),
+        };
+        let mut exponent = exponent.unsigned_abs();
+        let mut result = Matrix::identity(size);
+
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result.mul_matrix(&base)?;
+            }
+            exponent /= 2;
+            if exponent > 0 {
+                base = base.mul_matrix(&base)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Элемент вектора, строка матрицы или элемент матрицы по номерам, начиная с 1:
+    /// `v[2]`, `m[1]`, `m[1; 2]`.
+    pub fn index(&self, indices: &[Value]) -> Result<Value, CalcError> {
+        let index = |value: &Value, len: usize| {
+            let index = Integer::from_value(value, "Index")?.value();
+            match usize::try_from(index) {
+                Ok(index) if (1..=len).contains(&index) => Ok(index - 1),
+                _ => Err(CalcError::IndexOutOfRange(value.to_string())),
+            }
+        };
+
+        match (self.shape, indices) {
+            (Shape::Vector(len), [i]) => Ok(self.elements[index(i, len)?].clone()),
+            (Shape::Matrix(rows, cols), [row]) => {
+                let row = index(row, rows)?;
+                Matrix::vector(self.elements[row * cols..(row + 1) * cols].to_vec())
+                    .map(Value::Matrix)
+            }
+            (Shape::Matrix(rows, cols), [row, col]) => {
+                Ok(self.elements[index(row, rows)? * cols + index(col, cols)?].clone())
+            }
+            (Shape::Vector(_), _) => Err(CalcError::IncorrectNumberOfArguments(1, indices.len())),
+            (Shape::Matrix(..), _) => Err(CalcError::IncorrectNumberOfArguments(2, indices.len())),
+        }
+    }
+
+    /// Запись, в которой элементы показаны функцией `element`.
+    pub fn format(&self, element: impl Fn(&Value) -> String) -> String {
+        let join = |values: &[Value]| values.iter().map(&element).collect::<Vec<_>>().join("; ");
+        match self.shape {
+            Shape::Vector(_) => format!("[{}]", join(&self.elements)),
+            Shape::Matrix(_, cols) => {
+                let rows = self
+                    .elements
+                    .chunks(cols)
+                    .map(|row| format!("[{}]", join(row)))
+                    .collect::<Vec<_>>();
+                format!("[{}]", rows.join("; "))
+            }
+        }
+    }
+
+    fn mul_matrix(&self, rhs: &Matrix) -> Result<Self, CalcError> {
+        match self.mul(rhs)? {
+            Value::Matrix(matrix) => Ok(matrix),
+            _ => unreachable!("a product of square matrices is a matrix"),
+        }
+    }
+
+    /// Приводит систему `self * x = rhs` к ступенчатому виду методом Гаусса - Жордана.
+    /// Столбцы `rhs` - правые части. Возвращает преобразованные правые части и ранг матрицы.
+    /// Если ранг равен числу столбцов, первые строки правых частей - решение.
+    fn eliminate(&self, rhs: Vec<Vec<Value>>) -> Result<(Vec<Vec<Value>>, usize), CalcError> {
+        let mut rows = self.exact_rows();
+        let cols = rows[0].len();
+        let mut rhs = rhs
+            .iter()
+            .map(|row| row.iter().map(exact).collect::<Vec<Value>>())
+            .collect::<Vec<Vec<Value>>>();
+
+        let mut rank = 0;
+        for col in 0..cols {
+            let Some(pivot) = pivot_row(&rows, rank, col)? else {
+                continue;
+            };
+            rows.swap(rank, pivot);
+            rhs.swap(rank, pivot);
+
+            let pivot = rows[rank][col].clone();
+            for value in rows[rank][col..].iter_mut().chain(rhs[rank].iter_mut()) {
+                *value = div(value, &pivot)?;
+            }
+
+            for row in (0..rows.len()).filter(|&row| row != rank) {
+                let factor = rows[row][col].clone();
+                if is_zero(&factor)? {
+                    continue;
+                }
+                subtract_row(&mut rows, row, rank, &factor, col)?;
+                subtract_row(&mut rhs, row, rank, &factor, 0)?;
+            }
+            rank += 1;
+        }
+
+        let rhs = rhs
+            .into_iter()
+            .map(|row| row.into_iter().map(|value| self.restore(value)).collect())
+            .collect();
+        Ok((rhs, rank))
+    }
+
+    fn rows(&self) -> Vec<Vec<Value>> {
+        let cols = match self.shape {
+            Shape::Vector(len) => len,
+            Shape::Matrix(_, cols) => cols,
+        };
+        self.elements.chunks(cols).map(<[Value]>::to_vec).collect()
+    }
+
+    /// Строки, в которых `Decimal` заменены точными дробями.
+    fn exact_rows(&self) -> Vec<Vec<Value>> {
+        self.rows()
+            .iter()
+            .map(|row| row.iter().map(exact).collect())
+            .collect()
+    }
+
+    /// Возвращает дробь результата в `Decimal`, если в матрице не было дробей.
+    fn restore(&self, value: Value) -> Value {
+        match self
+            .elements
+            .iter()
+            .any(|value| matches!(value, Value::Rational(_)))
+        {
+            true => value,
+            false => value.inexact(),
+        }
+    }
+
+    /// Размер квадратной матрицы, которую принимает `name`.
+    fn square(&self, name: &str) -> Result<usize, CalcError> {
+        match self.shape {
+            Shape::Matrix(rows, cols) if rows == cols => Ok(rows),
+            _ => Err(CalcError::NotSquareMatrix(name.to_string())),
+        }
+    }
+
+    fn check_shape(&self, rhs: &Matrix) -> Result<(), CalcError> {
+        match self.shape == rhs.shape {
+            true => Ok(()),
+            false => Err(self.mismatch(rhs)),
+        }
+    }
+
+    fn mismatch(&self, rhs: &Matrix) -> CalcError {
+        CalcError::DimensionMismatch(self.shape.to_string(), rhs.shape.to_string())
+    }
+}
+
+/// `[1; 2]`, `[[1; 2]; [3; 4]]`.
+impl Display for Matrix {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        write!(fmt, "{}", self.format(Value::to_string))
+    }
+}
+
+/// Вычитает из строки `row` строку `pivot`, умноженную на `factor`, начиная со столбца `from`.
+fn subtract_row(
+    rows: &mut [Vec<Value>], row: usize, pivot: usize, factor: &Value, from: usize,
+) -> Result<(), CalcError> {
+    let pivot = rows[pivot].clone();
+    for (value, pivot) in rows[row].iter_mut().zip(&pivot).skip(from) {
+        *value = sub(value, &mul(factor, pivot)?)?;
+    }
+    Ok(())
+}
+
+fn mul(left: &Value, right: &Value) -> Result<Value, CalcError> {
+    FactoryOp::match_(Opcode::Mul, left.clone(), right.clone())
+}
+
+fn div(left: &Value, right: &Value) -> Result<Value, CalcError> {
+    FactoryOp::match_(Opcode::Div, left.clone(), right.clone())
+}
+
+fn sub(left: &Value, right: &Value) -> Result<Value, CalcError> {
+    FactoryOp::match_(Opcode::Sub, left.clone(), right.clone())
+}
+
+/// Сумма непустой последовательности. Она начинается с первого слагаемого, а не с нуля,
+/// чтобы складывались и величины с единицами.
+fn sum(mut values: impl Iterator<Item = Result<Value, CalcError>>) -> Result<Value, CalcError> {
+    let first = values.next().ok_or(CalcError::InvalidMatrix)??;
+    values.try_fold(first, |sum, value| {
+        FactoryOp::match_(Opcode::Add, sum, value?)
+    })
+}
+
+fn exact(value: &Value) -> Value {
+    match value {
+        Value::Real(real) => Value::Rational(Rational::from(*real)),
+        value => value.clone(),
+    }
+}
+
+fn is_zero(value: &Value) -> Result<bool, CalcError> {
+    value.magnitude().map(|magnitude| magnitude.is_zero())
+}
+
+/// Строка с наибольшим по модулю элементом столбца `col` среди строк от `from` до конца.
+/// `None`, если все они нулевые.
+fn pivot_row(rows: &[Vec<Value>], from: usize, col: usize) -> Result<Option<usize>, CalcError> {
+    let mut pivot = None;
+    let mut max = Decimal::ZERO;
+    for (row, values) in rows.iter().enumerate().skip(from) {
+        let magnitude = values[col].magnitude()?;
+        if magnitude > max {
+            (pivot, max) = (Some(row), magnitude);
+        }
+    }
+    Ok(pivot)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MatrixRepr {
+    Vector(Vec<Value>),
+    Matrix(Vec<Vec<Value>>),
+}
+
+impl From<Matrix> for MatrixRepr {
+    fn from(matrix: Matrix) -> Self {
+        match matrix.shape {
+            Shape::Vector(_) => MatrixRepr::Vector(matrix.elements),
+            Shape::Matrix(..) => MatrixRepr::Matrix(matrix.rows()),
+        }
+    }
+}
+
+impl TryFrom<MatrixRepr> for Matrix {
+    type Error = CalcError;
+
+    fn try_from(repr: MatrixRepr) -> Result<Self, Self::Error> {
+        match repr {
+            MatrixRepr::Vector(elements) => Matrix::vector(elements),
+            MatrixRepr::Matrix(rows) => Matrix::from_rows(rows),
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Общий интерфейс вещественных чисел, над которыми работают операторы.
+//!
+//! Реализован для `Decimal`, `f64` и `BigNumber`. Какой из них получают числа из ввода,
+//! определяет `NumberMode` в `Config`.
+
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::{big::BigNumber, errors::CalcError, math::DecimalMath};
+
+pub trait Number: Clone + PartialOrd {
+    fn from_decimal(value: Decimal) -> Self;
+
+    /// Значение, округлённое до `Decimal`, или `CalcError::Overflow`, если оно не помещается.
+    fn to_decimal(&self) -> Result<Decimal, CalcError>;
+
+    fn is_zero(&self) -> bool;
+
+    fn is_sign_negative(&self) -> bool;
+
+    fn is_integer(&self) -> bool;
+
+    /// Отбрасывает дробную часть.
+    fn trunc(&self) -> Self;
+
+    fn neg(&self) -> Self;
+
+    /// Возвращает `None`, если результат не помещается в тип.
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+
+    /// Делитель не должен быть нулём: это проверяет вызывающий код.
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+
+    /// Остаток со знаком делимого. Делитель не должен быть нулём.
+    fn checked_rem(&self, rhs: &Self) -> Option<Self>;
+
+    fn pow(&self, exponent: &Self) -> Result<Self, CalcError>;
+
+    fn sqrt(&self) -> Result<Self, CalcError>;
+
+    /// Число с запасом точности для промежуточных результатов длинных вычислений.
+    #[must_use]
+    fn widened(&self) -> Self {
+        self.clone()
+    }
+
+    /// Возвращает результат, вычисленный с запасом точности, к точности числа `like`.
+    #[must_use]
+    fn narrowed(self, _like: &Self) -> Self {
+        self
+    }
+
+    /// Убирает конечные нули из записи числа.
+    #[must_use]
+    fn normalize(self) -> Self {
+        self
+    }
+}
+
+impl Number for Decimal {
+    fn from_decimal(value: Decimal) -> Self {
+        value
+    }
+
+    fn to_decimal(&self) -> Result<Decimal, CalcError> {
+        Ok(*self)
+    }
+
+    fn is_zero(&self) -> bool {
+        Decimal::is_zero(self)
+    }
+
+    fn is_sign_negative(&self) -> bool {
+        Decimal::is_sign_negative(self)
+    }
+
+    fn is_integer(&self) -> bool {
+        self.fract().is_zero()
+    }
+
+    fn trunc(&self) -> Self {
+        Decimal::trunc(self)
+    }
+
+    fn neg(&self) -> Self {
+        -*self
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Decimal::checked_add(*self, *rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        Decimal::checked_sub(*self, *rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Decimal::checked_mul(*self, *rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        Decimal::checked_div(*self, *rhs)
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        Decimal::checked_rem(*self, *rhs)
+    }
+
+    fn pow(&self, exponent: &Self) -> Result<Self, CalcError> {
+        self.powf(*exponent)
+    }
+
+    fn sqrt(&self) -> Result<Self, CalcError> {
+        self.nth_root(Decimal::TWO)
+    }
+
+    fn normalize(self) -> Self {
+        Decimal::normalize(&self)
+    }
+}
+
+/// Бесконечность и `NaN` не становятся значениями: операция, которая их даёт, возвращает ошибку.
+impl Number for f64 {
+    fn from_decimal(value: Decimal) -> Self {
+        // `Decimal::to_f64` делит мантиссу на степень десяти и округляет дважды, а разбор
+        // десятичной записи даёт ближайшее `f64`.
+        value.to_string().parse().unwrap_or_default()
+    }
+
+    fn to_decimal(&self) -> Result<Decimal, CalcError> {
+        Decimal::from_f64(*self).ok_or(CalcError::Overflow)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0.0
+    }
+
+    fn is_sign_negative(&self) -> bool {
+        f64::is_sign_negative(*self)
+    }
+
+    fn is_integer(&self) -> bool {
+        self.fract() == 0.0
+    }
+
+    fn trunc(&self) -> Self {
+        f64::trunc(*self)
+    }
+
+    fn neg(&self) -> Self {
+        -*self
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        finite(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        finite(self - rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        finite(self * rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        finite(self / rhs)
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        finite(self % rhs)
+    }
+
+    fn pow(&self, exponent: &Self) -> Result<Self, CalcError> {
+        match self.powf(*exponent) {
+            result if result.is_nan() => Err(CalcError::MathError),
+            result if result.is_infinite() && self.is_zero() => Err(CalcError::DivisionByZero),
+            result if result.is_infinite() => Err(CalcError::Overflow),
+            result if result == 0.0 && !self.is_zero() => Err(CalcError::Underflow),
+            result => Ok(result),
+        }
+    }
+
+    fn sqrt(&self) -> Result<Self, CalcError> {
+        match *self < 0.0 {
+            true => Err(CalcError::MathError),
+            false => Ok(f64::sqrt(*self)),
+        }
+    }
+
+    fn normalize(self) -> Self {
+        self + 0.0
+    }
+}
+
+impl Number for BigNumber {
+    fn from_decimal(value: Decimal) -> Self {
+        BigNumber::from_decimal(value, 0)
+    }
+
+    fn to_decimal(&self) -> Result<Decimal, CalcError> {
+        BigNumber::to_decimal(self)
+    }
+
+    fn is_zero(&self) -> bool {
+        BigNumber::is_zero(self)
+    }
+
+    fn is_sign_negative(&self) -> bool {
+        BigNumber::is_sign_negative(self)
+    }
+
+    fn is_integer(&self) -> bool {
+        BigNumber::is_integer(self)
+    }
+
+    fn trunc(&self) -> Self {
+        BigNumber::trunc(self)
+    }
+
+    fn neg(&self) -> Self {
+        BigNumber::neg(self)
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        BigNumber::checked_add(self, rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        BigNumber::checked_sub(self, rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        BigNumber::checked_mul(self, rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        BigNumber::checked_div(self, rhs)
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        BigNumber::checked_rem(self, rhs)
+    }
+
+    fn pow(&self, exponent: &Self) -> Result<Self, CalcError> {
+        BigNumber::pow(self, exponent)
+    }
+
+    fn sqrt(&self) -> Result<Self, CalcError> {
+        BigNumber::sqrt(self)
+    }
+
+    fn widened(&self) -> Self {
+        BigNumber::widened(self)
+    }
+
+    fn narrowed(self, like: &Self) -> Self {
+        self.with_digits(like.digits())
+    }
+}
+
+/// Запись `f64` без длинных хвостов из нулей: `0.1`, `1e300`, `2.5e-8`.
+#[must_use]
+pub fn format_float(value: f64) -> String {
+    match value.abs() {
+        abs if abs != 0.0 && !(1e-7..1e21).contains(&abs) => format!("{value:e}"),
+        _ => format!("{}", value + 0.0),
+    }
+}
+
+fn finite(value: f64) -> Option<f64> {
+    value.is_finite().then_some(value)
+}
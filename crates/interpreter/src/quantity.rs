@@ -0,0 +1,434 @@
+//! Величины с единицами измерения: `3 km`, `9.81 m/s^2`.
+//!
+//! Величины складываются и сравниваются, только если их размерности совпадают.
+//! При умножении одноимённые и однородные единицы объединяются (`km * m` - это `km^2`),
+//! а произведение когерентных единиц СИ получает собственное имя: `kg*m/s^2` - это `N`.
+//! Безразмерный результат становится обычным числом.
+
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Error, Formatter};
+
+use crate::{
+    errors::CalcError,
+    math::DecimalMath,
+    number::Number,
+    rational::Rational,
+    unit::{Dimension, Unit, DIMENSIONLESS},
+    value::Value,
+};
+
+/// Единицы величины вместе с показателями их степеней.
+type Units = Vec<(Unit, i8)>;
+
+/// Число вместе с произведением степеней единиц.
+/// Сохраняется объектом `{"amount": "9.81", "unit": "m/s^2"}`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "QuantityRepr", try_from = "QuantityRepr")]
+pub struct Quantity {
+    amount: Decimal,
+    units: Units,
+}
+
+impl Quantity {
+    #[must_use]
+    pub fn new(amount: Decimal, unit: Unit) -> Self {
+        Quantity {
+            amount,
+            units: vec![(unit, 1)],
+        }
+    }
+
+    /// Создаёт величину из записи единиц вида `m^3/(kg*s^2)`.
+    pub fn parse(amount: Decimal, units: &str) -> Result<Self, CalcError> {
+        let (numer, denom) = units.split_once('/').unwrap_or((units, ""));
+        let denom = denom.trim_start_matches('(').trim_end_matches(')');
+
+        let parts = numer.split('*').map(|part| (part, 1)).chain(
+            denom
+                .split('*')
+                .filter(|part| !part.is_empty())
+                .map(|part| (part, -1)),
+        );
+        let units = parts
+            .filter(|(part, _)| *part != "1")
+            .map(|(part, sign)| {
+                let (name, exponent) = part.split_once('^').unwrap_or((part, "1"));
+                let unit = Unit::find(name)
+                    .ok_or_else(|| CalcError::CallingNonexistentVariable(name.to_string()))?;
+                let exponent = exponent.parse::<i8>().map_err(|_| CalcError::SyntaxError)?;
+                Ok((unit, exponent.checked_mul(sign).ok_or(CalcError::Overflow)?))
+            })
+            .collect::<Result<Units, CalcError>>()?;
+
+        Ok(Quantity { amount, units })
+    }
+
+    /// Величина без единиц. Нужна, чтобы умножать и делить величины на числа.
+    fn plain(amount: Decimal) -> Self {
+        Quantity {
+            amount,
+            units: Vec::new(),
+        }
+    }
+
+    /// Переводит значение в величину. Число становится величиной без единиц.
+    pub fn from_value(value: &Value, name: &str) -> Result<Self, CalcError> {
+        match value {
+            Value::Quantity(quantity) => Ok(quantity.clone()),
+            value => value.real(name).map(Quantity::plain),
+        }
+    }
+
+    /// Величина без единиц становится обычным числом.
+    #[must_use]
+    pub fn into_value(self) -> Value {
+        match self.units.is_empty() {
+            true => Value::Real(self.amount.normalize()),
+            false => Value::Quantity(Quantity {
+                amount: self.amount.normalize(),
+                ..self
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn dimension(&self) -> Result<Dimension, CalcError> {
+        dimension(&self.units)
+    }
+
+    /// Запись единиц величины: `km/h`, `m^3/(kg*s^2)`, `s^-1`. У безразмерной величины - `1`.
+    #[must_use]
+    pub fn unit(&self) -> String {
+        format_units(&self.units)
+    }
+
+    #[must_use]
+    pub fn neg(&self) -> Self {
+        Quantity {
+            amount: -self.amount,
+            units: self.units.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn abs(&self) -> Self {
+        Quantity {
+            amount: self.amount.abs(),
+            units: self.units.clone(),
+        }
+    }
+
+    pub fn percent(&self) -> Result<Self, CalcError> {
+        self.checked_div(&Quantity::plain(Decimal::ONE_HUNDRED))
+    }
+
+    /// Сумма в единицах левой величины. Температура в градусах справа считается
+    /// абсолютной (`0 K + 20 degC` равно `293.15 K`), иначе - разностью температур.
+    pub fn checked_add(&self, rhs: &Quantity) -> Result<Self, CalcError> {
+        self.check_dimension(rhs)?;
+        let rhs = rhs.amount_in(&self.units, rhs.is_affine())?;
+        self.with_amount(self.amount.checked_add(rhs))
+    }
+
+    pub fn checked_sub(&self, rhs: &Quantity) -> Result<Self, CalcError> {
+        self.check_dimension(rhs)?;
+        let rhs = rhs.amount_in(&self.units, rhs.is_affine())?;
+        self.with_amount(self.amount.checked_sub(rhs))
+    }
+
+    pub fn checked_mul(&self, rhs: &Quantity) -> Result<Self, CalcError> {
+        self.combine(rhs, 1)
+    }
+
+    pub fn checked_div(&self, rhs: &Quantity) -> Result<Self, CalcError> {
+        if rhs.amount.is_zero() {
+            return Err(CalcError::DivisionByZero);
+        }
+        self.combine(rhs, -1)
+    }
+
+    /// Остаток в единицах левой величины: `7 m mod 2 m` равно `1 m`.
+    pub fn checked_rem(&self, rhs: &Quantity) -> Result<Self, CalcError> {
+        self.check_dimension(rhs)?;
+        let rhs = rhs.amount_in(&self.units, false)?;
+        if rhs.is_zero() {
+            return Err(CalcError::ModuloByZero);
+        }
+        self.with_amount(self.amount.checked_rem(rhs))
+    }
+
+    /// Целая часть отношения однородных величин - обычное число: `7 m div 2 m` равно `3`.
+    pub fn checked_int_div(&self, rhs: &Quantity) -> Result<Self, CalcError> {
+        self.check_dimension(rhs)?;
+        let rhs = rhs.amount_in(&self.units, false)?;
+        if rhs.is_zero() {
+            return Err(CalcError::DivisionByZero);
+        }
+        let quotient = self.amount.checked_div(rhs).ok_or(CalcError::Overflow)?;
+        Ok(Quantity::plain(quotient.trunc()))
+    }
+
+    /// Степень с безразмерным показателем. Показатели степеней единиц должны остаться целыми.
+    pub fn pow(&self, exponent: &Quantity) -> Result<Self, CalcError> {
+        if !exponent.units.is_empty() {
+            return Err(CalcError::UnitMismatch(exponent.unit(), format_units(&[])));
+        }
+        let base = self.absolute()?;
+        let units = self.powers(&base, |power| power.checked_mul(exponent.amount))?;
+        let amount = Number::pow(&base.amount, &exponent.amount)?;
+
+        Quantity { amount, units }.simplified()
+    }
+
+    /// Корень степени `degree`: `sqrt(9 m^2)` равно `3 m`.
+    pub fn root(&self, degree: Decimal) -> Result<Self, CalcError> {
+        let base = self.absolute()?;
+        let units = self.powers(&base, |power| power.checked_div(degree))?;
+        let amount = base.amount.nth_root(degree)?;
+
+        Quantity { amount, units }.simplified()
+    }
+
+    /// Переводит величину в единицы `target`. Числовое значение `target` не важно.
+    /// Температура переводится по абсолютной шкале: `100 degC to degF` равно `212 degF`.
+    pub fn convert(&self, target: &Quantity) -> Result<Self, CalcError> {
+        if target.units.is_empty() {
+            return Err(CalcError::ExpectedUnit(
+                target.amount.normalize().to_string(),
+            ));
+        }
+        self.check_dimension(target)?;
+        let amount = self.amount_in(&target.units, true)?;
+        Ok(Quantity {
+            amount,
+            units: target.units.clone(),
+        })
+    }
+
+    /// Новые показатели степеней единиц `base`. Если показатель получается дробным,
+    /// возвращается `CalcError::InvalidUnitPower`.
+    fn powers(
+        &self, base: &Quantity, power: impl Fn(Decimal) -> Option<Decimal>,
+    ) -> Result<Units, CalcError> {
+        base.units
+            .iter()
+            .map(|(unit, exponent)| {
+                power(Decimal::from(*exponent))
+                    .filter(|power| power.fract().is_zero())
+                    .and_then(|power| power.to_i8())
+                    .map(|power| (unit.clone(), power))
+                    .ok_or_else(|| CalcError::InvalidUnitPower(self.unit()))
+            })
+            .collect()
+    }
+
+    fn with_amount(&self, amount: Option<Decimal>) -> Result<Self, CalcError> {
+        Ok(Quantity {
+            amount: amount.ok_or(CalcError::Overflow)?,
+            units: self.units.clone(),
+        })
+    }
+
+    /// Есть ли у шкалы единицы сдвиг, как у градусов Цельсия.
+    fn is_affine(&self) -> bool {
+        !offset(&self.units).is_zero()
+    }
+
+    fn check_dimension(&self, rhs: &Quantity) -> Result<(), CalcError> {
+        match self.dimension()? == rhs.dimension()? {
+            true => Ok(()),
+            false => Err(CalcError::UnitMismatch(self.unit(), rhs.unit())),
+        }
+    }
+
+    /// Значение величины в единицах `units` той же размерности.
+    /// При `absolute` учитывается сдвиг шкал.
+    fn amount_in(&self, units: &[(Unit, i8)], absolute: bool) -> Result<Decimal, CalcError> {
+        let ratio = factor(&self.units)?.checked_div(factor(units)?)?;
+        if !absolute {
+            return scale(self.amount, ratio);
+        }
+
+        let amount = self
+            .amount
+            .checked_add(offset(&self.units))
+            .ok_or(CalcError::Overflow)?;
+        scale(amount, ratio)?
+            .checked_sub(offset(units))
+            .ok_or(CalcError::Overflow)
+    }
+
+    /// Температура в градусах переводится в кельвины, остальные величины не меняются.
+    fn absolute(&self) -> Result<Self, CalcError> {
+        match self.is_affine() {
+            true => {
+                let unit = Unit::coherent(self.dimension()?).ok_or(CalcError::UnknownError)?;
+                self.convert(&Quantity::new(Decimal::ONE, unit))
+            }
+            false => Ok(self.clone()),
+        }
+    }
+
+    /// Произведение при `sign = 1` и частное при `sign = -1`.
+    /// Умножение на число сохраняет единицы, даже если это градусы: `2 * 10 degC` равно `20 degC`.
+    fn combine(&self, rhs: &Quantity, sign: i8) -> Result<Self, CalcError> {
+        let amount = |left: Decimal, right: Decimal| match sign {
+            1 => left.checked_mul(right),
+            _ => left.checked_div(right),
+        };
+        if rhs.units.is_empty() {
+            return self.with_amount(amount(self.amount, rhs.amount));
+        }
+        if self.units.is_empty() && sign == 1 {
+            return rhs.with_amount(amount(self.amount, rhs.amount));
+        }
+
+        let (left, right) = (self.absolute()?, rhs.absolute()?);
+        let mut amount = amount(left.amount, right.amount).ok_or(CalcError::Overflow)?;
+        let mut units = left.units;
+        for (unit, power) in right.units {
+            let power = power.checked_mul(sign).ok_or(CalcError::Overflow)?;
+            let same = units
+                .iter()
+                .position(|(other, _)| other.name() == unit.name())
+                .or_else(|| {
+                    units
+                        .iter()
+                        .position(|(other, _)| other.dimension() == unit.dimension())
+                });
+            match same {
+                Some(index) => {
+                    let ratio = unit
+                        .factor()
+                        .checked_div(units[index].0.factor())?
+                        .checked_pow(Rational::from(Decimal::from(power)))?;
+                    amount = scale(amount, ratio)?;
+                    units[index].1 = units[index]
+                        .1
+                        .checked_add(power)
+                        .ok_or(CalcError::Overflow)?;
+                }
+                None => units.push((unit, power)),
+            }
+        }
+        units.retain(|(_, power)| *power != 0);
+
+        Quantity { amount, units }.simplified()
+    }
+
+    /// Безразмерная величина становится числом, а произведение когерентных единиц СИ
+    /// заменяется единицей с собственным именем.
+    fn simplified(self) -> Result<Self, CalcError> {
+        let dimension = self.dimension()?;
+        if dimension == DIMENSIONLESS {
+            return scale(self.amount, factor(&self.units)?).map(Quantity::plain);
+        }
+        if self.units.len() > 1 && factor(&self.units)? == Rational::from(Decimal::ONE) {
+            if let Some(unit) = Unit::coherent(dimension) {
+                return Ok(Quantity::new(self.amount, unit));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// `3 km`, `9.81 m/s^2`.
+impl Display for Quantity {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        write!(fmt, "{} {}", self.amount, self.unit())
+    }
+}
+
+/// Размерность произведения единиц. Показатель, не поместившийся в `i8`, даёт
+/// `CalcError::Overflow`: так `(1 L)^50` - ошибка, а не паника.
+fn dimension(units: &[(Unit, i8)]) -> Result<Dimension, CalcError> {
+    let mut result = DIMENSIONLESS;
+    for (unit, power) in units {
+        for (total, base) in result.iter_mut().zip(unit.dimension()) {
+            *total = base
+                .checked_mul(*power)
+                .and_then(|power| total.checked_add(power))
+                .ok_or(CalcError::Overflow)?;
+        }
+    }
+    Ok(result)
+}
+
+/// Во сколько раз произведение единиц больше когерентной единицы СИ.
+fn factor(units: &[(Unit, i8)]) -> Result<Rational, CalcError> {
+    units
+        .iter()
+        .try_fold(Rational::from(Decimal::ONE), |result, (unit, power)| {
+            result.checked_mul(
+                unit.factor()
+                    .checked_pow(Rational::from(Decimal::from(*power)))?,
+            )
+        })
+}
+
+/// Сдвиг шкалы есть только у единицы температуры в первой степени, записанной отдельно.
+fn offset(units: &[(Unit, i8)]) -> Decimal {
+    match units {
+        [(unit, 1)] => unit.offset(),
+        _ => Decimal::ZERO,
+    }
+}
+
+/// Умножает `amount` на точную дробь `ratio`.
+fn scale(amount: Decimal, ratio: Rational) -> Result<Decimal, CalcError> {
+    amount
+        .checked_mul(ratio.numer())
+        .and_then(|result| result.checked_div(ratio.denom()))
+        .or_else(|| amount.checked_mul(ratio.to_decimal()))
+        .ok_or(CalcError::Overflow)
+}
+
+fn format_units(units: &[(Unit, i8)]) -> String {
+    let join = |units: &[(Unit, i8)], sign: i16| {
+        units
+            .iter()
+            .map(|(unit, power)| match i16::from(*power) * sign {
+                1 => unit.name().to_string(),
+                power => format!("{}^{power}", unit.name()),
+            })
+            .collect::<Vec<String>>()
+            .join("*")
+    };
+    let (numer, denom): (Units, Units) = units.iter().cloned().partition(|(_, power)| *power > 0);
+
+    match (numer.is_empty(), denom.len()) {
+        (true, 0) => String::from("1"),
+        (true, _) => join(&denom, 1),
+        (false, 0) => join(&numer, 1),
+        (false, 1) => format!("{}/{}", join(&numer, 1), join(&denom, -1)),
+        (false, _) => format!("{}/({})", join(&numer, 1), join(&denom, -1)),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct QuantityRepr {
+    amount: Decimal,
+    unit: String,
+}
+
+impl From<Quantity> for QuantityRepr {
+    fn from(quantity: Quantity) -> Self {
+        QuantityRepr {
+            unit: quantity.unit(),
+            amount: quantity.amount,
+        }
+    }
+}
+
+impl TryFrom<QuantityRepr> for Quantity {
+    type Error = CalcError;
+
+    fn try_from(repr: QuantityRepr) -> Result<Self, Self::Error> {
+        Quantity::parse(repr.amount, &repr.unit)
+    }
+}
@@ -0,0 +1,215 @@
+//! Точные дроби для рационального режима.
+//!
+//! Числитель и знаменатель - целые `Decimal`, поэтому любая дробь переводится в `Decimal`
+//! без переполнения. Если результат операции не помещается в дробь, операция возвращает
+//! `CalcError::Overflow`, а вызывающий код повторяет её над `Decimal`.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Error, Formatter};
+use std::ops::Neg;
+
+use crate::errors::CalcError;
+
+/// Ограничение на количество шагов разложения в цепную дробь.
+const MAX_CONTINUED_FRACTION_STEPS: usize = 128;
+
+/// Несократимая дробь `numer / denom` с положительным знаменателем.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rational {
+    numer: Decimal,
+    denom: Decimal,
+}
+
+impl Rational {
+    /// Создаёт дробь из целых `numer` и `denom` и сокращает её.
+    pub fn new(numer: Decimal, denom: Decimal) -> Result<Self, CalcError> {
+        if !numer.fract().is_zero() || !denom.fract().is_zero() {
+            return Err(CalcError::MathError);
+        }
+        if denom.is_zero() {
+            return Err(CalcError::DivisionByZero);
+        }
+        Ok(Rational::reduced(numer, denom))
+    }
+
+    /// Ближайшая к `value` дробь со знаменателем не больше `max_denom`.
+    /// Берётся последняя подходящая дробь цепной дроби, знаменатель которой ещё не превышает `max_denom`.
+    pub fn approximate(value: Decimal, max_denom: Decimal) -> Result<Self, CalcError> {
+        let (mut numer, mut prev_numer) = (Decimal::ONE, Decimal::ZERO);
+        let (mut denom, mut prev_denom) = (Decimal::ZERO, Decimal::ONE);
+        let mut rest = value;
+
+        for _ in 0..MAX_CONTINUED_FRACTION_STEPS {
+            let term = rest.floor();
+            let next = term
+                .checked_mul(numer)
+                .and_then(|n| n.checked_add(prev_numer))
+                .zip(
+                    term.checked_mul(denom)
+                        .and_then(|d| d.checked_add(prev_denom)),
+                );
+            let Some((next_numer, next_denom)) = next else {
+                break;
+            };
+            if next_denom > max_denom {
+                break;
+            }
+            (prev_numer, numer) = (numer, next_numer);
+            (prev_denom, denom) = (denom, next_denom);
+
+            let fract = rest - term;
+            match Decimal::ONE.checked_div(fract) {
+                Some(inverse) if !fract.is_zero() => rest = inverse,
+                _ => break,
+            }
+        }
+
+        Rational::new(numer, denom)
+    }
+
+    #[must_use]
+    pub fn numer(&self) -> Decimal {
+        self.numer
+    }
+
+    #[must_use]
+    pub fn denom(&self) -> Decimal {
+        self.denom
+    }
+
+    /// Значение дроби, округлённое до точности `Decimal`.
+    #[must_use]
+    pub fn to_decimal(self) -> Decimal {
+        (self.numer / self.denom).normalize()
+    }
+
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        self.denom == Decimal::ONE
+    }
+
+    #[must_use]
+    pub fn abs(self) -> Self {
+        Rational {
+            numer: self.numer.abs(),
+            denom: self.denom,
+        }
+    }
+
+    pub fn checked_add(self, rhs: Rational) -> Result<Self, CalcError> {
+        let numer = add(mul(self.numer, rhs.denom)?, mul(rhs.numer, self.denom)?)?;
+        Rational::new(numer, mul(self.denom, rhs.denom)?)
+    }
+
+    pub fn checked_sub(self, rhs: Rational) -> Result<Self, CalcError> {
+        self.checked_add(-rhs)
+    }
+
+    pub fn checked_mul(self, rhs: Rational) -> Result<Self, CalcError> {
+        Rational::new(mul(self.numer, rhs.numer)?, mul(self.denom, rhs.denom)?)
+    }
+
+    pub fn checked_div(self, rhs: Rational) -> Result<Self, CalcError> {
+        Rational::new(mul(self.numer, rhs.denom)?, mul(self.denom, rhs.numer)?)
+    }
+
+    /// Целая часть частного, округлённая к нулю, как у `div` над `Decimal`.
+    pub fn checked_int_div(self, rhs: Rational) -> Result<Self, CalcError> {
+        let quotient = self.checked_div(rhs)?;
+        let remainder = quotient.numer % quotient.denom;
+        Rational::new((quotient.numer - remainder) / quotient.denom, Decimal::ONE)
+    }
+
+    /// Остаток со знаком делимого, как у `mod` над `Decimal`.
+    pub fn checked_rem(self, rhs: Rational) -> Result<Self, CalcError> {
+        if rhs.numer.is_zero() {
+            return Err(CalcError::ModuloByZero);
+        }
+        self.checked_sub(rhs.checked_mul(self.checked_int_div(rhs)?)?)
+    }
+
+    /// Сокращает дробь из целых `numer` и ненулевого `denom`.
+    fn reduced(numer: Decimal, denom: Decimal) -> Self {
+        let divisor = gcd(numer, denom);
+        let (mut numer, mut denom) = ((numer / divisor).normalize(), (denom / divisor).normalize());
+        if denom.is_sign_negative() {
+            (numer, denom) = (-numer, -denom);
+        }
+        Rational { numer, denom }
+    }
+
+    /// Возводит дробь в целую степень. Дробная степень не точна и возвращает ошибку.
+    pub fn checked_pow(self, exponent: Rational) -> Result<Self, CalcError> {
+        if !exponent.is_integer() {
+            return Err(CalcError::MathError);
+        }
+        if self.numer.is_zero() && exponent.numer.is_sign_negative() {
+            return Err(CalcError::DivisionByZero);
+        }
+
+        let (mut result, mut base) = (Rational::from(Decimal::ONE), self);
+        let mut rest = exponent.numer.abs();
+        while !rest.is_zero() {
+            if !(rest % Decimal::TWO).is_zero() {
+                result = result.checked_mul(base)?;
+            }
+            rest = (rest / Decimal::TWO).trunc();
+            if !rest.is_zero() {
+                base = base.checked_mul(base)?;
+            }
+        }
+
+        match exponent.numer.is_sign_negative() {
+            true => Rational::from(Decimal::ONE).checked_div(result),
+            false => Ok(result),
+        }
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational::reduced(-self.numer, self.denom)
+    }
+}
+
+/// Десятичная дробь переводится в обыкновенную точно: `0.25` - это `1/4`.
+impl From<Decimal> for Rational {
+    fn from(value: Decimal) -> Self {
+        let numer = Decimal::from_i128_with_scale(value.mantissa(), 0);
+        let denom = Decimal::from_i128_with_scale(10_i128.pow(value.scale()), 0);
+        Rational::reduced(numer, denom)
+    }
+}
+
+/// `1/3`, `-2/5`, `4`.
+impl Display for Rational {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match self.is_integer() {
+            true => write!(fmt, "{}", self.numer),
+            false => write!(fmt, "{}/{}", self.numer, self.denom),
+        }
+    }
+}
+
+/// Наибольший общий делитель двух целых чисел.
+fn gcd(a: Decimal, b: Decimal) -> Decimal {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while !b.is_zero() {
+        (a, b) = (b, a % b);
+    }
+    match a.is_zero() {
+        true => Decimal::ONE,
+        false => a,
+    }
+}
+
+fn add(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_add(right).ok_or(CalcError::Overflow)
+}
+
+fn mul(left: Decimal, right: Decimal) -> Result<Decimal, CalcError> {
+    left.checked_mul(right).ok_or(CalcError::Overflow)
+}
@@ -0,0 +1,190 @@
+//! Реестр встроенных функций.
+//!
+//! Функция выражения `name(...)` сначала ищется здесь, а затем среди пользовательских функций.
+//! Программы, встраивающие интерпретатор, могут добавлять свои функции через `Interpreter::registry`:
+//!
+//! ```
+//! use interpreter::{config::Config, interpreter::Interpreter, registry::NativeFunction};
+//! use rust_decimal::Decimal;
+//!
+//! let mut interpreter = Interpreter::new(Config::default()).unwrap();
+//! interpreter
+//!     .registry
+//!     .register(
+//!         NativeFunction::real("twice", 1..=1, |args, _| Ok(args[0] * Decimal::TWO))
+//!             .with_doc("twice(number) - doubles the number"),
+//!     )
+//!     .unwrap();
+//! ```
+//!
+//! Функции, созданные через `NativeFunction::real`, принимают только вещественные числа.
+//! Чтобы принимать и комплексные, функция создаётся через `NativeFunction::new`:
+//!
+//! ```
+//! use interpreter::{registry::NativeFunction, value::Value};
+//!
+//! let conj = NativeFunction::new("conj", 1..=1, |args, _| {
+//!     Ok(Value::from(args[0].to_complex()?.conj()))
+//! });
+//! ```
+
+use rust_decimal::Decimal;
+use std::{
+    fmt::{Debug, Error, Formatter},
+    ops::RangeInclusive,
+    sync::Arc,
+};
+
+use crate::{
+    ast::func::register_builtins, errors::CalcError, interpreter::Interpreter, value::Value,
+};
+
+/// Тело функции: получает вычисленные аргументы вместе с подставленными значениями по умолчанию.
+pub type NativeBody = dyn Fn(&[Value], &Interpreter) -> Result<Value, CalcError> + Send + Sync;
+
+/// Функция, реализованная на Rust.
+///
+/// * `name` - имя, по которому функция вызывается.
+/// * `arity` - допустимое количество аргументов.
+/// * `defaults` - значения необязательных аргументов, начиная с первого необязательного.
+/// * `doc` - краткое описание для пользователя.
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: RangeInclusive<usize>,
+    pub defaults: Vec<Value>,
+    pub doc: String,
+    body: Arc<NativeBody>,
+}
+
+impl NativeFunction {
+    #[must_use]
+    pub fn new(
+        name: &str, arity: RangeInclusive<usize>,
+        body: impl Fn(&[Value], &Interpreter) -> Result<Value, CalcError> + Send + Sync + 'static,
+    ) -> Self {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            defaults: Vec::new(),
+            doc: String::new(),
+            body: Arc::new(body),
+        }
+    }
+
+    /// Создаёт функцию вещественных чисел.
+    /// Комплексный аргумент приводит к ошибке `CalcError::ComplexNotSupported`.
+    /// Функция считает в `Decimal`, а результат переводится в числа режима из настроек.
+    #[must_use]
+    pub fn real(
+        name: &str, arity: RangeInclusive<usize>,
+        body: impl Fn(&[Decimal], &Interpreter) -> Result<Decimal, CalcError> + Send + Sync + 'static,
+    ) -> Self {
+        let function_name = name.to_string();
+        NativeFunction::new(name, arity, move |args, calc| {
+            let args = args
+                .iter()
+                .map(|arg| arg.real(&function_name))
+                .collect::<Result<Vec<Decimal>, CalcError>>()?;
+            body(&args, calc).map(|result| calc.config.number(result))
+        })
+    }
+
+    #[must_use]
+    pub fn with_defaults(mut self, defaults: Vec<Value>) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    #[must_use]
+    pub fn with_doc(mut self, doc: &str) -> Self {
+        self.doc = doc.to_string();
+        self
+    }
+
+    /// Возвращает `CalcError::IncorrectNumberOfArguments`, если функция не принимает `len` аргументов.
+    pub fn check_arity(&self, len: usize) -> Result<(), CalcError> {
+        if !self.arity.contains(&len) {
+            let expected = len.clamp(*self.arity.start(), *self.arity.end());
+            return Err(CalcError::IncorrectNumberOfArguments(expected, len));
+        }
+        Ok(())
+    }
+
+    /// Вызывает функцию, дополнив `args` значениями по умолчанию.
+    pub fn call(&self, args: &[Value], calc: &Interpreter) -> Result<Value, CalcError> {
+        self.check_arity(args.len())?;
+
+        let mut args = args.to_vec();
+        let given_optional = args.len() - self.arity.start();
+        args.extend(self.defaults.iter().skip(given_optional).cloned());
+
+        (self.body)(&args, calc)
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        write!(fmt, "{}{:?}: {}", self.name, self.arity, self.doc)
+    }
+}
+
+/// Набор функций, доступных из выражений.
+/// `Registry::default()` содержит все встроенные функции калькулятора.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    functions: Vec<NativeFunction>,
+}
+
+impl Registry {
+    /// Создаёт реестр без единой функции.
+    #[must_use]
+    pub fn empty() -> Self {
+        Registry {
+            functions: Vec::new(),
+        }
+    }
+
+    /// Добавляет функцию. Функция с тем же именем заменяется.
+    /// Возвращает `CalcError::MissingDefaultValues`, если у функции не по одному значению
+    /// по умолчанию на каждый необязательный аргумент.
+    pub fn register(&mut self, function: NativeFunction) -> Result<&mut Self, CalcError> {
+        if function.defaults.len() != function.arity.end() - function.arity.start() {
+            return Err(CalcError::MissingDefaultValues(function.name));
+        }
+        Ok(self.insert(function))
+    }
+
+    /// Добавляет функцию без проверки значений по умолчанию: для встроенных функций,
+    /// которые проверяются тестами.
+    pub(crate) fn insert(&mut self, function: NativeFunction) -> &mut Self {
+        match self.functions.iter_mut().find(|f| f.name == function.name) {
+            Some(existing) => *existing = function,
+            None => self.functions.push(function),
+        }
+        self
+    }
+
+    /// Удаляет функцию `name` и возвращает её.
+    pub fn unregister(&mut self, name: &str) -> Option<NativeFunction> {
+        let index = self.functions.iter().position(|f| f.name == name)?;
+        Some(self.functions.remove(index))
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&NativeFunction> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NativeFunction> {
+        self.functions.iter()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry::empty();
+        register_builtins(&mut registry);
+        registry
+    }
+}
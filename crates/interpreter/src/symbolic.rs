@@ -0,0 +1,166 @@
+//! Символьное выражение как значение: результат `diff(x^2; x)` и `simplify(x + x)`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Error, Formatter};
+
+use crate::{
+    ast::{diff, expr::Expr},
+    errors::CalcError,
+    function::MAX_CALL_DEPTH,
+    interpreter::Interpreter,
+    traits::GetResult,
+    value::Value,
+};
+
+/// Выражение, которое не вычисляется, а показывается пользователю: `2 * x`.
+/// Сохраняется объектом `{"expression": ...}`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Expression {
+    expression: Box<Expr>,
+}
+
+impl Expression {
+    #[must_use]
+    pub fn new(expression: Expr) -> Self {
+        Expression {
+            expression: Box::new(expression),
+        }
+    }
+
+    #[must_use]
+    pub fn expr(&self) -> &Expr {
+        &self.expression
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        write!(fmt, "{}", self.expression)
+    }
+}
+
+/// Имена переменных, которые встречаются в выражении.
+pub(crate) fn variables(expr: &Expr) -> Vec<String> {
+    let mut names = Vec::new();
+    collect_variables(expr, &mut names);
+    names
+}
+
+fn collect_variables(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Variable(name) => names.push(name.clone()),
+        Expr::Op(left, _, right) | Expr::Equation(left, right) => {
+            collect_variables(left, names);
+            collect_variables(right, names);
+        }
+        Expr::UnaryOp(_, value) | Expr::Spanned(_, value) | Expr::Simplify(value) => {
+            collect_variables(value, names);
+        }
+        Expr::Call(_, exprs) | Expr::Matrix(exprs) | Expr::Solve(exprs) => {
+            exprs.iter().for_each(|expr| collect_variables(expr, names));
+        }
+        Expr::Index(target, indices) => {
+            collect_variables(target, names);
+            indices
+                .iter()
+                .for_each(|expr| collect_variables(expr, names));
+        }
+        Expr::Diff(body, _, point) => {
+            collect_variables(body, names);
+            if let Some(point) = point {
+                collect_variables(point, names);
+            }
+        }
+        Expr::Integrate(body, var, from, to) => {
+            names.extend(variables(body).into_iter().filter(|name| name != var));
+            collect_variables(from, names);
+            collect_variables(to, names);
+        }
+        Expr::Derivative(body, var, at) => {
+            names.extend(variables(body).into_iter().filter(|name| name != var));
+            collect_variables(at, names);
+        }
+        Expr::Sum(index, from, to, body) | Expr::Product(index, from, to, body) => {
+            collect_variables(from, names);
+            collect_variables(to, names);
+            names.extend(variables(body).into_iter().filter(|name| name != index));
+        }
+        Expr::Number(_) | Expr::Literal(_) | Expr::Value(_) | Expr::Error(_) => {}
+    }
+}
+
+/// Подставляет тела пользовательских функций и выражения из переменных, раскрывает
+/// вложенные `diff` и `simplify`. Переменная `bound` остаётся именем, даже если в ней
+/// хранится выражение: по ней берётся производная.
+pub(crate) fn expand(
+    interpreter: &Interpreter, expr: &Expr, bound: Option<&str>, depth: usize,
+) -> Result<Expr, CalcError> {
+    let expand_all = |exprs: &[Box<Expr>]| {
+        exprs
+            .iter()
+            .map(|expr| expand(interpreter, expr, bound, depth).map(Box::new))
+            .collect::<Result<Vec<Box<Expr>>, CalcError>>()
+    };
+
+    match expr {
+        Expr::Variable(name) if Some(name.as_str()) != bound => {
+            match interpreter.variables.get_result(name) {
+                Some(Value::Expression(expression)) => {
+                    expand(interpreter, expression.expr(), bound, depth)
+                }
+                _ => Ok(expr.clone()),
+            }
+        }
+        Expr::Value(Value::Expression(expression)) => {
+            expand(interpreter, expression.expr(), bound, depth)
+        }
+        Expr::Op(left, op, right) => Ok(Expr::Op(
+            Box::new(expand(interpreter, left, bound, depth)?),
+            *op,
+            Box::new(expand(interpreter, right, bound, depth)?),
+        )),
+        Expr::UnaryOp(op, value) => Ok(Expr::UnaryOp(
+            *op,
+            Box::new(expand(interpreter, value, bound, depth)?),
+        )),
+        Expr::Call(name, args) if interpreter.registry.get(name).is_none() => {
+            let function = interpreter
+                .functions
+                .get_result(name)
+                .ok_or(CalcError::CallingNonexistentFunction(name.to_string()))?;
+            if args.len() != function.params.len() {
+                return Err(CalcError::IncorrectNumberOfArguments(
+                    function.params.len(),
+                    args.len(),
+                ));
+            }
+            if depth >= MAX_CALL_DEPTH {
+                return Err(CalcError::RecursionLimitExceeded);
+            }
+            let args = expand_all(args)?
+                .into_iter()
+                .map(|arg| *arg)
+                .collect::<Vec<Expr>>();
+            let body = function.body.replace(&function.params, &args);
+            expand(interpreter, &body, bound, depth + 1)
+        }
+        Expr::Call(name, args) => {
+            if let Some(native) = interpreter.registry.get(name) {
+                native.check_arity(args.len())?;
+            }
+            Ok(Expr::Call(name.clone(), expand_all(args)?))
+        }
+        Expr::Matrix(elements) => Ok(Expr::Matrix(expand_all(elements)?)),
+        Expr::Diff(body, var, point) => {
+            let derivative = diff::derivative(interpreter, body, var)?;
+            let derivative = match point {
+                Some(point) => derivative.replace(std::slice::from_ref(var), &[*point.clone()]),
+                None => derivative,
+            };
+            expand(interpreter, &derivative, bound, depth)
+        }
+        Expr::Simplify(value) => expand(interpreter, value, bound, depth),
+        Expr::Spanned(_, expr) => expand(interpreter, expr, bound, depth),
+        _ => Ok(expr.clone()),
+    }
+}
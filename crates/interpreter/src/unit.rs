@@ -0,0 +1,250 @@
+//! Единицы измерения.
+//!
+//! Имя, которое не является ни переменной, ни константой, ищется среди единиц: `km`, `h`, `degC`.
+//! Единица задаётся множителем и сдвигом относительно когерентной единицы СИ:
+//! `значение в СИ = (значение + offset) * factor`. Сдвиг есть только у градусов Цельсия и Фаренгейта.
+
+use rust_decimal::Decimal;
+use std::{str::FromStr, sync::OnceLock};
+
+use crate::rational::Rational;
+
+/// Показатели степеней основных величин: длины, массы, времени, силы тока, температуры,
+/// количества вещества, силы света и объёма информации.
+pub type Dimension = [i8; 8];
+
+pub const DIMENSIONLESS: Dimension = [0; 8];
+
+const LENGTH: Dimension = [1, 0, 0, 0, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0, 0, 0, 0];
+const CURRENT: Dimension = [0, 0, 0, 1, 0, 0, 0, 0];
+const TEMPERATURE: Dimension = [0, 0, 0, 0, 1, 0, 0, 0];
+const AMOUNT: Dimension = [0, 0, 0, 0, 0, 1, 0, 0];
+const LUMINOSITY: Dimension = [0, 0, 0, 0, 0, 0, 1, 0];
+const DATA: Dimension = [0, 0, 0, 0, 0, 0, 0, 1];
+
+const FREQUENCY: Dimension = [0, 0, -1, 0, 0, 0, 0, 0];
+const VOLUME: Dimension = [3, 0, 0, 0, 0, 0, 0, 0];
+const SPEED: Dimension = [1, 0, -1, 0, 0, 0, 0, 0];
+const FORCE: Dimension = [1, 1, -2, 0, 0, 0, 0, 0];
+const PRESSURE: Dimension = [-1, 1, -2, 0, 0, 0, 0, 0];
+const ENERGY: Dimension = [2, 1, -2, 0, 0, 0, 0, 0];
+const POWER: Dimension = [2, 1, -3, 0, 0, 0, 0, 0];
+const CHARGE: Dimension = [0, 0, 1, 1, 0, 0, 0, 0];
+const VOLTAGE: Dimension = [2, 1, -3, -1, 0, 0, 0, 0];
+const RESISTANCE: Dimension = [2, 1, -3, -2, 0, 0, 0, 0];
+
+/// Десятичные приставки и показатели степени десяти. Двухбуквенная `da` стоит раньше `d`.
+const SI_PREFIXES: [(&str, i32); 20] = [
+    ("Y", 24),
+    ("Z", 21),
+    ("E", 18),
+    ("P", 15),
+    ("T", 12),
+    ("G", 9),
+    ("M", 6),
+    ("k", 3),
+    ("h", 2),
+    ("da", 1),
+    ("d", -1),
+    ("c", -2),
+    ("m", -3),
+    ("u", -6),
+    ("n", -9),
+    ("p", -12),
+    ("f", -15),
+    ("a", -18),
+    ("z", -21),
+    ("y", -24),
+];
+
+/// Двоичные приставки единиц информации и показатели степени двойки.
+const BINARY_PREFIXES: [(&str, u32); 6] = [
+    ("Ki", 10),
+    ("Mi", 20),
+    ("Gi", 30),
+    ("Ti", 40),
+    ("Pi", 50),
+    ("Ei", 60),
+];
+
+/// Единицы СИ с множителем 1, в которые сворачивается произведение единиц: `kg*m/s^2` - это `N`.
+const COHERENT: [&str; 15] = [
+    "m", "kg", "s", "A", "K", "mol", "cd", "bit", "N", "J", "W", "Pa", "C", "V", "Ohm",
+];
+
+/// Какие приставки принимает единица.
+#[derive(Clone, Copy, PartialEq)]
+enum Prefixes {
+    None,
+    Decimal,
+    /// Десятичные и двоичные: `kB`, `KiB`.
+    Binary,
+}
+
+/// Единица измерения с собственным именем: `m`, `km`, `degF`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+    name: String,
+    factor: Rational,
+    offset: Decimal,
+    dimension: Dimension,
+}
+
+impl Unit {
+    fn new(name: &str, factor: Rational, offset: Decimal, dimension: Dimension) -> Self {
+        Unit {
+            name: name.to_string(),
+            factor,
+            offset,
+            dimension,
+        }
+    }
+
+    /// Ищет единицу по имени, в том числе с приставкой: `km`, `MiB`, `kWh`.
+    #[must_use]
+    pub fn find(name: &str) -> Option<Unit> {
+        let definitions = definitions();
+        if let Some((unit, _)) = definitions.iter().find(|(unit, _)| unit.name == name) {
+            return Some(unit.clone());
+        }
+
+        definitions
+            .iter()
+            .filter(|(_, prefixes)| *prefixes != Prefixes::None)
+            .find_map(|(unit, prefixes)| {
+                let prefix = name.strip_suffix(unit.name.as_str())?;
+                let multiplier = prefix_multiplier(prefix, *prefixes)?;
+                let factor = unit.factor.checked_mul(multiplier).ok()?;
+                Some(Unit::new(name, factor, unit.offset, unit.dimension))
+            })
+    }
+
+    /// Когерентная единица СИ размерности `dimension`, если у неё есть собственное имя.
+    #[must_use]
+    pub fn coherent(dimension: Dimension) -> Option<Unit> {
+        COHERENT
+            .iter()
+            .filter_map(|name| Unit::find(name))
+            .find(|unit| unit.dimension == dimension)
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Во сколько раз единица больше когерентной единицы СИ той же размерности.
+    #[must_use]
+    pub fn factor(&self) -> Rational {
+        self.factor
+    }
+
+    #[must_use]
+    pub fn offset(&self) -> Decimal {
+        self.offset
+    }
+
+    #[must_use]
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+}
+
+/// Множитель приставки `prefix` или `None`, если единица её не принимает.
+fn prefix_multiplier(prefix: &str, prefixes: Prefixes) -> Option<Rational> {
+    if let Some((_, power)) = SI_PREFIXES.iter().find(|(name, _)| *name == prefix) {
+        let multiplier = Rational::from(Decimal::from(10_i128.pow(power.unsigned_abs())));
+        return match *power > 0 {
+            true => Some(multiplier),
+            false => Rational::from(Decimal::ONE).checked_div(multiplier).ok(),
+        };
+    }
+
+    BINARY_PREFIXES
+        .iter()
+        .find(|(name, _)| prefixes == Prefixes::Binary && *name == prefix)
+        .map(|(_, power)| Rational::from(Decimal::from(1_u64 << power)))
+}
+
+/// Встроенные единицы. Множитель задаётся дробью `numer / denom` десятичных записей.
+fn definitions() -> &'static [(Unit, Prefixes)] {
+    static DEFINITIONS: OnceLock<Vec<(Unit, Prefixes)>> = OnceLock::new();
+
+    DEFINITIONS.get_or_init(|| {
+        let unit = |name: &str, numer: &str, denom: &str, dimension: Dimension| {
+            let factor = Rational::from(Decimal::from_str(numer).expect("valid unit factor"))
+                .checked_div(Rational::from(
+                    Decimal::from_str(denom).expect("valid unit factor"),
+                ))
+                .expect("valid unit factor");
+            Unit::new(name, factor, Decimal::ZERO, dimension)
+        };
+
+        vec![
+            // СИ
+            (unit("m", "1", "1", LENGTH), Prefixes::Decimal),
+            (unit("g", "0.001", "1", MASS), Prefixes::Decimal),
+            (unit("gram", "0.001", "1", MASS), Prefixes::None),
+            (unit("t", "1000", "1", MASS), Prefixes::None),
+            (unit("s", "1", "1", TIME), Prefixes::Decimal),
+            (unit("min", "60", "1", TIME), Prefixes::None),
+            (unit("h", "3600", "1", TIME), Prefixes::None),
+            (unit("day", "86400", "1", TIME), Prefixes::None),
+            (unit("week", "604800", "1", TIME), Prefixes::None),
+            (unit("year", "31557600", "1", TIME), Prefixes::None),
+            (unit("A", "1", "1", CURRENT), Prefixes::Decimal),
+            (unit("K", "1", "1", TEMPERATURE), Prefixes::Decimal),
+            (unit("mol", "1", "1", AMOUNT), Prefixes::Decimal),
+            (unit("cd", "1", "1", LUMINOSITY), Prefixes::Decimal),
+            (unit("Hz", "1", "1", FREQUENCY), Prefixes::Decimal),
+            (unit("N", "1", "1", FORCE), Prefixes::Decimal),
+            (unit("Pa", "1", "1", PRESSURE), Prefixes::Decimal),
+            (unit("bar", "100000", "1", PRESSURE), Prefixes::Decimal),
+            (unit("atm", "101325", "1", PRESSURE), Prefixes::None),
+            (unit("J", "1", "1", ENERGY), Prefixes::Decimal),
+            (unit("Wh", "3600", "1", ENERGY), Prefixes::Decimal),
+            (unit("cal", "4.184", "1", ENERGY), Prefixes::Decimal),
+            (
+                unit("eV", "0.0000000000000000001602176634", "1", ENERGY),
+                Prefixes::Decimal,
+            ),
+            (unit("W", "1", "1", POWER), Prefixes::Decimal),
+            (unit("C", "1", "1", CHARGE), Prefixes::Decimal),
+            (unit("V", "1", "1", VOLTAGE), Prefixes::Decimal),
+            (unit("Ohm", "1", "1", RESISTANCE), Prefixes::Decimal),
+            (unit("L", "0.001", "1", VOLUME), Prefixes::Decimal),
+            (unit("l", "0.001", "1", VOLUME), Prefixes::Decimal),
+            // Британские и американские единицы
+            (unit("in", "0.0254", "1", LENGTH), Prefixes::None),
+            (unit("ft", "0.3048", "1", LENGTH), Prefixes::None),
+            (unit("yd", "0.9144", "1", LENGTH), Prefixes::None),
+            (unit("mi", "1609.344", "1", LENGTH), Prefixes::None),
+            (unit("oz", "0.028349523125", "1", MASS), Prefixes::None),
+            (unit("lb", "0.45359237", "1", MASS), Prefixes::None),
+            (unit("gal", "0.003785411784", "1", VOLUME), Prefixes::None),
+            (unit("mph", "0.44704", "1", SPEED), Prefixes::None),
+            // Температура
+            (
+                Unit::new(
+                    "degC",
+                    Rational::from(Decimal::ONE),
+                    Decimal::new(27315, 2),
+                    TEMPERATURE,
+                ),
+                Prefixes::None,
+            ),
+            (
+                Unit {
+                    offset: Decimal::new(45967, 2),
+                    ..unit("degF", "5", "9", TEMPERATURE)
+                },
+                Prefixes::None,
+            ),
+            // Информация
+            (unit("bit", "1", "1", DATA), Prefixes::Binary),
+            (unit("B", "8", "1", DATA), Prefixes::Binary),
+        ]
+    })
+}
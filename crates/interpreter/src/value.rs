@@ -0,0 +1,259 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Display, Error, Formatter};
+
+use crate::{
+    big::BigNumber,
+    complex::Complex,
+    errors::CalcError,
+    integer::{Base, Integer},
+    matrix::Matrix,
+    number::{format_float, Number},
+    quantity::Quantity,
+    rational::Rational,
+    symbolic::Expression,
+};
+
+/// Значение, которое получается при вычислении выражения.
+///
+/// Число с нулевой мнимой частью всегда хранится как `Value::Real`,
+/// поэтому `(1 + i)(1 - i)` равно вещественному `2`.
+/// Вещественные значения сохраняются так же, как и раньше, - одним числом,
+/// поэтому старые файлы с историей и переменными читаются без изменений.
+/// `Value::Rational` - точная дробь рационального режима, `Value::Float` и `Value::Big` -
+/// числа режимов `float` и `big`, `Value::Quantity` - число с единицами измерения,
+/// `Value::Integer` - целое число, записанное в другой системе счисления: `0xFF`,
+/// `Value::Matrix` - вектор или матрица, `Value::Expression` - выражение, например производная.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Real(Decimal),
+    Complex(Complex),
+    Rational(Rational),
+    Float(#[serde(with = "float")] f64),
+    Big(BigNumber),
+    Quantity(Quantity),
+    Integer(Integer),
+    Matrix(Matrix),
+    Expression(Expression),
+}
+
+impl Value {
+    /// Создаёт значение `re + im * i`.
+    #[must_use]
+    pub fn complex(re: Decimal, im: Decimal) -> Self {
+        match im.is_zero() {
+            true => Value::Real(re.normalize()),
+            false => Value::Complex(Complex::new(re.normalize(), im.normalize())),
+        }
+    }
+
+    /// Комплексные числа считаются в `Decimal`, поэтому `f64` и `BigNumber` переводятся в него.
+    pub fn to_complex(&self) -> Result<Complex, CalcError> {
+        match self {
+            Value::Real(re) => Ok(Complex::from(*re)),
+            Value::Rational(rational) => Ok(Complex::from(rational.to_decimal())),
+            Value::Float(value) => value.to_decimal().map(Complex::from),
+            Value::Big(value) => value.to_decimal().map(Complex::from),
+            Value::Complex(complex) => Ok(*complex),
+            Value::Integer(integer) => Ok(Complex::from(integer.to_decimal())),
+            Value::Quantity(_) => Err(CalcError::UnitNotSupported(String::from(
+                "Complex arithmetic",
+            ))),
+            Value::Matrix(_) => Err(CalcError::MatrixNotSupported(String::from(
+                "Complex arithmetic",
+            ))),
+            Value::Expression(_) => Err(CalcError::ExpressionNotSupported(String::from(
+                "Complex arithmetic",
+            ))),
+        }
+    }
+
+    /// Переводит точную дробь в `Decimal`. Остальные значения не меняются.
+    #[must_use]
+    pub fn inexact(self) -> Self {
+        match self {
+            Value::Rational(rational) => Value::Real(rational.to_decimal()),
+            value => value,
+        }
+    }
+
+    /// Переводит дробь, целое, `f64` и `BigNumber` в `Decimal`. Комплексные значения и величины
+    /// с единицами не меняются.
+    pub fn into_decimal(self) -> Result<Self, CalcError> {
+        match self {
+            Value::Float(value) => value.to_decimal().map(Value::Real),
+            Value::Big(value) => value.to_decimal().map(Value::Real),
+            Value::Integer(integer) => Ok(Value::Real(integer.to_decimal())),
+            value => Ok(value.inexact()),
+        }
+    }
+
+    /// Возвращает вещественное значение или `CalcError::ComplexNotSupported`,
+    /// если его принимает функция или оператор `name`, не определённые для комплексных чисел.
+    /// Для величин с единицами возвращается `CalcError::UnitNotSupported`.
+    /// Числа `f64` и `BigNumber` округляются до `Decimal`.
+    pub fn real(&self, name: &str) -> Result<Decimal, CalcError> {
+        match self {
+            Value::Real(re) => Ok(*re),
+            Value::Rational(rational) => Ok(rational.to_decimal()),
+            Value::Float(value) => value.to_decimal(),
+            Value::Big(value) => value.to_decimal(),
+            Value::Integer(integer) => Ok(integer.to_decimal()),
+            Value::Complex(_) => Err(CalcError::ComplexNotSupported(name.to_string())),
+            Value::Quantity(_) => Err(CalcError::UnitNotSupported(name.to_string())),
+            Value::Matrix(_) => Err(CalcError::MatrixNotSupported(name.to_string())),
+            Value::Expression(_) => Err(CalcError::ExpressionNotSupported(name.to_string())),
+        }
+    }
+
+    /// Модуль значения в `Decimal`. У величины с единицами - модуль числа в её единицах.
+    pub fn magnitude(&self) -> Result<Decimal, CalcError> {
+        match self {
+            Value::Quantity(quantity) => Ok(quantity.amount().abs()),
+            value => value.to_complex()?.abs(),
+        }
+    }
+
+    /// Запись значения, в которой целые числа показаны в системе счисления `base`.
+    /// Целое, записанное не в десятичной системе, сохраняет свою: `hex(255)` всегда `0xFF`.
+    #[must_use]
+    pub fn to_string_in(&self, base: Base) -> String {
+        let integer = match self {
+            Value::Matrix(matrix) => return matrix.format(|value| value.to_string_in(base)),
+            Value::Integer(integer) if integer.base() != Base::Decimal => None,
+            value => Integer::exact(value),
+        };
+        match integer {
+            Some(integer) if base != Base::Decimal => integer.with_base(base).to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Приводит вещественные значения к общему виду, чтобы выполнить над ними операцию:
+    /// целое уступает дроби, дробь - `Decimal`, `Decimal` - `f64`, а `f64` - `BigNumber`.
+    /// Комплексные значения и величины с единицами не меняются.
+    pub fn unify(left: Value, right: Value) -> Result<(Value, Value), CalcError> {
+        match (left.rank(), right.rank()) {
+            (Some(l), Some(r)) if l < r => Ok((left.promote(&right)?, right)),
+            (Some(l), Some(r)) if l > r => {
+                let right = right.promote(&left)?;
+                Ok((left, right))
+            }
+            _ => Ok((left, right)),
+        }
+    }
+
+    fn rank(&self) -> Option<u8> {
+        match self {
+            Value::Integer(_) => Some(0),
+            Value::Rational(_) => Some(1),
+            Value::Real(_) => Some(2),
+            Value::Float(_) => Some(3),
+            Value::Big(_) => Some(4),
+            Value::Complex(_) | Value::Quantity(_) | Value::Matrix(_) | Value::Expression(_) => {
+                None
+            }
+        }
+    }
+
+    /// Переводит вещественное значение в вид значения `like`, который старше.
+    fn promote(self, like: &Value) -> Result<Value, CalcError> {
+        let real = match self.inexact() {
+            Value::Integer(integer) if matches!(like, Value::Rational(_)) => {
+                return Ok(Value::Rational(Rational::from(integer.to_decimal())))
+            }
+            Value::Integer(integer) => integer.to_decimal(),
+            Value::Real(real) => real,
+            Value::Float(value) => match like {
+                Value::Big(big) => return BigNumber::from_f64(value, big.digits()).map(Value::Big),
+                _ => return Ok(Value::Float(value)),
+            },
+            value => return Ok(value),
+        };
+        match like {
+            Value::Big(big) => Ok(Value::Big(BigNumber::from_decimal(real, big.digits()))),
+            Value::Float(_) => Ok(Value::Float(f64::from_decimal(real))),
+            _ => Ok(Value::Real(real)),
+        }
+    }
+}
+
+impl From<Decimal> for Value {
+    fn from(re: Decimal) -> Self {
+        Value::Real(re)
+    }
+}
+
+impl From<Rational> for Value {
+    fn from(rational: Rational) -> Self {
+        Value::Rational(rational)
+    }
+}
+
+impl From<Integer> for Value {
+    fn from(integer: Integer) -> Self {
+        Value::Integer(integer)
+    }
+}
+
+impl From<Complex> for Value {
+    fn from(complex: Complex) -> Self {
+        Value::complex(complex.re, complex.im)
+    }
+}
+
+/// `3 + 4i`, `-2i`, `i`, `1/3`, `1e300`, `9.81 m/s^2`, `0xFF`, `[1; 2]`, `2 * x`.
+impl Display for Value {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        let complex = match self {
+            Value::Real(re) => return write!(fmt, "{re}"),
+            Value::Rational(rational) => return write!(fmt, "{rational}"),
+            Value::Float(value) => return write!(fmt, "{}", format_float(*value)),
+            Value::Big(value) => return write!(fmt, "{value}"),
+            Value::Quantity(quantity) => return write!(fmt, "{quantity}"),
+            Value::Integer(integer) => return write!(fmt, "{integer}"),
+            Value::Matrix(matrix) => return write!(fmt, "{matrix}"),
+            Value::Expression(expression) => return write!(fmt, "{expression}"),
+            Value::Complex(complex) => complex,
+        };
+
+        let im = match complex.im.abs() {
+            im if im == Decimal::ONE => String::from("i"),
+            im => format!("{im}i"),
+        };
+        match (complex.re.is_zero(), complex.im.is_sign_negative()) {
+            (true, true) => write!(fmt, "-{im}"),
+            (true, false) => write!(fmt, "{im}"),
+            (false, true) => write!(fmt, "{} - {im}", complex.re),
+            (false, false) => write!(fmt, "{} + {im}", complex.re),
+        }
+    }
+}
+
+impl Debug for Value {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Value::Real(re) => write!(fmt, "{re:?}"),
+            _ => write!(fmt, "{self}"),
+        }
+    }
+}
+
+/// `f64` сохраняется объектом `{"float": 0.5}`, чтобы при чтении не стать `Decimal`.
+mod float {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct FloatRepr {
+        float: f64,
+    }
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        FloatRepr { float: *value }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        FloatRepr::deserialize(deserializer).map(|repr| repr.float)
+    }
+}
@@ -1,25 +1,27 @@
-use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::traits::{GetElementByName, GetResult, RemoveElementIfMaxValue};
+use crate::{
+    traits::{GetElementByName, GetResult, RemoveElementIfMaxValue},
+    value::Value,
+};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Variable {
     pub name: String,
-    pub value: Decimal,
+    pub value: Value,
 }
 
 impl Variable {
-    pub fn new(name: String, value: Decimal) -> Self {
+    pub fn new(name: String, value: Value) -> Self {
         Variable { name, value }
     }
 }
 
-impl GetResult<Option<Decimal>> for Vec<Variable> {
-    fn get_result(&self, input: &str) -> Option<Decimal> {
+impl GetResult<Option<Value>> for Vec<Variable> {
+    fn get_result(&self, input: &str) -> Option<Value> {
         self.iter()
             .find(|variable| variable.name == input)
-            .map(|variable| variable.value)
+            .map(|variable| variable.value.clone())
     }
 }
 
@@ -1,4 +1,4 @@
-use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
@@ -11,6 +11,10 @@ pub struct UserConfig {
     output_line_history: u8,
     max_size_history: u8,
     max_number_variable: u8,
+    angle_unit: String,
+    number_mode: String,
+    digits: u32,
+    output_base: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -19,8 +23,15 @@ pub struct Commands {
     help: String,
     history: String,
     empty_input: String,
+    angle: String,
+    mode: String,
+    base: String,
 }
 
+const ANGLE_UNITS: [&str; 3] = ["degrees", "radians", "gradians"];
+const NUMBER_MODES: [&str; 4] = ["decimal", "rational", "float", "big"];
+const OUTPUT_BASES: [&str; 4] = ["decimal", "hex", "binary", "octal"];
+
 pub fn create_user_config(config_dir: &str, langs: &[String]) -> anyhow::Result<()> {
     let theme = ColorfulTheme::default();
     let mut config = create_default_config(langs);
@@ -46,10 +57,17 @@ fn create_default_config(langs: &[String]) -> UserConfig {
             help: "/help".to_string(),
             history: "/history".to_string(),
             empty_input: "".to_string(),
+            angle: "/angle".to_string(),
+            mode: "/mode".to_string(),
+            base: "/base".to_string(),
         },
         output_line_history: 10,
         max_size_history: 50,
         max_number_variable: 50,
+        angle_unit: ANGLE_UNITS[0].to_string(),
+        number_mode: NUMBER_MODES[0].to_string(),
+        digits: 50,
+        output_base: OUTPUT_BASES[0].to_string(),
     }
 }
 
@@ -89,6 +107,39 @@ fn customize_settings(config: &mut UserConfig, theme: &ColorfulTheme) -> anyhow:
         .interact()?
         .parse()?;
 
+    let angle_unit = Select::with_theme(theme)
+        .with_prompt("Angle unit for trigonometric functions")
+        .items(&ANGLE_UNITS)
+        .default(0)
+        .interact()?;
+    config.angle_unit = ANGLE_UNITS[angle_unit].to_string();
+
+    let number_mode = Select::with_theme(theme)
+        .with_prompt("Number mode: decimal numbers, exact fractions, fast f64 or big decimals")
+        .items(&NUMBER_MODES)
+        .default(0)
+        .interact()?;
+    config.number_mode = NUMBER_MODES[number_mode].to_string();
+
+    if config.number_mode == "big" {
+        config.digits = Input::with_theme(theme)
+            .with_prompt("Significant digits of big decimals")
+            .default(config.digits.to_string())
+            .validate_with(|input: &String| match input.parse::<u32>() {
+                Ok(digits) if digits > 0 => Ok(()),
+                _ => Err("Please enter a positive number"),
+            })
+            .interact()?
+            .parse()?;
+    }
+
+    let output_base = Select::with_theme(theme)
+        .with_prompt("Base in which integer results are shown")
+        .items(&OUTPUT_BASES)
+        .default(0)
+        .interact()?;
+    config.output_base = OUTPUT_BASES[output_base].to_string();
+
     println!("\nCustomizing commands:");
     config.commands.end = Input::with_theme(theme)
         .with_prompt("Command to end session")
@@ -105,6 +156,21 @@ fn customize_settings(config: &mut UserConfig, theme: &ColorfulTheme) -> anyhow:
         .default(config.commands.history.clone())
         .interact()?;
 
+    config.commands.angle = Input::with_theme(theme)
+        .with_prompt("Command to show or change angle unit")
+        .default(config.commands.angle.clone())
+        .interact()?;
+
+    config.commands.mode = Input::with_theme(theme)
+        .with_prompt("Command to show or change number mode")
+        .default(config.commands.mode.clone())
+        .interact()?;
+
+    config.commands.base = Input::with_theme(theme)
+        .with_prompt("Command to show or change output base")
+        .default(config.commands.base.clone())
+        .interact()?;
+
     Ok(())
 }
 